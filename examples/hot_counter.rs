@@ -0,0 +1,77 @@
+//! Hot model library for `DylibModel`: counts Up key presses and finishes on Escape.
+//!
+//! Build it with `cargo build --example hot_counter` and run application with
+//! `HOT_MODEL_LIBRARY` set to built library, e.g. `target/debug/examples/libhot_counter.so`.
+//! Change it and build again while application runs: new logic continues with saved count.
+
+#[path = "../src/hot_model_api.rs"]
+#[allow(dead_code)]
+mod hot_model_api;
+
+use hot_model_api::{InputState, WriteFn, API_VERSION};
+use std::ffi::c_void;
+use winit::event::VirtualKeyCode;
+
+struct Counter {
+    count: i32,
+}
+
+#[no_mangle]
+pub extern "C" fn hot_model_api_version() -> u32 {
+    API_VERSION
+}
+
+/// # Safety
+///
+/// `state` must point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn hot_model_create(state: *const u8, len: usize) -> *mut c_void {
+    let count = if len == 0 {
+        0
+    } else {
+        match bincode::deserialize(std::slice::from_raw_parts(state, len)) {
+            Ok(count) => count,
+            Err(_) => return std::ptr::null_mut(),
+        }
+    };
+    Box::into_raw(Box::new(Counter { count })) as *mut c_void
+}
+
+/// # Safety
+///
+/// `model` must be created by `hot_model_create` and not destroyed yet.
+#[no_mangle]
+pub unsafe extern "C" fn hot_model_destroy(model: *mut c_void) {
+    drop(Box::from_raw(model as *mut Counter));
+}
+
+/// # Safety
+///
+/// `model` must be alive, and `input_state` must point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn hot_model_update(
+    model: *mut c_void,
+    input_state: *const u8,
+    len: usize,
+) -> bool {
+    let counter = &mut *(model as *mut Counter);
+    let input_state: InputState =
+        match bincode::deserialize(std::slice::from_raw_parts(input_state, len)) {
+            Ok(input_state) => input_state,
+            Err(_) => return true,
+        };
+    if input_state.just_pressed(VirtualKeyCode::Up) {
+        counter.count += 1;
+    }
+    !input_state.just_pressed(VirtualKeyCode::Escape)
+}
+
+/// # Safety
+///
+/// `model` must be alive, and `write` must accept `out`.
+#[no_mangle]
+pub unsafe extern "C" fn hot_model_save(model: *const c_void, out: *mut c_void, write: WriteFn) {
+    let counter = &*(model as *const Counter);
+    let state = bincode::serialize(&counter.count).expect("can't encode count");
+    write(out, state.as_ptr(), state.len());
+}
@@ -0,0 +1,508 @@
+use crate::debug_ui::{Color, DrawCommand, DrawList, Rect, LINE_HEIGHT};
+use crate::error::settings::SettingsError;
+use crate::input::{Input, InputEvent};
+use crate::model::model_manager::{tick_period, Command, CommandSender};
+use crate::model::status::StatusWatch;
+use crate::model::Model;
+use slog::{info, warn, Logger};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use winit::event::{ElementState, VirtualKeyCode};
+
+/// Open or close console. Character, typed by toggle key press, is consumed along with it.
+const TOGGLE_KEY: VirtualKeyCode = VirtualKeyCode::Grave;
+/// Characters, which toggle key types, with and without shift.
+const TOGGLE_CHARACTERS: [char; 2] = ['`', '~'];
+/// File in settings directory, where entered lines are kept between sessions.
+const HISTORY_FILE: &str = "console_history";
+/// Directory in settings directory for `save` and `load` slots.
+const SAVES_DIR: &str = "saves";
+const HISTORY_LIMIT: usize = 100;
+const OUTPUT_LIMIT: usize = 100;
+/// Count of output lines shown above prompt.
+const VISIBLE_LINES: usize = 12;
+const CONSOLE_WIDTH: f64 = 640.0;
+const PADDING: f64 = 4.0;
+
+const BACKGROUND_COLOR: Color = [0.05, 0.05, 0.1, 0.85];
+const TEXT_COLOR: Color = [0.8, 0.9, 0.8, 1.0];
+const PROMPT_COLOR: Color = [1.0, 1.0, 1.0, 1.0];
+
+const COMMANDS: &[&str] = &[
+    "help", "pause", "run", "step", "rewind", "set", "get", "vars", "save", "load", "clear",
+];
+const HELP: &[&str] = &[
+    "pause | run           stop or resume model updating",
+    "step [n]              make n updates while paused",
+    "rewind [ticks]        return model to state ticks ago",
+    "set <var> <value>     change variable",
+    "get [var]             show variable, or all variables",
+    "vars                  list variables",
+    "save <slot>           save model snapshot to slot",
+    "load <slot>           load model snapshot from slot",
+    "clear                 clear console output",
+];
+/// Variable, handled by console itself rather than by model.
+const TICK_RATE_VARIABLE: &str = "tickrate";
+
+/// Lines, printed to console. Shared with commands, which run on model manager thread.
+type Output = Arc<Mutex<VecDeque<String>>>;
+
+/// Drop-down developer console, translating typed commands into model manager commands.
+pub struct Console<M> {
+    open: bool,
+    /// Toggle key was pressed, and character it types is not received yet.
+    toggled: bool,
+    line: String,
+    history: Vec<String>,
+    /// Index of history line, shown in prompt while browsing history.
+    browsing: Option<usize>,
+    output: Output,
+    /// Model variable names, refreshed every time console is opened.
+    variables: Arc<Mutex<Vec<&'static str>>>,
+    command_tx: CommandSender<M>,
+    status: StatusWatch,
+    settings_dir: PathBuf,
+    logger: Logger,
+}
+
+impl<M: Model + 'static> Console<M> {
+    pub fn new(
+        command_tx: CommandSender<M>,
+        status: StatusWatch,
+        settings_dir: PathBuf,
+        logger: Logger,
+    ) -> Self {
+        let history = load_history(&settings_dir.join(HISTORY_FILE)).unwrap_or_else(|e| {
+            info!(logger, "Console history is not loaded: {}", e);
+            Vec::new()
+        });
+        Self {
+            open: false,
+            toggled: false,
+            line: String::new(),
+            history,
+            browsing: None,
+            output: Arc::new(Mutex::new(VecDeque::new())),
+            variables: Arc::new(Mutex::new(Vec::new())),
+            command_tx,
+            status,
+            settings_dir,
+            logger,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Handles toggle key and, while console is open, typing.
+    /// Returns true if input is consumed, so it must not be passed to model.
+    /// Key releases and pointer input always go to model, so keys, held when console
+    /// is opened, don't stay pressed in model.
+    pub fn handle(&mut self, input: &Input) -> bool {
+        let toggled = std::mem::replace(&mut self.toggled, false);
+        match input.event() {
+            InputEvent::Keyboard {
+                key: TOGGLE_KEY,
+                state: ElementState::Pressed,
+            } => {
+                self.toggle();
+                self.toggled = true;
+                true
+            }
+            InputEvent::Symbol(c) if toggled && TOGGLE_CHARACTERS.contains(c) => true,
+            InputEvent::Text(text) if toggled && is_toggle_character(text) => true,
+            _ if !self.open => false,
+            InputEvent::Keyboard {
+                key,
+                state: ElementState::Pressed,
+            } => {
+                self.key_pressed(*key);
+                true
+            }
+            InputEvent::Symbol(c) => {
+                self.type_text(&c.to_string());
+                true
+            }
+            InputEvent::Text(text) | InputEvent::Paste(text) => {
+                self.type_text(text);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// The latest output line and prompt in one line, or None if console is closed.
+    pub fn summary(&self) -> Option<String> {
+        if !self.open {
+            return None;
+        }
+        let prompt = format!("> {}_", self.line);
+        match self.output.lock().unwrap().back() {
+            Some(last) => Some(format!("{} | {}", last, prompt)),
+            None => Some(prompt),
+        }
+    }
+
+    /// Builds console frame. Returns None if console is closed.
+    pub fn frame(&self) -> Option<DrawList> {
+        if !self.open {
+            return None;
+        }
+        let output = self.output.lock().unwrap();
+        let shown = output.len().min(VISIBLE_LINES);
+        let height = (VISIBLE_LINES + 1) as f64 * LINE_HEIGHT + 2.0 * PADDING;
+        let mut commands = vec![DrawCommand::Rect {
+            rect: Rect {
+                x: 0.0,
+                y: 0.0,
+                width: CONSOLE_WIDTH,
+                height,
+            },
+            color: BACKGROUND_COLOR,
+        }];
+        // Output is aligned to bottom, right above prompt.
+        let first_y = PADDING + (VISIBLE_LINES - shown) as f64 * LINE_HEIGHT;
+        for (i, line) in output.iter().skip(output.len() - shown).enumerate() {
+            commands.push(DrawCommand::Text {
+                position: (PADDING, first_y + i as f64 * LINE_HEIGHT),
+                text: line.clone(),
+                color: TEXT_COLOR,
+            });
+        }
+        commands.push(DrawCommand::Text {
+            position: (PADDING, height - PADDING - LINE_HEIGHT),
+            text: format!("> {}_", self.line),
+            color: PROMPT_COLOR,
+        });
+        Some(DrawList { commands })
+    }
+
+    fn toggle(&mut self) {
+        self.open = !self.open;
+        if self.open {
+            self.refresh_variables();
+        }
+    }
+
+    fn type_text(&mut self, text: &str) {
+        self.line.extend(text.chars().filter(|c| !c.is_control()));
+        self.browsing = None;
+    }
+
+    fn key_pressed(&mut self, key: VirtualKeyCode) {
+        match key {
+            VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter => {
+                let line = std::mem::take(&mut self.line);
+                self.browsing = None;
+                self.submit(line);
+            }
+            VirtualKeyCode::Back => {
+                self.line.pop();
+            }
+            VirtualKeyCode::Escape => self.open = false,
+            VirtualKeyCode::Tab => self.complete(),
+            VirtualKeyCode::Up => self.browse(true),
+            VirtualKeyCode::Down => self.browse(false),
+            _ => {}
+        }
+    }
+
+    /// Shows older or newer history line in prompt.
+    fn browse(&mut self, older: bool) {
+        if self.history.is_empty() {
+            return;
+        }
+        let last = self.history.len() - 1;
+        self.browsing = match (self.browsing, older) {
+            (None, true) => Some(last),
+            (None, false) => None,
+            (Some(i), true) => Some(i.saturating_sub(1)),
+            (Some(i), false) if i < last => Some(i + 1),
+            (Some(_), false) => None,
+        };
+        self.line = match self.browsing {
+            Some(i) => self.history[i].clone(),
+            None => String::new(),
+        };
+    }
+
+    /// Completes last word with command or variable name. If several names fit,
+    /// completes their common prefix and prints them.
+    fn complete(&mut self) {
+        let words: Vec<&str> = self.line.split(' ').collect();
+        let (word, candidates): (&str, Vec<&str>) = match words.as_slice() {
+            [word] => (word, COMMANDS.to_vec()),
+            ["set", word] | ["get", word] => {
+                let mut names = self.variables.lock().unwrap().clone();
+                names.push(TICK_RATE_VARIABLE);
+                (word, names)
+            }
+            _ => return,
+        };
+        let fitting: Vec<&str> = candidates
+            .into_iter()
+            .filter(|c| c.starts_with(word))
+            .collect();
+        let completion = match fitting.as_slice() {
+            [] => return,
+            [single] => format!("{} ", single),
+            several => {
+                self.print(several.join(" "));
+                common_prefix(several).to_string()
+            }
+        };
+        let keep = self.line.len() - word.len();
+        self.line.truncate(keep);
+        self.line.push_str(&completion);
+    }
+
+    fn submit(&mut self, line: String) {
+        let line = line.trim().to_string();
+        if line.is_empty() {
+            return;
+        }
+        self.print(format!("> {}", line));
+        if self.history.last() != Some(&line) {
+            self.history.push(line.clone());
+            let extra = self.history.len().saturating_sub(HISTORY_LIMIT);
+            self.history.drain(..extra);
+            let path = self.settings_dir.join(HISTORY_FILE);
+            save_history(&path, &self.history).unwrap_or_else(|e| {
+                warn!(self.logger, "Can't save console history: {}", e);
+            });
+        }
+        if let Err(message) = self.execute(&line) {
+            self.print(message);
+        }
+    }
+
+    fn execute(&mut self, line: &str) -> Result<(), String> {
+        info!(self.logger, "Console command: {}", line);
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            ["help"] => HELP.iter().for_each(|line| self.print(line.to_string())),
+            ["pause"] => self.send(Command::Stop),
+            ["run"] => self.send(Command::Run),
+            ["step"] => self.send(Command::Step(1)),
+            ["step", n] => self.send(Command::Step(parse(n)?)),
+            ["rewind"] => self.send(Command::Rewind(1)),
+            ["rewind", ticks] => self.send(Command::Rewind(parse(ticks)?)),
+            ["set", name, value] if *name == TICK_RATE_VARIABLE => {
+                let rate: f64 = parse(value)?;
+                // Zero rate means as fast as possible.
+                let rate = if rate == 0.0 { None } else { Some(rate) };
+                if let Some(rate) = rate {
+                    tick_period(rate).map_err(|e| e.to_string())?;
+                }
+                self.send(Command::SetTickRate(rate));
+            }
+            ["set", name, value] => {
+                let (name, value) = (name.to_string(), value.to_string());
+                let output = self.output.clone();
+                self.send(Command::Apply(Box::new(move |model: &mut M| {
+                    let line = match model.set_variable(&name, &value) {
+                        Ok(()) => format!("{} = {}", name, value),
+                        Err(message) => message,
+                    };
+                    push_line(&output, line);
+                })));
+            }
+            ["get", name] if *name == TICK_RATE_VARIABLE => {
+                let line = match self.status.get().tick_rate {
+                    Some(rate) => format!("{} = {}", TICK_RATE_VARIABLE, rate),
+                    None => format!("{} = 0 (unlimited)", TICK_RATE_VARIABLE),
+                };
+                self.print(line);
+            }
+            ["get"] | ["vars"] => self.print_variables(None),
+            ["get", name] => self.print_variables(Some(name.to_string())),
+            ["save", slot] => {
+                let path = self.slot_path(slot)?;
+                self.send(Command::Save(path));
+            }
+            ["load", slot] => {
+                let path = self.slot_path(slot)?;
+                self.send(Command::Load(path));
+            }
+            ["clear"] => self.output.lock().unwrap().clear(),
+            [command, ..] if COMMANDS.contains(command) => {
+                return Err("Wrong arguments. Type help for usage".to_string());
+            }
+            [command, ..] => return Err(format!("Unknown command: {}", command)),
+            [] => {}
+        }
+        Ok(())
+    }
+
+    /// Prints model variables, or only one with given name.
+    fn print_variables(&self, name: Option<String>) {
+        let output = self.output.clone();
+        self.send(Command::Query(Box::new(move |model: &M| {
+            let variables = model.variables();
+            let shown: Vec<_> = variables
+                .iter()
+                .filter(|(n, _)| name.as_deref().map_or(true, |name| *n == name))
+                .collect();
+            if shown.is_empty() {
+                if let Some(name) = name {
+                    push_line(&output, format!("Unknown variable: {}", name));
+                }
+            }
+            for (name, value) in shown {
+                push_line(&output, format!("{} = {}", name, value));
+            }
+        })));
+    }
+
+    /// Asks model for its variable names, used by autocomplete.
+    fn refresh_variables(&self) {
+        let variables = self.variables.clone();
+        self.send(Command::Query(Box::new(move |model: &M| {
+            let names = model.variables().into_iter().map(|(name, _)| name);
+            *variables.lock().unwrap() = names.collect();
+        })));
+    }
+
+    /// Path of snapshot slot. Slot names can't leave saves directory.
+    fn slot_path(&self, slot: &str) -> Result<PathBuf, String> {
+        if !slot
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err(format!("Wrong slot name: {}", slot));
+        }
+        let dir = self.settings_dir.join(SAVES_DIR);
+        std::fs::create_dir_all(&dir).map_err(|e| format!("Can't create {:?}: {}", dir, e))?;
+        Ok(dir.join(slot))
+    }
+
+    fn send(&self, command: Command<M>) {
+        self.command_tx.send(command).unwrap_or_else(|e| {
+            warn!(self.logger, "Can't send console command: {}", e);
+        });
+    }
+
+    fn print(&self, line: String) {
+        push_line(&self.output, line);
+    }
+}
+
+fn is_toggle_character(text: &str) -> bool {
+    let mut chars = text.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => TOGGLE_CHARACTERS.contains(&c),
+        _ => false,
+    }
+}
+
+fn push_line(output: &Output, line: String) {
+    let mut output = output.lock().unwrap();
+    output.push_back(line);
+    while output.len() > OUTPUT_LIMIT {
+        output.pop_front();
+    }
+}
+
+fn parse<T: std::str::FromStr>(word: &str) -> Result<T, String> {
+    word.parse()
+        .map_err(|_| format!("Can't parse number: {}", word))
+}
+
+fn common_prefix<'a>(words: &[&'a str]) -> &'a str {
+    let first = words[0];
+    let len = words[1..].iter().fold(first.len(), |len, word| {
+        first[..len]
+            .char_indices()
+            .zip(word.chars())
+            .find(|((_, a), b)| a != b)
+            .map_or(len.min(word.len()), |((i, _), _)| i)
+    });
+    &first[..len]
+}
+
+fn load_history(path: &Path) -> Result<Vec<String>, SettingsError> {
+    let text = std::fs::read_to_string(path)?;
+    Ok(text.lines().map(str::to_string).collect())
+}
+
+fn save_history(path: &Path, history: &[String]) -> Result<(), SettingsError> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut text = history.join("\n");
+    text.push('\n');
+    std::fs::write(path, text)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::counter_model::CounterModel;
+    use crate::model::model_manager::ModelManager;
+    use slog::{o, Discard};
+
+    fn console() -> Console<CounterModel> {
+        let logger = Logger::root(Discard, o!());
+        let (_, bonds) = ModelManager::new(Arc::new(CounterModel::new()), logger.clone());
+        let settings_dir = std::env::temp_dir().join("console_test_settings");
+        Console::new(bonds.command_tx, bonds.status, settings_dir, logger)
+    }
+
+    fn key(key: VirtualKeyCode, state: ElementState) -> Input {
+        (None, InputEvent::Keyboard { key, state }).into()
+    }
+
+    fn symbol(c: char) -> Input {
+        (None, InputEvent::Symbol(c)).into()
+    }
+
+    #[test]
+    fn only_toggle_character_is_dropped() {
+        let mut console = console();
+        assert!(console.handle(&key(TOGGLE_KEY, ElementState::Pressed)));
+        assert!(console.handle(&symbol('`')));
+        assert!(console.is_open());
+        for c in "a~`".chars() {
+            assert!(console.handle(&symbol(c)));
+        }
+        assert_eq!(console.line, "a~`");
+
+        assert!(console.handle(&key(TOGGLE_KEY, ElementState::Pressed)));
+        assert!(console.handle(&symbol('~')));
+        assert!(!console.is_open());
+        assert!(!console.handle(&symbol('`')));
+    }
+
+    #[test]
+    fn key_releases_go_to_model() {
+        let mut console = console();
+        console.handle(&key(TOGGLE_KEY, ElementState::Pressed));
+        assert!(console.handle(&key(VirtualKeyCode::W, ElementState::Pressed)));
+        assert!(!console.handle(&key(VirtualKeyCode::W, ElementState::Released)));
+        assert!(!console.handle(&key(TOGGLE_KEY, ElementState::Released)));
+    }
+
+    #[test]
+    fn summary_shows_latest_output_and_prompt() {
+        let mut console = console();
+        assert_eq!(console.summary(), None);
+        console.handle(&key(TOGGLE_KEY, ElementState::Pressed));
+        assert_eq!(console.summary().unwrap(), "> _");
+        console.print("done".to_string());
+        console.handle(&symbol('x'));
+        assert_eq!(console.summary().unwrap(), "done | > x_");
+    }
+
+    #[test]
+    fn unsupported_tick_rate_is_rejected() {
+        let mut console = console();
+        assert!(console.execute("set tickrate -5").is_err());
+        assert!(console.execute("set tickrate 1e12").is_err());
+        assert!(console.execute("set tickrate 0").is_ok());
+        assert!(console.execute("set tickrate 30").is_ok());
+    }
+}
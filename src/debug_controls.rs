@@ -0,0 +1,241 @@
+use crate::input::{Input, InputEvent};
+use crate::model::model_manager::{Command, CommandSender};
+use crate::model::status::StatusWatch;
+use slog::{info, warn, Logger};
+use std::fmt;
+use winit::event::{ElementState, VirtualKeyCode};
+
+/// Pause or resume model updating.
+const PAUSE_KEY: VirtualKeyCode = VirtualKeyCode::F6;
+/// Make one model update while paused.
+const STEP_KEY: VirtualKeyCode = VirtualKeyCode::F7;
+/// Make `STEP_COUNT` model updates while paused.
+const MULTI_STEP_KEY: VirtualKeyCode = VirtualKeyCode::F8;
+/// Switch to next speed.
+const SPEED_KEY: VirtualKeyCode = VirtualKeyCode::F9;
+/// Rewind model for `REWIND_SECONDS`.
+const REWIND_KEY: VirtualKeyCode = VirtualKeyCode::F10;
+
+const STEP_COUNT: u32 = 10;
+const REWIND_SECONDS: f64 = 1.0;
+/// Speeds, which differ less, are the same. Tick rate is rounded by model manager.
+const SPEED_TOLERANCE: f64 = 1e-3;
+
+/// Model updating speed relative to normal tick rate, switched by hotkey.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Speed {
+    Normal,
+    Half,
+    Quarter,
+}
+
+impl Speed {
+    pub fn factor(self) -> f64 {
+        match self {
+            Speed::Normal => 1.0,
+            Speed::Half => 0.5,
+            Speed::Quarter => 0.25,
+        }
+    }
+
+    /// The next slower speed after `factor`, or normal one after the slowest.
+    /// Any speed, set not by hotkey, is followed by the nearest slower one.
+    fn after(factor: Option<f64>) -> Self {
+        let factor = factor.unwrap_or(f64::INFINITY);
+        [Speed::Normal, Speed::Half, Speed::Quarter]
+            .iter()
+            .copied()
+            .find(|speed| speed.factor() < factor - SPEED_TOLERANCE)
+            .unwrap_or(Speed::Normal)
+    }
+}
+
+/// Current debug mode of model manager.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DebugMode {
+    /// Model manager is stopped, whoever stopped it.
+    pub paused: bool,
+    /// Tick rate of model manager relative to normal one, whoever set it.
+    /// None means as fast as possible.
+    pub speed: Option<f64>,
+}
+
+impl DebugMode {
+    /// Model is running at normal speed, so there is nothing to show.
+    pub fn is_normal(&self) -> bool {
+        !self.paused
+            && self
+                .speed
+                .is_some_and(|speed| (speed - 1.0).abs() < SPEED_TOLERANCE)
+    }
+}
+
+impl fmt::Display for DebugMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.paused, self.speed) {
+            (true, _) => write!(f, "paused"),
+            (false, None) => write!(f, "unlimited speed"),
+            (false, Some(_)) if self.is_normal() => write!(f, "running"),
+            (false, Some(speed)) if speed < 1.0 => write!(f, "slow motion {:.2}x", speed),
+            (false, Some(speed)) => write!(f, "fast motion {:.2}x", speed),
+        }
+    }
+}
+
+/// Translates debug hotkeys into model manager commands.
+pub struct DebugControls<M> {
+    command_tx: CommandSender<M>,
+    status: StatusWatch,
+    /// Normal tick rate.
+    tick_rate: f64,
+    logger: Logger,
+}
+
+impl<M> DebugControls<M> {
+    pub fn new(
+        command_tx: CommandSender<M>,
+        status: StatusWatch,
+        tick_rate: f64,
+        logger: Logger,
+    ) -> Self {
+        Self {
+            command_tx,
+            status,
+            tick_rate,
+            logger,
+        }
+    }
+
+    /// Mode is read from model manager status, so it's the same, whatever changed it.
+    /// It changes, when model manager interprets command, not when hotkey is pressed.
+    pub fn mode(&self) -> DebugMode {
+        let status = self.status.get();
+        DebugMode {
+            paused: status.is_paused(),
+            speed: status.tick_rate.map(|rate| rate / self.tick_rate),
+        }
+    }
+
+    /// Handles debug hotkeys.
+    /// Returns true if input is a debug hotkey, so it must not be passed to model.
+    pub fn handle(&mut self, input: &Input) -> bool {
+        let (key, state) = match input.event() {
+            InputEvent::Keyboard { key, state } => (*key, *state),
+            _ => return false,
+        };
+        if ![PAUSE_KEY, STEP_KEY, MULTI_STEP_KEY, SPEED_KEY, REWIND_KEY].contains(&key) {
+            return false;
+        }
+        if state == ElementState::Released {
+            return true;
+        }
+
+        let mut mode = self.mode();
+        match key {
+            PAUSE_KEY if mode.paused => {
+                mode.paused = false;
+                self.send(Command::Run);
+            }
+            PAUSE_KEY => {
+                mode.paused = true;
+                self.send(Command::Stop);
+            }
+            STEP_KEY if mode.paused => self.send(Command::Step(1)),
+            MULTI_STEP_KEY if mode.paused => self.send(Command::Step(STEP_COUNT)),
+            STEP_KEY | MULTI_STEP_KEY => info!(self.logger, "Stepping is available only in pause"),
+            REWIND_KEY => {
+                let ticks = (self.tick_rate * REWIND_SECONDS) as u64;
+                self.send(Command::Rewind(ticks));
+            }
+            _ => {
+                let speed = Speed::after(mode.speed);
+                mode.speed = Some(speed.factor());
+                let tick_rate = self.tick_rate * speed.factor();
+                self.send(Command::SetTickRate(Some(tick_rate)));
+            }
+        }
+        if key == PAUSE_KEY || key == SPEED_KEY {
+            info!(self.logger, "Debug mode: {}", mode);
+        }
+        true
+    }
+
+    fn send(&self, command: Command<M>) {
+        info!(self.logger, "Debug command: {:?}", command);
+        self.command_tx.send(command).unwrap_or_else(|e| {
+            warn!(self.logger, "Can't send debug command: {}", e);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::counter_model::CounterModel;
+    use crate::model::model_manager::ModelManager;
+    use slog::{o, Discard};
+    use std::sync::Arc;
+
+    #[test]
+    fn pause_follows_model_manager() {
+        let logger = Logger::root(Discard, o!());
+        let (mut manager, bonds) = ModelManager::new(Arc::new(CounterModel::new()), logger.clone());
+        let mut controls = DebugControls::new(bonds.command_tx.clone(), bonds.status, 60.0, logger);
+        manager.run_once();
+        assert!(controls.mode().paused);
+
+        let pause = (
+            None,
+            InputEvent::Keyboard {
+                key: PAUSE_KEY,
+                state: ElementState::Pressed,
+            },
+        );
+        assert!(controls.handle(&pause.into()));
+        manager.run_once();
+        assert!(!controls.mode().paused);
+
+        // Paused not by hotkey, e.g. by debug overlay.
+        bonds.command_tx.send(Command::Stop).unwrap();
+        manager.run_once();
+        assert!(controls.mode().paused);
+    }
+
+    #[test]
+    fn speed_follows_model_manager() {
+        let logger = Logger::root(Discard, o!());
+        let (mut manager, bonds) = ModelManager::new(Arc::new(CounterModel::new()), logger.clone());
+        let mut controls = DebugControls::new(bonds.command_tx.clone(), bonds.status, 60.0, logger);
+        let switch = |controls: &mut DebugControls<CounterModel>, manager: &mut ModelManager<_>| {
+            let speed = (
+                None,
+                InputEvent::Keyboard {
+                    key: SPEED_KEY,
+                    state: ElementState::Pressed,
+                },
+            );
+            controls.handle(&speed.into());
+            manager.run_once();
+            controls.mode().speed.unwrap()
+        };
+        // Unlimited speed is followed by normal one.
+        assert!((switch(&mut controls, &mut manager) - 1.0).abs() < SPEED_TOLERANCE);
+        assert!((switch(&mut controls, &mut manager) - 0.5).abs() < SPEED_TOLERANCE);
+
+        // Tick rate set not by hotkey, e.g. by console.
+        bonds
+            .command_tx
+            .send(Command::SetTickRate(Some(45.0)))
+            .unwrap();
+        manager.run_once();
+        assert!((controls.mode().speed.unwrap() - 0.75).abs() < SPEED_TOLERANCE);
+        let running = DebugMode {
+            paused: false,
+            ..controls.mode()
+        };
+        assert_eq!(running.to_string(), "slow motion 0.75x");
+        assert!((switch(&mut controls, &mut manager) - 0.5).abs() < SPEED_TOLERANCE);
+        assert!((switch(&mut controls, &mut manager) - 0.25).abs() < SPEED_TOLERANCE);
+        assert!((switch(&mut controls, &mut manager) - 1.0).abs() < SPEED_TOLERANCE);
+    }
+}
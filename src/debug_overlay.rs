@@ -0,0 +1,247 @@
+use crate::debug_ui::{DrawList, Ui};
+use crate::input::{Input, InputEvent};
+use crate::log_buffer::LogBuffer;
+use crate::model::model_manager::{Command, CommandSender};
+use crate::model::registry::ModelId;
+use crate::model::status::StatusWatch;
+use crate::presenter::DRAWS_DEBUG_UI;
+use slog::{info, warn, Logger};
+use std::time::{Duration, Instant};
+use winit::event::{ElementState, VirtualKeyCode};
+use winit::window::WindowId;
+
+/// Show or hide overlay.
+const TOGGLE_KEY: VirtualKeyCode = VirtualKeyCode::F3;
+/// Top left corner of overlay panel in window.
+const ORIGIN: (f64, f64) = (8.0, 8.0);
+/// FPS and tick rate are averaged over this period.
+const MEASURE_PERIOD: Duration = Duration::from_secs(1);
+const TICK_RATE_RANGE: (f64, f64) = (1.0, 240.0);
+const LOG_LINES: usize = 8;
+
+/// Frames and ticks counted since measurement start.
+struct Rates {
+    since: Instant,
+    frames: u32,
+    tick: u64,
+    fps: f64,
+    tick_rate: f64,
+}
+
+/// Debug overlay, drawn by presenter on top of the main window frame.
+/// Shows model manager state and recent log lines, and has widgets sending commands to it.
+/// While presenter can't draw it, its status is shown in window title using `summary`.
+pub struct DebugOverlay<M> {
+    window_id: WindowId,
+    visible: bool,
+    /// Status lines of the latest frame.
+    status_lines: Vec<String>,
+    ui: Ui,
+    command_tx: CommandSender<M>,
+    status: StatusWatch,
+    log: LogBuffer,
+    /// Value of tick rate slider.
+    tick_rate: f64,
+    rates: Rates,
+    logger: Logger,
+}
+
+impl<M> DebugOverlay<M> {
+    pub fn new(
+        window_id: WindowId,
+        command_tx: CommandSender<M>,
+        status: StatusWatch,
+        log: LogBuffer,
+        tick_rate: f64,
+        logger: Logger,
+    ) -> Self {
+        let tick = status.get().tick;
+        Self {
+            window_id,
+            visible: false,
+            status_lines: Vec::new(),
+            ui: Ui::new(),
+            command_tx,
+            status,
+            log,
+            tick_rate,
+            rates: Rates {
+                since: Instant::now(),
+                frames: 0,
+                tick,
+                fps: 0.0,
+                tick_rate: 0.0,
+            },
+            logger,
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Handles toggle hotkey and pointer input over overlay. Pointer input goes to model,
+    /// while presenter can't draw overlay widgets.
+    /// Returns true if input is consumed, so it must not be passed to model.
+    pub fn handle(&mut self, input: &Input) -> bool {
+        if let InputEvent::Keyboard {
+            key: TOGGLE_KEY,
+            state,
+        } = input.event()
+        {
+            if *state == ElementState::Pressed {
+                self.visible = !self.visible;
+                info!(self.logger, "Debug overlay visible: {}", self.visible);
+            }
+            return true;
+        }
+        if !DRAWS_DEBUG_UI || !self.visible || input.window_id() != Some(self.window_id) {
+            return false;
+        }
+        self.ui.handle(input)
+    }
+
+    /// Status lines of the latest frame in one line, or None if overlay is hidden.
+    pub fn summary(&self) -> Option<String> {
+        if !self.visible {
+            return None;
+        }
+        Some(self.status_lines.join(" | "))
+    }
+
+    /// Builds overlay frame. `queued` is count of inputs, waiting to be taken by every model.
+    /// Returns None if overlay is hidden.
+    pub fn frame(&mut self, now: Instant, queued: &[(ModelId, usize)]) -> Option<DrawList> {
+        if !self.visible {
+            return None;
+        }
+        let status = self.status.get();
+        self.measure(now, status.tick);
+
+        let target = match status.tick_rate {
+            Some(rate) => format!("{:.1}", rate),
+            None => "unlimited".to_string(),
+        };
+        let mut lines = vec![
+            format!("FPS: {:.0}", self.rates.fps),
+            format!(
+                "Ticks: {:.1}/s (target {}), tick {}",
+                self.rates.tick_rate, target, status.tick
+            ),
+            format!("Model manager: {:?}", status.state),
+        ];
+        for (id, count) in queued {
+            lines.push(format!("Queued input of {}: {}", id, count));
+        }
+        self.status_lines = lines;
+
+        let ui = &mut self.ui;
+        ui.begin(ORIGIN);
+        for line in &self.status_lines {
+            ui.label(line);
+        }
+
+        let mut commands = Vec::new();
+        let mut paused = status.is_paused();
+        if ui.checkbox("Paused", &mut paused) {
+            commands.push(if paused { Command::Stop } else { Command::Run });
+        }
+        if ui.button("Step") {
+            commands.push(Command::Step(1));
+        }
+        if ui.slider("Tick rate", &mut self.tick_rate, TICK_RATE_RANGE) {
+            commands.push(Command::SetTickRate(Some(self.tick_rate)));
+        }
+
+        let lines = self.log.lines();
+        for line in &lines[lines.len().saturating_sub(LOG_LINES)..] {
+            ui.label(line);
+        }
+        let draw_list = ui.end();
+
+        for command in commands {
+            self.send(command);
+        }
+        Some(draw_list)
+    }
+
+    /// Counts frame and updates FPS and tick rate when measure period is over.
+    fn measure(&mut self, now: Instant, tick: u64) {
+        let rates = &mut self.rates;
+        rates.frames += 1;
+        let elapsed = now.saturating_duration_since(rates.since);
+        if elapsed < MEASURE_PERIOD {
+            return;
+        }
+        let seconds = elapsed.as_secs_f64();
+        rates.fps = rates.frames as f64 / seconds;
+        // Tick goes back on rewind or model reset.
+        rates.tick_rate = tick.saturating_sub(rates.tick) as f64 / seconds;
+        rates.since = now;
+        rates.frames = 0;
+        rates.tick = tick;
+    }
+
+    fn send(&self, command: Command<M>) {
+        info!(self.logger, "Debug overlay command: {:?}", command);
+        self.command_tx.send(command).unwrap_or_else(|e| {
+            warn!(self.logger, "Can't send debug overlay command: {}", e);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::counter_model::CounterModel;
+    use crate::model::model_manager::ModelManager;
+    use slog::{o, Discard};
+    use std::sync::Arc;
+    use winit::event::MouseButton;
+
+    fn overlay() -> DebugOverlay<CounterModel> {
+        let logger = Logger::root(Discard, o!());
+        let (_, bonds) = ModelManager::new(Arc::new(CounterModel::new()), logger.clone());
+        // SAFETY: dummy id is only compared with itself.
+        let window_id = unsafe { WindowId::dummy() };
+        let log = LogBuffer::new(LOG_LINES);
+        DebugOverlay::new(window_id, bonds.command_tx, bonds.status, log, 60.0, logger)
+    }
+
+    fn input(overlay: &DebugOverlay<CounterModel>, event: InputEvent) -> Input {
+        let input: Input = (None, event).into();
+        input.in_window(Some(overlay.window_id))
+    }
+
+    #[test]
+    fn toggle_key_shows_status_summary() {
+        let mut overlay = overlay();
+        let toggle = InputEvent::Keyboard {
+            key: TOGGLE_KEY,
+            state: ElementState::Pressed,
+        };
+        assert!(overlay.handle(&input(&overlay, toggle.clone())));
+        assert!(overlay.is_visible());
+        overlay.frame(Instant::now(), &[(ModelId("counter"), 3)]);
+        let summary = overlay.summary().unwrap();
+        assert!(summary.contains("tick 0"));
+        assert!(summary.contains("Queued input of counter: 3"));
+
+        overlay.handle(&input(&overlay, toggle));
+        assert!(overlay.summary().is_none());
+        assert!(overlay.frame(Instant::now(), &[]).is_none());
+    }
+
+    #[test]
+    fn undrawn_widgets_do_not_take_pointer() {
+        let mut overlay = overlay();
+        overlay.visible = true;
+        overlay.frame(Instant::now(), &[]);
+        let over_panel = InputEvent::CursorMove {
+            position: (ORIGIN.0 + 1.0, ORIGIN.1 + 1.0),
+        };
+        overlay.handle(&input(&overlay, over_panel));
+        let click = (MouseButton::Left, ElementState::Pressed).into();
+        assert_eq!(overlay.handle(&input(&overlay, click)), DRAWS_DEBUG_UI);
+    }
+}
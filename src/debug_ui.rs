@@ -0,0 +1,277 @@
+use crate::input::{Input, InputEvent};
+use winit::event::{ElementState, MouseButton};
+
+/// Width of glyph of fixed width debug font, in pixels.
+pub const CHAR_WIDTH: f64 = 8.0;
+pub const LINE_HEIGHT: f64 = 16.0;
+const PADDING: f64 = 4.0;
+const SLIDER_WIDTH: f64 = 160.0;
+
+/// RGBA color, components from 0.0 to 1.0.
+pub type Color = [f32; 4];
+
+const PANEL_COLOR: Color = [0.0, 0.0, 0.0, 0.7];
+const TEXT_COLOR: Color = [1.0, 1.0, 1.0, 1.0];
+const WIDGET_COLOR: Color = [0.3, 0.3, 0.3, 1.0];
+const HOT_COLOR: Color = [0.45, 0.45, 0.45, 1.0];
+const ACTIVE_COLOR: Color = [0.2, 0.5, 0.9, 1.0];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Rect {
+    pub fn contains(&self, point: (f64, f64)) -> bool {
+        point.0 >= self.x
+            && point.0 < self.x + self.width
+            && point.1 >= self.y
+            && point.1 < self.y + self.height
+    }
+}
+
+/// Primitive, that presenter draws on top of frame, in window pixel coordinates.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DrawCommand {
+    Rect {
+        rect: Rect,
+        color: Color,
+    },
+    /// Single line of text in fixed width debug font. `position` is top left corner.
+    Text {
+        position: (f64, f64),
+        text: String,
+        color: Color,
+    },
+}
+
+/// Draw commands of one UI frame, in drawing order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DrawList {
+    pub commands: Vec<DrawCommand>,
+}
+
+/// Identifier of widget: its index in frame. Stable while UI layout doesn't change.
+type WidgetId = usize;
+
+/// Immediate mode UI: widgets are declared every frame and report interaction immediately.
+/// Widgets are laid out top down in single column panel.
+#[derive(Default)]
+pub struct Ui {
+    pointer: Option<(f64, f64)>,
+    pointer_down: bool,
+    /// Pointer was pressed since previous frame.
+    pressed: bool,
+    /// Pointer was released since previous frame.
+    released: bool,
+    /// Widget, pointer was pressed on and is still held.
+    active: Option<WidgetId>,
+    /// Panel area of previous frame, used to tell if pointer input belongs to UI.
+    panel: Option<Rect>,
+
+    origin: (f64, f64),
+    next_y: f64,
+    width: f64,
+    next_id: WidgetId,
+    draw: DrawList,
+}
+
+impl Ui {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Tracks pointer. Returns true if input is captured by UI, so it must not be passed to model.
+    pub fn handle(&mut self, input: &Input) -> bool {
+        match input.event() {
+            InputEvent::CursorMove { position } => {
+                self.pointer = Some(*position);
+                self.active.is_some()
+            }
+            InputEvent::MouseButton {
+                button: MouseButton::Left,
+                state,
+            } => {
+                let over = self.is_over_panel();
+                match state {
+                    ElementState::Pressed if over => {
+                        self.pointer_down = true;
+                        self.pressed = true;
+                        true
+                    }
+                    ElementState::Pressed => false,
+                    ElementState::Released => {
+                        let captured = self.pointer_down;
+                        self.pointer_down = false;
+                        self.released = true;
+                        captured
+                    }
+                }
+            }
+            InputEvent::MouseButton { .. } | InputEvent::Scroll { .. } => self.is_over_panel(),
+            _ => false,
+        }
+    }
+
+    /// Pointer is over panel, drawn in previous frame.
+    pub fn is_over_panel(&self) -> bool {
+        match (self.panel, self.pointer) {
+            (Some(panel), Some(pointer)) => panel.contains(pointer),
+            _ => false,
+        }
+    }
+
+    /// Starts new frame with panel at `origin`.
+    pub fn begin(&mut self, origin: (f64, f64)) {
+        self.origin = origin;
+        self.next_y = origin.1 + PADDING;
+        self.width = 0.0;
+        self.next_id = 0;
+        self.draw = DrawList::default();
+    }
+
+    /// Finishes frame and returns its draw commands.
+    pub fn end(&mut self) -> DrawList {
+        let panel = Rect {
+            x: self.origin.0,
+            y: self.origin.1,
+            width: self.width + 2.0 * PADDING,
+            height: self.next_y - self.origin.1,
+        };
+        self.panel = Some(panel);
+        // Panel size is known only now, so background goes under already declared widgets.
+        self.draw.commands.insert(
+            0,
+            DrawCommand::Rect {
+                rect: panel,
+                color: PANEL_COLOR,
+            },
+        );
+        if self.released {
+            self.active = None;
+        }
+        self.pressed = false;
+        self.released = false;
+        std::mem::take(&mut self.draw)
+    }
+
+    pub fn label(&mut self, text: &str) {
+        let rect = self.allocate(text_width(text));
+        self.text((rect.x, rect.y), text);
+    }
+
+    /// Returns true if button was clicked.
+    pub fn button(&mut self, text: &str) -> bool {
+        let rect = self.allocate(text_width(text) + 2.0 * PADDING);
+        let (id, hot) = self.interact(rect);
+        let clicked = self.released && hot && self.active == Some(id);
+        let color = self.widget_color(id, hot);
+        self.rect(rect, color);
+        self.text((rect.x + PADDING, rect.y), text);
+        clicked
+    }
+
+    /// Returns true if `checked` was toggled.
+    pub fn checkbox(&mut self, text: &str, checked: &mut bool) -> bool {
+        let rect = self.allocate(LINE_HEIGHT + PADDING + text_width(text));
+        let (id, hot) = self.interact(rect);
+        let toggled = self.released && hot && self.active == Some(id);
+        if toggled {
+            *checked = !*checked;
+        }
+        let tick = Rect {
+            width: LINE_HEIGHT,
+            ..rect
+        };
+        let color = if *checked {
+            ACTIVE_COLOR
+        } else {
+            self.widget_color(id, hot)
+        };
+        self.rect(tick, color);
+        self.text((rect.x + LINE_HEIGHT + PADDING, rect.y), text);
+        toggled
+    }
+
+    /// Slider, dragged to change `value` inside `range`. Returns true if value was changed.
+    pub fn slider(&mut self, text: &str, value: &mut f64, range: (f64, f64)) -> bool {
+        let label = format!("{}: {:.1}", text, value);
+        let rect = self.allocate(SLIDER_WIDTH.max(text_width(&label)));
+        let (id, hot) = self.interact(rect);
+        let mut changed = false;
+        if self.active == Some(id) {
+            if let Some(pointer) = self.pointer {
+                let part = ((pointer.0 - rect.x) / rect.width).clamp(0.0, 1.0);
+                let new_value = range.0 + part * (range.1 - range.0);
+                changed = new_value != *value;
+                *value = new_value;
+            }
+        }
+        let part = (*value - range.0) / (range.1 - range.0);
+        let color = self.widget_color(id, hot);
+        self.rect(rect, color);
+        self.rect(
+            Rect {
+                width: rect.width * part.clamp(0.0, 1.0),
+                ..rect
+            },
+            ACTIVE_COLOR,
+        );
+        self.text((rect.x + PADDING, rect.y), &label);
+        changed
+    }
+
+    /// Takes place for next widget of given width.
+    fn allocate(&mut self, width: f64) -> Rect {
+        let rect = Rect {
+            x: self.origin.0 + PADDING,
+            y: self.next_y,
+            width,
+            height: LINE_HEIGHT,
+        };
+        self.next_y += LINE_HEIGHT + PADDING;
+        self.width = self.width.max(width);
+        rect
+    }
+
+    /// Assigns id to widget and makes it active if pointer was pressed on it.
+    /// Returns id and whether pointer is over widget.
+    fn interact(&mut self, rect: Rect) -> (WidgetId, bool) {
+        let id = self.next_id;
+        self.next_id += 1;
+        let hot = self.pointer.is_some_and(|p| rect.contains(p));
+        if hot && self.pressed {
+            self.active = Some(id);
+        }
+        (id, hot)
+    }
+
+    fn widget_color(&self, id: WidgetId, hot: bool) -> Color {
+        if self.active == Some(id) {
+            ACTIVE_COLOR
+        } else if hot {
+            HOT_COLOR
+        } else {
+            WIDGET_COLOR
+        }
+    }
+
+    fn rect(&mut self, rect: Rect, color: Color) {
+        self.draw.commands.push(DrawCommand::Rect { rect, color });
+    }
+
+    fn text(&mut self, position: (f64, f64), text: &str) {
+        self.draw.commands.push(DrawCommand::Text {
+            position,
+            text: text.to_string(),
+            color: TEXT_COLOR,
+        });
+    }
+}
+
+fn text_width(text: &str) -> f64 {
+    text.chars().count() as f64 * CHAR_WIDTH
+}
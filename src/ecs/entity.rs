@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+
+/// Handle of entity. Index of destroyed entity is reused with next generation,
+/// so old handles don't refer to new entity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Entity {
+    index: u32,
+    generation: u32,
+}
+
+impl Entity {
+    pub fn index(&self) -> usize {
+        self.index as usize
+    }
+
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+/// Allocator of entities.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Entities {
+    /// Current generation of every index. Index is alive if its generation is odd.
+    generations: Vec<u32>,
+    free: Vec<u32>,
+}
+
+impl Entities {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn create(&mut self) -> Entity {
+        let index = match self.free.pop() {
+            Some(index) => index,
+            None => {
+                self.generations.push(0);
+                (self.generations.len() - 1) as u32
+            }
+        };
+        let generation = &mut self.generations[index as usize];
+        *generation += 1;
+        Entity {
+            index,
+            generation: *generation,
+        }
+    }
+
+    /// Returns false if entity was already destroyed.
+    pub fn destroy(&mut self, entity: Entity) -> bool {
+        if !self.is_alive(entity) {
+            return false;
+        }
+        self.generations[entity.index()] += 1;
+        self.free.push(entity.index);
+        true
+    }
+
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        entity.generation % 2 == 1
+            && self.generations.get(entity.index()) == Some(&entity.generation)
+    }
+
+    pub fn len(&self) -> usize {
+        self.generations.len() - self.free.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Alive entities in order of their indices.
+    pub fn iter(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.generations
+            .iter()
+            .enumerate()
+            .filter(|(_, generation)| *generation % 2 == 1)
+            .map(|(index, generation)| Entity {
+                index: index as u32,
+                generation: *generation,
+            })
+    }
+}
@@ -0,0 +1,7 @@
+//! Entity-component-system: entities are handles, data is kept in component storages,
+//! and logic is in systems, which declare components they read and write.
+
+pub mod entity;
+pub mod schedule;
+pub mod storage;
+pub mod world;
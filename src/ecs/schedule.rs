@@ -0,0 +1,467 @@
+use super::entity::{Entities, Entity};
+use super::storage::Component;
+use super::world::{ComponentId, Read, World, Write};
+use crate::input::Input;
+use crate::input_state::InputState;
+use crate::model::jobs::{JobId, JobSystem, TaskGraph};
+use crate::model::request::Request;
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+/// Components, which system reads and writes.
+#[derive(Debug, Clone, Default)]
+pub struct Access {
+    reads: HashSet<ComponentId>,
+    writes: HashSet<ComponentId>,
+}
+
+impl Access {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn read<C: Component>(mut self) -> Self {
+        self.reads.insert(ComponentId::of::<C>());
+        self
+    }
+
+    pub fn write<C: Component>(mut self) -> Self {
+        self.writes.insert(ComponentId::of::<C>());
+        self
+    }
+
+    pub fn can_read(&self, id: ComponentId) -> bool {
+        self.reads.contains(&id) || self.writes.contains(&id)
+    }
+
+    pub fn can_write(&self, id: ComponentId) -> bool {
+        self.writes.contains(&id)
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.writes.is_empty()
+    }
+
+    /// Systems with conflicting access can't run at the same time.
+    pub fn conflicts(&self, other: &Access) -> bool {
+        self.writes.iter().any(|id| other.can_read(*id))
+            || other.writes.iter().any(|id| self.can_read(*id))
+    }
+
+    fn components(&self) -> impl Iterator<Item = &ComponentId> {
+        self.reads.iter().chain(self.writes.iter())
+    }
+}
+
+/// Logic of ECS model, working with components it declares in `access`.
+pub trait System: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn access(&self) -> Access;
+    fn run(&self, context: &Context);
+}
+
+/// System made of closure.
+pub struct FnSystem<F> {
+    name: &'static str,
+    access: Access,
+    run: F,
+}
+
+impl<F: Fn(&Context) + Send + Sync> System for FnSystem<F> {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn access(&self) -> Access {
+        self.access.clone()
+    }
+
+    fn run(&self, context: &Context) {
+        (self.run)(context)
+    }
+}
+
+pub fn system<F: Fn(&Context) + Send + Sync>(
+    name: &'static str,
+    access: Access,
+    run: F,
+) -> FnSystem<F> {
+    FnSystem { name, access, run }
+}
+
+/// Deferred change of world.
+type Change = Box<dyn FnOnce(&mut World) + Send>;
+
+/// World changes and requests to application, made by systems.
+/// Changes are deferred until systems are done, because entities can't be created and destroyed
+/// while other systems work with world.
+#[derive(Default)]
+pub struct Commands {
+    changes: Mutex<Vec<Change>>,
+    requests: Mutex<Vec<Request>>,
+}
+
+impl Commands {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&self, change: impl FnOnce(&mut World) + Send + 'static) {
+        self.changes.lock().unwrap().push(Box::new(change));
+    }
+
+    /// Creates entity and lets `build` attach components to it.
+    pub fn spawn(&self, build: impl FnOnce(&mut World, Entity) + Send + 'static) {
+        self.push(|world| {
+            let entity = world.create();
+            build(world, entity);
+        });
+    }
+
+    pub fn destroy(&self, entity: Entity) {
+        self.push(move |world| {
+            world.destroy(entity);
+        });
+    }
+
+    pub fn insert<C: Component>(&self, entity: Entity, component: C) {
+        self.push(move |world| {
+            world.insert(entity, component);
+        });
+    }
+
+    pub fn remove<C: Component>(&self, entity: Entity) {
+        self.push(move |world| {
+            world.remove::<C>(entity);
+        });
+    }
+
+    pub fn request(&self, request: Request) {
+        self.requests.lock().unwrap().push(request);
+    }
+
+    /// Applies changes in order they were made and returns requests.
+    pub fn apply(self, world: &mut World) -> Vec<Request> {
+        for change in self.changes.into_inner().unwrap() {
+            change(world);
+        }
+        self.requests.into_inner().unwrap()
+    }
+}
+
+/// What system can access while running.
+pub struct Context<'a> {
+    system: &'static str,
+    access: &'a Access,
+    world: &'a World,
+    inputs: &'a [Input],
+    input_state: &'a InputState,
+    commands: &'a Commands,
+}
+
+impl Context<'_> {
+    /// Panics if system didn't declare reading or writing `C`, or if it holds `write` guard of `C`.
+    /// Storage stays locked while guard lives, so system may read component several times.
+    pub fn read<C: Component>(&self) -> Read<'_, C> {
+        assert!(
+            self.access.can_read(ComponentId::of::<C>()),
+            "System {} didn't declare access to {}",
+            self.system,
+            C::NAME
+        );
+        // Conflicting systems never run at the same time, so only this system can hold the lock.
+        self.world.try_read().unwrap_or_else(|| {
+            panic!(
+                "System {} reads {} while it holds write guard of it",
+                self.system,
+                C::NAME
+            )
+        })
+    }
+
+    /// Panics if system didn't declare writing `C`, or if it holds other guard of `C`.
+    pub fn write<C: Component>(&self) -> Write<'_, C> {
+        assert!(
+            self.access.can_write(ComponentId::of::<C>()),
+            "System {} didn't declare writing {}",
+            self.system,
+            C::NAME
+        );
+        self.world.try_write().unwrap_or_else(|| {
+            panic!(
+                "System {} writes {} while it holds other guard of it",
+                self.system,
+                C::NAME
+            )
+        })
+    }
+
+    pub fn entities(&self) -> &Entities {
+        self.world.entities()
+    }
+
+    /// Input events of current tick. Empty in prior.
+    pub fn inputs(&self) -> &[Input] {
+        self.inputs
+    }
+
+    pub fn input_state(&self) -> &InputState {
+        self.input_state
+    }
+
+    pub fn commands(&self) -> &Commands {
+        self.commands
+    }
+}
+
+/// Part of tick, in which system runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// While model is shared with presenter. Only read-only systems run in it.
+    Prior,
+    Update,
+}
+
+struct Entry {
+    system: Box<dyn System>,
+    access: Access,
+    phase: Phase,
+}
+
+/// Ordered systems of ECS model. System runs after previously added systems of the same phase,
+/// which access conflicts with its access. Systems without conflicts run in parallel.
+#[derive(Default)]
+pub struct Schedule {
+    entries: Vec<Entry>,
+}
+
+impl Schedule {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds system, running in update.
+    pub fn add(&mut self, system: impl System + 'static) -> &mut Self {
+        self.push(Box::new(system), Phase::Update)
+    }
+
+    /// Adds read-only system, running in prior. Panics if system declares writing.
+    pub fn add_prior(&mut self, system: impl System + 'static) -> &mut Self {
+        assert!(
+            system.access().is_read_only(),
+            "System {} writes components and can't run in prior",
+            system.name()
+        );
+        self.push(Box::new(system), Phase::Prior)
+    }
+
+    fn push(&mut self, system: Box<dyn System>, phase: Phase) -> &mut Self {
+        let access = system.access();
+        self.entries.push(Entry {
+            system,
+            access,
+            phase,
+        });
+        self
+    }
+
+    /// Names of systems in order they were added.
+    pub fn systems(&self, phase: Phase) -> impl Iterator<Item = &'static str> + '_ {
+        self.entries
+            .iter()
+            .filter(move |entry| entry.phase == phase)
+            .map(|entry| entry.system.name())
+    }
+
+    /// Panics if some system uses component, which is not registered in world.
+    pub fn check(&self, world: &World) {
+        for entry in &self.entries {
+            if let Some(id) = entry
+                .access
+                .components()
+                .find(|id| !world.is_registered(**id))
+            {
+                panic!(
+                    "System {} uses not registered component {}",
+                    entry.system.name(),
+                    id.name()
+                );
+            }
+        }
+    }
+
+    /// Runs systems of phase and returns commands they made.
+    pub fn run(
+        &self,
+        phase: Phase,
+        world: &World,
+        inputs: &[Input],
+        input_state: &InputState,
+        jobs: &JobSystem,
+    ) -> Commands {
+        let commands = Commands::new();
+        let mut graph = TaskGraph::new();
+        let mut added: Vec<(JobId, &Access)> = Vec::new();
+        for entry in self.entries.iter().filter(|entry| entry.phase == phase) {
+            let after: Vec<JobId> = added
+                .iter()
+                .filter(|(_, access)| access.conflicts(&entry.access))
+                .map(|(id, _)| *id)
+                .collect();
+            let context = Context {
+                system: entry.system.name(),
+                access: &entry.access,
+                world,
+                inputs,
+                input_state,
+                commands: &commands,
+            };
+            let (id, _) = graph
+                .add(&after, move || entry.system.run(&context))
+                .expect("dependencies are added to the same graph");
+            added.push((id, &entry.access));
+        }
+        jobs.run(graph);
+        commands
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize)]
+    struct Log(String);
+
+    impl Component for Log {
+        const NAME: &'static str = "log";
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Other;
+
+    impl Component for Other {
+        const NAME: &'static str = "other";
+    }
+
+    fn append(name: &'static str) -> impl System {
+        system(name, Access::new().write::<Log>(), move |context| {
+            for (_, log) in context.write::<Log>().iter_mut() {
+                log.0.push_str(name);
+            }
+        })
+    }
+
+    #[test]
+    fn only_writes_conflict() {
+        let read = Access::new().read::<Log>();
+        let write = Access::new().write::<Log>();
+        let other = Access::new().write::<Other>();
+        assert!(!read.conflicts(&read));
+        assert!(read.conflicts(&write));
+        assert!(write.conflicts(&read));
+        assert!(write.conflicts(&write));
+        assert!(!write.conflicts(&other));
+        assert!(!other.conflicts(&read));
+    }
+
+    /// Runs closure with context of system, writing `Log`.
+    fn with_context(run: impl FnOnce(&Context)) {
+        let mut world = World::new();
+        world.register::<Log>();
+        let access = Access::new().write::<Log>();
+        let context = Context {
+            system: "test",
+            access: &access,
+            world: &world,
+            inputs: &[],
+            input_state: &InputState::new(),
+            commands: &Commands::new(),
+        };
+        run(&context);
+    }
+
+    #[test]
+    #[should_panic(expected = "System test reads log while it holds write guard of it")]
+    fn reading_written_component_panics() {
+        with_context(|context| {
+            let _log = context.write::<Log>();
+            context.read::<Log>();
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "System test writes log while it holds other guard of it")]
+    fn writing_read_component_panics() {
+        with_context(|context| {
+            let _log = context.read::<Log>();
+            context.write::<Log>();
+        });
+    }
+
+    #[test]
+    fn guards_of_component_can_be_taken_one_after_another() {
+        with_context(|context| {
+            let first = context.read::<Log>();
+            let second = context.read::<Log>();
+            drop((first, second));
+            drop(context.write::<Log>());
+            context.read::<Log>();
+        });
+    }
+
+    #[test]
+    fn conflicting_systems_run_in_order_added() {
+        let mut world = World::new();
+        world.register::<Log>();
+        let entity = world.create();
+        world.insert(entity, Log(String::new()));
+        let mut schedule = Schedule::new();
+        schedule.add(append("a")).add(append("b")).add(append("c"));
+        let jobs = JobSystem::new(4);
+        for _ in 0..10 {
+            schedule.run(Phase::Update, &world, &[], &InputState::new(), &jobs);
+        }
+        assert_eq!(world.read::<Log>().get(entity).unwrap().0, "abc".repeat(10));
+    }
+
+    #[test]
+    fn commands_apply_in_order_made() {
+        let mut world = World::new();
+        world.register::<Log>();
+        let entity = world.create();
+        let commands = Commands::new();
+        commands.insert(entity, Log(String::from("a")));
+        commands.push(move |world| {
+            world
+                .storage_mut::<Log>()
+                .get_mut(entity)
+                .unwrap()
+                .0
+                .push('b');
+        });
+        commands.request(Request::SetTitle(String::from("first")));
+        commands.destroy(entity);
+        commands.request(Request::SetTitle(String::from("second")));
+        commands.spawn(|world, entity| {
+            world.insert(entity, Log(String::from("c")));
+        });
+
+        let requests = commands.apply(&mut world);
+        assert_eq!(
+            requests,
+            vec![
+                Request::SetTitle(String::from("first")),
+                Request::SetTitle(String::from("second")),
+            ]
+        );
+        assert!(!world.is_alive(entity));
+        let logs: Vec<String> = world
+            .read::<Log>()
+            .iter()
+            .map(|(_, log)| log.0.clone())
+            .collect();
+        assert_eq!(logs, vec![String::from("c")]);
+    }
+}
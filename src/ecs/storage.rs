@@ -0,0 +1,138 @@
+use super::entity::Entity;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::any::Any;
+
+/// Data, which can be attached to entities. `NAME` identifies storage in saved world,
+/// so it must be unique and stable.
+pub trait Component: Any + Send + Sync + Serialize + DeserializeOwned {
+    const NAME: &'static str;
+}
+
+/// Components of one type, indexed by entity index.
+pub struct Storage<C> {
+    components: Vec<Option<(Entity, C)>>,
+    len: usize,
+}
+
+impl<C: Component> Storage<C> {
+    pub fn new() -> Self {
+        Self {
+            components: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// Attaches component to entity. Returns replaced component.
+    pub fn insert(&mut self, entity: Entity, component: C) -> Option<C> {
+        let index = entity.index();
+        if index >= self.components.len() {
+            self.components.resize_with(index + 1, || None);
+        }
+        let old = self.components[index].replace((entity, component));
+        match old {
+            Some((owner, old)) if owner == entity => Some(old),
+            Some(_) => None,
+            None => {
+                self.len += 1;
+                None
+            }
+        }
+    }
+
+    pub fn remove(&mut self, entity: Entity) -> Option<C> {
+        let slot = self.components.get_mut(entity.index())?;
+        if slot.as_ref().is_some_and(|(owner, _)| *owner == entity) {
+            self.len -= 1;
+            slot.take().map(|(_, component)| component)
+        } else {
+            None
+        }
+    }
+
+    pub fn get(&self, entity: Entity) -> Option<&C> {
+        match self.components.get(entity.index()) {
+            Some(Some((owner, component))) if *owner == entity => Some(component),
+            _ => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, entity: Entity) -> Option<&mut C> {
+        match self.components.get_mut(entity.index()) {
+            Some(Some((owner, component))) if *owner == entity => Some(component),
+            _ => None,
+        }
+    }
+
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.get(entity).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Entities with component in order of their indices.
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, &C)> {
+        self.components
+            .iter()
+            .flatten()
+            .map(|(entity, component)| (*entity, component))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Entity, &mut C)> {
+        self.components
+            .iter_mut()
+            .flatten()
+            .map(|(entity, component)| (*entity, component))
+    }
+}
+
+impl<C: Component> Default for Storage<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Storage of any component type, so world can keep them together.
+pub trait AnyStorage: Send + Sync {
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    /// Removes component of destroyed entity.
+    fn remove_entity(&mut self, entity: Entity);
+    fn save(&self) -> bincode::Result<Vec<u8>>;
+    /// Replaces components with saved ones.
+    fn load(&mut self, data: &[u8]) -> bincode::Result<()>;
+}
+
+impl<C: Component> AnyStorage for Storage<C> {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn remove_entity(&mut self, entity: Entity) {
+        self.remove(entity);
+    }
+
+    fn save(&self) -> bincode::Result<Vec<u8>> {
+        let components: Vec<(Entity, &C)> = self.iter().collect();
+        bincode::serialize(&components)
+    }
+
+    fn load(&mut self, data: &[u8]) -> bincode::Result<()> {
+        let components: Vec<(Entity, C)> = bincode::deserialize(data)?;
+        *self = Self::new();
+        for (entity, component) in components {
+            self.insert(entity, component);
+        }
+        Ok(())
+    }
+}
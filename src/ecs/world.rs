@@ -0,0 +1,293 @@
+use super::entity::{Entities, Entity};
+use super::storage::{AnyStorage, Component, Storage};
+use serde::{Deserialize, Serialize};
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard, TryLockError};
+
+/// Identifier of component type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ComponentId {
+    type_id: TypeId,
+    name: &'static str,
+}
+
+impl ComponentId {
+    pub fn of<C: Component>() -> Self {
+        Self {
+            type_id: TypeId::of::<C>(),
+            name: C::NAME,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+/// Entities and storages of registered components.
+/// Every storage is locked separately, so systems can work with different storages in parallel.
+#[derive(Default)]
+pub struct World {
+    entities: Entities,
+    storages: HashMap<ComponentId, RwLock<Box<dyn AnyStorage>>>,
+}
+
+/// Saved entities and components. Components are stored by names of their types.
+#[derive(Serialize, Deserialize)]
+pub struct SavedWorld {
+    entities: Entities,
+    storages: Vec<(String, Vec<u8>)>,
+}
+
+impl World {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds storage for components of type `C`, if it's not added yet.
+    pub fn register<C: Component>(&mut self) {
+        self.storages
+            .entry(ComponentId::of::<C>())
+            .or_insert_with(|| RwLock::new(Box::new(Storage::<C>::new())));
+    }
+
+    pub fn is_registered(&self, id: ComponentId) -> bool {
+        self.storages.contains_key(&id)
+    }
+
+    pub fn create(&mut self) -> Entity {
+        self.entities.create()
+    }
+
+    /// Destroys entity with all its components. Returns false if entity was already destroyed.
+    pub fn destroy(&mut self, entity: Entity) -> bool {
+        if !self.entities.destroy(entity) {
+            return false;
+        }
+        for storage in self.storages.values_mut() {
+            storage.get_mut().unwrap().remove_entity(entity);
+        }
+        true
+    }
+
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        self.entities.is_alive(entity)
+    }
+
+    pub fn entities(&self) -> &Entities {
+        &self.entities
+    }
+
+    /// Attaches component to entity and returns replaced one.
+    /// Component is not attached to destroyed entity.
+    pub fn insert<C: Component>(&mut self, entity: Entity, component: C) -> Option<C> {
+        if !self.is_alive(entity) {
+            return None;
+        }
+        self.storage_mut::<C>().insert(entity, component)
+    }
+
+    pub fn remove<C: Component>(&mut self, entity: Entity) -> Option<C> {
+        self.storage_mut::<C>().remove(entity)
+    }
+
+    /// Storage of components without locking, available with exclusive access to world.
+    pub fn storage_mut<C: Component>(&mut self) -> &mut Storage<C> {
+        self.storages
+            .get_mut(&ComponentId::of::<C>())
+            .unwrap_or_else(|| panic!("Component {} is not registered", C::NAME))
+            .get_mut()
+            .unwrap()
+            .as_any_mut()
+            .downcast_mut()
+            .unwrap()
+    }
+
+    /// Locks storage of components for reading.
+    pub fn read<C: Component>(&self) -> Read<'_, C> {
+        Read {
+            guard: self.lock::<C>().read().unwrap(),
+            component: PhantomData,
+        }
+    }
+
+    /// Locks storage of components for writing.
+    pub fn write<C: Component>(&self) -> Write<'_, C> {
+        Write {
+            guard: self.lock::<C>().write().unwrap(),
+            component: PhantomData,
+        }
+    }
+
+    /// Locks storage of components for reading, unless it's locked for writing. Never blocks.
+    pub fn try_read<C: Component>(&self) -> Option<Read<'_, C>> {
+        let guard = match self.lock::<C>().try_read() {
+            Ok(guard) => guard,
+            Err(TryLockError::WouldBlock) => return None,
+            Err(TryLockError::Poisoned(e)) => panic!("Storage of {} is poisoned: {}", C::NAME, e),
+        };
+        Some(Read {
+            guard,
+            component: PhantomData,
+        })
+    }
+
+    /// Locks storage of components for writing, unless it's locked already. Never blocks.
+    pub fn try_write<C: Component>(&self) -> Option<Write<'_, C>> {
+        let guard = match self.lock::<C>().try_write() {
+            Ok(guard) => guard,
+            Err(TryLockError::WouldBlock) => return None,
+            Err(TryLockError::Poisoned(e)) => panic!("Storage of {} is poisoned: {}", C::NAME, e),
+        };
+        Some(Write {
+            guard,
+            component: PhantomData,
+        })
+    }
+
+    fn lock<C: Component>(&self) -> &RwLock<Box<dyn AnyStorage>> {
+        self.storages
+            .get(&ComponentId::of::<C>())
+            .unwrap_or_else(|| panic!("Component {} is not registered", C::NAME))
+    }
+
+    pub fn save(&self) -> bincode::Result<SavedWorld> {
+        let mut storages = Vec::with_capacity(self.storages.len());
+        for (id, storage) in &self.storages {
+            storages.push((id.name.to_string(), storage.read().unwrap().save()?));
+        }
+        Ok(SavedWorld {
+            entities: self.entities.clone(),
+            storages,
+        })
+    }
+
+    /// Restores saved entities and components into world without entities.
+    /// All saved components must be registered.
+    pub fn load(&mut self, saved: SavedWorld) -> bincode::Result<()> {
+        for (name, data) in saved.storages {
+            let storage = self
+                .storages
+                .iter_mut()
+                .find(|(id, _)| id.name == name)
+                .map(|(_, storage)| storage)
+                .ok_or_else(|| bincode::ErrorKind::Custom(format!("Unknown component {}", name)))?;
+            storage.get_mut().unwrap().load(&data)?;
+        }
+        self.entities = saved.entities;
+        Ok(())
+    }
+}
+
+/// Storage, locked for reading.
+pub struct Read<'a, C> {
+    guard: RwLockReadGuard<'a, Box<dyn AnyStorage>>,
+    component: PhantomData<C>,
+}
+
+impl<C: Component> Deref for Read<'_, C> {
+    type Target = Storage<C>;
+
+    fn deref(&self) -> &Storage<C> {
+        self.guard.as_any().downcast_ref().unwrap()
+    }
+}
+
+/// Storage, locked for writing.
+pub struct Write<'a, C> {
+    guard: RwLockWriteGuard<'a, Box<dyn AnyStorage>>,
+    component: PhantomData<C>,
+}
+
+impl<C: Component> Deref for Write<'_, C> {
+    type Target = Storage<C>;
+
+    fn deref(&self) -> &Storage<C> {
+        self.guard.as_any().downcast_ref().unwrap()
+    }
+}
+
+impl<C: Component> DerefMut for Write<'_, C> {
+    fn deref_mut(&mut self) -> &mut Storage<C> {
+        self.guard.as_any_mut().downcast_mut().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Name(String);
+
+    impl Component for Name {
+        const NAME: &'static str = "name";
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Health(u32);
+
+    impl Component for Health {
+        const NAME: &'static str = "health";
+    }
+
+    fn registered() -> World {
+        let mut world = World::new();
+        world.register::<Name>();
+        world.register::<Health>();
+        world
+    }
+
+    #[test]
+    fn saved_world_loads_back() {
+        let mut world = registered();
+        let hero = world.create();
+        world.insert(hero, Name(String::from("hero")));
+        world.insert(hero, Health(10));
+        let destroyed = world.create();
+        world.insert(destroyed, Health(1));
+        world.destroy(destroyed);
+        let rock = world.create();
+        world.insert(rock, Name(String::from("rock")));
+
+        let bytes = bincode::serialize(&world.save().unwrap()).unwrap();
+        let mut loaded = registered();
+        loaded.load(bincode::deserialize(&bytes).unwrap()).unwrap();
+
+        assert!(loaded.is_alive(hero) && loaded.is_alive(rock));
+        assert!(!loaded.is_alive(destroyed));
+        assert_eq!(loaded.entities().len(), 2);
+        let names = loaded.read::<Name>();
+        assert_eq!(names.get(hero), Some(&Name(String::from("hero"))));
+        assert_eq!(names.get(rock), Some(&Name(String::from("rock"))));
+        let health = loaded.read::<Health>();
+        assert_eq!(health.len(), 1);
+        assert_eq!(health.get(hero), Some(&Health(10)));
+    }
+
+    #[test]
+    fn locked_storage_is_not_locked_again() {
+        let world = registered();
+        let names = world.read::<Name>();
+        assert!(world.try_read::<Name>().is_some());
+        assert!(world.try_write::<Name>().is_none());
+        assert!(world.try_write::<Health>().is_some());
+        drop(names);
+        let _names = world.write::<Name>();
+        assert!(world.try_read::<Name>().is_none());
+    }
+
+    #[test]
+    fn unknown_component_is_not_loaded() {
+        let mut world = registered();
+        let entity = world.create();
+        world.insert(entity, Health(3));
+        let saved = world.save().unwrap();
+        let mut loaded = World::new();
+        loaded.register::<Name>();
+        assert!(loaded.load(saved).is_err());
+    }
+}
@@ -1,2 +1,4 @@
 pub mod init;
 pub mod log_init;
+pub mod settings;
+pub mod versioned_file;
@@ -0,0 +1,36 @@
+use super::versioned_file::VersionedFileError;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug)]
+pub enum SettingsError {
+    Io(std::io::Error),
+    File(VersionedFileError),
+}
+
+impl std::error::Error for SettingsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SettingsError::Io(e) => Some(e),
+            SettingsError::File(e) => Some(e),
+        }
+    }
+}
+
+impl Display for SettingsError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        std::fmt::Display::fmt(self.source().unwrap(), f)
+    }
+}
+
+impl From<std::io::Error> for SettingsError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<VersionedFileError> for SettingsError {
+    fn from(e: VersionedFileError) -> Self {
+        Self::File(e)
+    }
+}
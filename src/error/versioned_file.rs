@@ -0,0 +1,49 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// Error of reading or writing file of `FileFormat`. Carries name of format.
+#[derive(Debug)]
+pub enum VersionedFileError {
+    Io(std::io::Error),
+    Encoding(bincode::Error),
+    /// File doesn't start with expected magic bytes.
+    UnknownFormat(&'static str),
+    /// File was written by version of application with incompatible format.
+    UnsupportedVersion(&'static str, u32),
+}
+
+impl Error for VersionedFileError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            VersionedFileError::Io(e) => Some(e),
+            VersionedFileError::Encoding(e) => Some(e),
+            VersionedFileError::UnknownFormat(_) | VersionedFileError::UnsupportedVersion(..) => {
+                None
+            }
+        }
+    }
+}
+
+impl Display for VersionedFileError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            VersionedFileError::UnknownFormat(name) => write!(f, "File is not a {}", name),
+            VersionedFileError::UnsupportedVersion(name, version) => {
+                write!(f, "Unsupported {} version: {}", name, version)
+            }
+            _ => Display::fmt(self.source().unwrap(), f),
+        }
+    }
+}
+
+impl From<std::io::Error> for VersionedFileError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<bincode::Error> for VersionedFileError {
+    fn from(e: bincode::Error) -> Self {
+        Self::Encoding(e)
+    }
+}
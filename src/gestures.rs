@@ -0,0 +1,626 @@
+use crate::input::{Input, InputEvent};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use winit::event::{ElementState, MouseButton, TouchPhase, VirtualKeyCode};
+use winit::window::WindowId;
+
+/// Higher-level input events, recognized from sequences of raw input events.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Gesture {
+    DoubleClick {
+        button: MouseButton,
+        position: Option<(f64, f64)>,
+    },
+    /// Mouse button is held without moving for long press duration.
+    LongPress {
+        button: MouseButton,
+        position: Option<(f64, f64)>,
+    },
+    /// Cursor moved farther than drag threshold while button is held.
+    DragStart {
+        button: MouseButton,
+        from: (f64, f64),
+    },
+    DragEnd {
+        button: MouseButton,
+        from: (f64, f64),
+        to: (f64, f64),
+    },
+    /// Registered combo was completed.
+    Combo(&'static str),
+    /// Distance between two touching fingers changed in `scale` times.
+    Pinch { scale: f64 },
+    /// Two touching fingers moved together.
+    TouchScroll { delta: (f64, f64) },
+}
+
+/// How combo keys must be pressed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ComboKind {
+    /// One after another, each within window after previous.
+    Sequence,
+    /// All held together, the last pressed within window after the first.
+    Chord,
+}
+
+#[derive(Debug, Clone)]
+pub struct Combo {
+    pub name: &'static str,
+    pub kind: ComboKind,
+    pub keys: Vec<VirtualKeyCode>,
+    pub window: Duration,
+}
+
+/// Thresholds of gesture recognition.
+#[derive(Debug, Clone, Copy)]
+pub struct GestureConfig {
+    pub double_click_time: Duration,
+    /// Maximum cursor distance between clicks of double click.
+    pub double_click_distance: f64,
+    pub long_press_time: Duration,
+    pub drag_threshold: f64,
+}
+
+impl Default for GestureConfig {
+    fn default() -> Self {
+        Self {
+            double_click_time: Duration::from_millis(400),
+            double_click_distance: 4.0,
+            long_press_time: Duration::from_millis(600),
+            drag_threshold: 5.0,
+        }
+    }
+}
+
+/// State of held mouse button.
+struct Press {
+    at: Instant,
+    window_id: Option<WindowId>,
+    from: Option<(f64, f64)>,
+    dragging: bool,
+    long_pressed: bool,
+}
+
+/// Released button, that may become the first click of double click.
+struct Click {
+    button: MouseButton,
+    at: Instant,
+    position: Option<(f64, f64)>,
+}
+
+/// Finds gestures in input stream.
+/// Long press is recognized by time, so `poll` must be called not later than `deadline`.
+pub struct GestureRecognizer {
+    config: GestureConfig,
+    combos: Vec<Combo>,
+    cursor: Option<(f64, f64)>,
+    presses: HashMap<MouseButton, Press>,
+    last_click: Option<Click>,
+    /// Recently pressed keys with press time, the oldest first.
+    key_presses: Vec<(VirtualKeyCode, Instant)>,
+    keys_down: HashMap<VirtualKeyCode, Instant>,
+    touches: HashMap<u64, (f64, f64)>,
+}
+
+impl GestureRecognizer {
+    pub fn new(config: GestureConfig) -> Self {
+        Self {
+            config,
+            combos: Vec::new(),
+            cursor: None,
+            presses: HashMap::new(),
+            last_click: None,
+            key_presses: Vec::new(),
+            keys_down: HashMap::new(),
+            touches: HashMap::new(),
+        }
+    }
+
+    pub fn add_combo(&mut self, combo: Combo) {
+        self.combos.push(combo);
+    }
+
+    /// Returns gestures, completed by input. Gestures have the same origin as input.
+    pub fn feed(&mut self, input: &Input) -> Vec<Input> {
+        let at = *input.happen_at();
+        let gestures = match input.event() {
+            InputEvent::CursorMove { position } => self.cursor_move(*position),
+            InputEvent::MouseButton { button, state } => match state {
+                ElementState::Pressed => self.button_pressed(*button, at, input.window_id()),
+                ElementState::Released => self.button_released(*button, at),
+            },
+            InputEvent::Keyboard { key, state } => match state {
+                ElementState::Pressed => self.key_pressed(*key, at),
+                ElementState::Released => {
+                    self.keys_down.remove(key);
+                    Vec::new()
+                }
+            },
+            InputEvent::Touch {
+                phase,
+                finger,
+                position,
+                ..
+            } => self.touch(*phase, *finger, *position),
+            _ => Vec::new(),
+        };
+        gestures
+            .into_iter()
+            .map(|gesture| input.with_event(InputEvent::Gesture(gesture)))
+            .collect()
+    }
+
+    /// Returns gestures recognized by time passed.
+    pub fn poll(&mut self, now: Instant) -> Vec<Input> {
+        let long_press_time = self.config.long_press_time;
+        let mut gestures = Vec::new();
+        for (button, press) in self.presses.iter_mut() {
+            if press.long_pressed || press.dragging || now < press.at + long_press_time {
+                continue;
+            }
+            press.long_pressed = true;
+            let gesture = Gesture::LongPress {
+                button: *button,
+                position: press.from,
+            };
+            let input = Input::new(now, None, InputEvent::Gesture(gesture));
+            gestures.push(input.in_window(press.window_id));
+        }
+        gestures
+    }
+
+    /// Time, when `poll` may recognize something.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.presses
+            .values()
+            .filter(|p| !p.long_pressed && !p.dragging)
+            .map(|p| p.at + self.config.long_press_time)
+            .min()
+    }
+
+    fn cursor_move(&mut self, position: (f64, f64)) -> Vec<Gesture> {
+        self.cursor = Some(position);
+        let threshold = self.config.drag_threshold;
+        let mut gestures = Vec::new();
+        for (button, press) in self.presses.iter_mut() {
+            let from = match press.from {
+                Some(from) if !press.dragging => from,
+                _ => continue,
+            };
+            if distance(from, position) > threshold {
+                press.dragging = true;
+                gestures.push(Gesture::DragStart {
+                    button: *button,
+                    from,
+                });
+            }
+        }
+        gestures
+    }
+
+    fn button_pressed(
+        &mut self,
+        button: MouseButton,
+        at: Instant,
+        window_id: Option<WindowId>,
+    ) -> Vec<Gesture> {
+        self.presses.insert(
+            button,
+            Press {
+                at,
+                window_id,
+                from: self.cursor,
+                dragging: false,
+                long_pressed: false,
+            },
+        );
+        Vec::new()
+    }
+
+    fn button_released(&mut self, button: MouseButton, at: Instant) -> Vec<Gesture> {
+        let press = match self.presses.remove(&button) {
+            Some(press) => press,
+            None => return Vec::new(),
+        };
+        if press.dragging {
+            self.last_click = None;
+            return match (press.from, self.cursor) {
+                (Some(from), Some(to)) => vec![Gesture::DragEnd { button, from, to }],
+                _ => Vec::new(),
+            };
+        }
+        if press.long_pressed {
+            self.last_click = None;
+            return Vec::new();
+        }
+
+        let double = match &self.last_click {
+            Some(last) => {
+                let near = match (last.position, self.cursor) {
+                    (Some(a), Some(b)) => distance(a, b) <= self.config.double_click_distance,
+                    _ => true,
+                };
+                last.button == button && at <= last.at + self.config.double_click_time && near
+            }
+            None => false,
+        };
+        if double {
+            self.last_click = None;
+            vec![Gesture::DoubleClick {
+                button,
+                position: self.cursor,
+            }]
+        } else {
+            self.last_click = Some(Click {
+                button,
+                at,
+                position: self.cursor,
+            });
+            Vec::new()
+        }
+    }
+
+    fn key_pressed(&mut self, key: VirtualKeyCode, at: Instant) -> Vec<Gesture> {
+        // Ignore key repeat. Held key keeps time of its first press.
+        if self.keys_down.contains_key(&key) {
+            return Vec::new();
+        }
+        self.keys_down.insert(key, at);
+        let longest = self.combos.iter().map(|c| c.keys.len()).max().unwrap_or(0);
+        self.key_presses.push((key, at));
+        if self.key_presses.len() > longest {
+            self.key_presses.remove(0);
+        }
+
+        let mut gestures = Vec::new();
+        for combo in &self.combos {
+            let completed = match combo.kind {
+                ComboKind::Sequence => sequence_completed(combo, &self.key_presses),
+                ComboKind::Chord => chord_completed(combo, key, &self.keys_down),
+            };
+            if completed {
+                gestures.push(Gesture::Combo(combo.name));
+            }
+        }
+        if !gestures.is_empty() {
+            self.key_presses.clear();
+        }
+        gestures
+    }
+
+    fn touch(&mut self, phase: TouchPhase, finger: u64, position: (f64, f64)) -> Vec<Gesture> {
+        let previous = self.two_fingers();
+        match phase {
+            TouchPhase::Started | TouchPhase::Moved => {
+                self.touches.insert(finger, position);
+            }
+            TouchPhase::Ended | TouchPhase::Cancelled => {
+                self.touches.remove(&finger);
+            }
+        }
+        if phase != TouchPhase::Moved {
+            return Vec::new();
+        }
+        let (before, after) = match (previous, self.two_fingers()) {
+            (Some(before), Some(after)) => (before, after),
+            _ => return Vec::new(),
+        };
+
+        let mut gestures = Vec::new();
+        let (distance_before, distance_after) =
+            (distance(before.0, before.1), distance(after.0, after.1));
+        if distance_before > 0.0 && distance_after != distance_before {
+            gestures.push(Gesture::Pinch {
+                scale: distance_after / distance_before,
+            });
+        }
+        let (center_before, center_after) = (center(before), center(after));
+        let delta = (
+            center_after.0 - center_before.0,
+            center_after.1 - center_before.1,
+        );
+        if delta != (0.0, 0.0) {
+            gestures.push(Gesture::TouchScroll { delta });
+        }
+        gestures
+    }
+
+    /// Positions of fingers, if exactly two fingers touch screen.
+    fn two_fingers(&self) -> Option<((f64, f64), (f64, f64))> {
+        if self.touches.len() != 2 {
+            return None;
+        }
+        let mut fingers: Vec<_> = self.touches.iter().collect();
+        fingers.sort_by_key(|(finger, _)| **finger);
+        Some((*fingers[0].1, *fingers[1].1))
+    }
+}
+
+fn sequence_completed(combo: &Combo, presses: &[(VirtualKeyCode, Instant)]) -> bool {
+    if combo.keys.is_empty() || presses.len() < combo.keys.len() {
+        return false;
+    }
+    let tail = &presses[presses.len() - combo.keys.len()..];
+    let keys_match = tail.iter().map(|(key, _)| key).eq(combo.keys.iter());
+    let in_time = tail.windows(2).all(|w| w[1].1 <= w[0].1 + combo.window);
+    keys_match && in_time
+}
+
+fn chord_completed(
+    combo: &Combo,
+    last: VirtualKeyCode,
+    keys_down: &HashMap<VirtualKeyCode, Instant>,
+) -> bool {
+    if combo.keys.is_empty() || !combo.keys.contains(&last) {
+        return false;
+    }
+    let times: Option<Vec<Instant>> = combo
+        .keys
+        .iter()
+        .map(|k| keys_down.get(k).copied())
+        .collect();
+    match times {
+        Some(times) => {
+            let first = times.iter().min().unwrap();
+            let last = times.iter().max().unwrap();
+            *last <= *first + combo.window
+        }
+        None => false,
+    }
+}
+
+fn distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+}
+
+fn center(fingers: ((f64, f64), (f64, f64))) -> (f64, f64) {
+    let (a, b) = fingers;
+    ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEFT: MouseButton = MouseButton::Left;
+
+    /// Recognizer with default config and time of its first input.
+    fn recognizer() -> (GestureRecognizer, Instant) {
+        (
+            GestureRecognizer::new(GestureConfig::default()),
+            Instant::now(),
+        )
+    }
+
+    fn feed(recognizer: &mut GestureRecognizer, at: Instant, event: InputEvent) -> Vec<Gesture> {
+        recognizer
+            .feed(&Input::new(at, None, event))
+            .into_iter()
+            .map(|input| match input.event() {
+                InputEvent::Gesture(gesture) => gesture.clone(),
+                event => panic!("unexpected event {:?}", event),
+            })
+            .collect()
+    }
+
+    fn click(recognizer: &mut GestureRecognizer, at: Instant) -> Vec<Gesture> {
+        feed(recognizer, at, (LEFT, ElementState::Pressed).into());
+        feed(recognizer, at, (LEFT, ElementState::Released).into())
+    }
+
+    fn key(recognizer: &mut GestureRecognizer, at: Instant, key: VirtualKeyCode) -> Vec<Gesture> {
+        let pressed = feed(
+            recognizer,
+            at,
+            InputEvent::Keyboard {
+                key,
+                state: ElementState::Pressed,
+            },
+        );
+        let event = InputEvent::Keyboard {
+            key,
+            state: ElementState::Released,
+        };
+        feed(recognizer, at, event);
+        pressed
+    }
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn long_press_is_recognized_after_threshold() {
+        let (mut recognizer, start) = recognizer();
+        feed(&mut recognizer, start, (LEFT, ElementState::Pressed).into());
+        assert_eq!(recognizer.deadline(), Some(start + ms(600)));
+        assert!(recognizer.poll(start + ms(599)).is_empty());
+
+        let gestures = recognizer.poll(start + ms(600));
+        assert_eq!(gestures.len(), 1);
+        match gestures[0].event() {
+            InputEvent::Gesture(gesture) => assert_eq!(
+                *gesture,
+                Gesture::LongPress {
+                    button: LEFT,
+                    position: None,
+                }
+            ),
+            event => panic!("unexpected event {:?}", event),
+        }
+        assert!(recognizer.poll(start + ms(900)).is_empty());
+        assert_eq!(recognizer.deadline(), None);
+
+        // Release of long press is not a click.
+        let released = (LEFT, ElementState::Released).into();
+        assert!(feed(&mut recognizer, start + ms(900), released).is_empty());
+        assert!(click(&mut recognizer, start + ms(1000)).is_empty());
+    }
+
+    #[test]
+    fn tap_before_threshold_is_not_long_press() {
+        let (mut recognizer, start) = recognizer();
+        click(&mut recognizer, start + ms(100));
+        assert_eq!(recognizer.deadline(), None);
+        assert!(recognizer.poll(start + ms(1000)).is_empty());
+    }
+
+    #[test]
+    fn double_click_needs_second_click_within_window() {
+        let (mut recognizer, start) = recognizer();
+        assert!(click(&mut recognizer, start).is_empty());
+        assert_eq!(
+            click(&mut recognizer, start + ms(400)),
+            vec![Gesture::DoubleClick {
+                button: LEFT,
+                position: None,
+            }]
+        );
+
+        // Third click starts new double click.
+        assert!(click(&mut recognizer, start + ms(500)).is_empty());
+        assert!(click(&mut recognizer, start + ms(901)).is_empty());
+        assert_eq!(click(&mut recognizer, start + ms(1000)).len(), 1);
+    }
+
+    #[test]
+    fn drag_starts_beyond_threshold() {
+        let (mut recognizer, start) = recognizer();
+        let moved = |x| InputEvent::CursorMove { position: (x, 0.0) };
+        feed(&mut recognizer, start, moved(0.0));
+        feed(&mut recognizer, start, (LEFT, ElementState::Pressed).into());
+        assert!(feed(&mut recognizer, start, moved(5.0)).is_empty());
+        assert_eq!(
+            feed(&mut recognizer, start, moved(6.0)),
+            vec![Gesture::DragStart {
+                button: LEFT,
+                from: (0.0, 0.0),
+            }]
+        );
+        // Dragged button is never long pressed.
+        assert!(recognizer.poll(start + ms(1000)).is_empty());
+        let released = (LEFT, ElementState::Released).into();
+        assert_eq!(
+            feed(&mut recognizer, start + ms(1000), released),
+            vec![Gesture::DragEnd {
+                button: LEFT,
+                from: (0.0, 0.0),
+                to: (6.0, 0.0),
+            }]
+        );
+    }
+
+    #[test]
+    fn sequence_combo_times_out_and_resets() {
+        let (mut recognizer, start) = recognizer();
+        recognizer.add_combo(Combo {
+            name: "twice",
+            kind: ComboKind::Sequence,
+            keys: vec![VirtualKeyCode::A, VirtualKeyCode::B],
+            window: ms(300),
+        });
+        key(&mut recognizer, start, VirtualKeyCode::A);
+        assert!(key(&mut recognizer, start + ms(301), VirtualKeyCode::B).is_empty());
+
+        key(&mut recognizer, start + ms(400), VirtualKeyCode::A);
+        assert_eq!(
+            key(&mut recognizer, start + ms(700), VirtualKeyCode::B),
+            vec![Gesture::Combo("twice")]
+        );
+        // Completed combo is forgotten, so its last key doesn't start the next one.
+        assert!(key(&mut recognizer, start + ms(800), VirtualKeyCode::B).is_empty());
+        // Other key in between breaks sequence.
+        key(&mut recognizer, start + ms(900), VirtualKeyCode::A);
+        key(&mut recognizer, start + ms(950), VirtualKeyCode::C);
+        assert!(key(&mut recognizer, start + ms(1000), VirtualKeyCode::B).is_empty());
+    }
+
+    #[test]
+    fn chord_combo_needs_keys_held_together() {
+        let (mut recognizer, start) = recognizer();
+        recognizer.add_combo(Combo {
+            name: "chord",
+            kind: ComboKind::Chord,
+            keys: vec![VirtualKeyCode::LControl, VirtualKeyCode::K],
+            window: ms(200),
+        });
+        let press = |key| InputEvent::Keyboard {
+            key,
+            state: ElementState::Pressed,
+        };
+        feed(&mut recognizer, start, press(VirtualKeyCode::LControl));
+        assert!(feed(&mut recognizer, start + ms(201), press(VirtualKeyCode::K)).is_empty());
+
+        let release = |key| InputEvent::Keyboard {
+            key,
+            state: ElementState::Released,
+        };
+        feed(&mut recognizer, start + ms(250), release(VirtualKeyCode::K));
+        // Key repeat of held key doesn't move its press time.
+        feed(
+            &mut recognizer,
+            start + ms(300),
+            press(VirtualKeyCode::LControl),
+        );
+        assert!(key(&mut recognizer, start + ms(300), VirtualKeyCode::K).is_empty());
+
+        feed(
+            &mut recognizer,
+            start + ms(400),
+            release(VirtualKeyCode::LControl),
+        );
+        feed(
+            &mut recognizer,
+            start + ms(500),
+            press(VirtualKeyCode::LControl),
+        );
+        assert_eq!(
+            key(&mut recognizer, start + ms(700), VirtualKeyCode::K),
+            vec![Gesture::Combo("chord")]
+        );
+    }
+
+    #[test]
+    fn two_fingers_pinch_and_scroll() {
+        let (mut recognizer, start) = recognizer();
+        let touch = |phase, finger, position| InputEvent::Touch {
+            phase,
+            finger,
+            position,
+            pressure: None,
+            altitude: None,
+        };
+        feed(
+            &mut recognizer,
+            start,
+            touch(TouchPhase::Started, 1, (0.0, 0.0)),
+        );
+        feed(
+            &mut recognizer,
+            start,
+            touch(TouchPhase::Started, 2, (10.0, 0.0)),
+        );
+        assert_eq!(
+            feed(
+                &mut recognizer,
+                start,
+                touch(TouchPhase::Moved, 2, (20.0, 0.0))
+            ),
+            vec![
+                Gesture::Pinch { scale: 2.0 },
+                Gesture::TouchScroll { delta: (5.0, 0.0) },
+            ]
+        );
+        feed(
+            &mut recognizer,
+            start,
+            touch(TouchPhase::Ended, 2, (20.0, 0.0)),
+        );
+        assert!(feed(
+            &mut recognizer,
+            start,
+            touch(TouchPhase::Moved, 1, (1.0, 0.0))
+        )
+        .is_empty());
+    }
+}
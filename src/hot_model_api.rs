@@ -0,0 +1,100 @@
+//! Hot model interface, shared by application and hot model libraries.
+//! Libraries include this file as module with `#[path]`, so it uses only std, serde and winit.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::ffi::c_void;
+use winit::event::{MouseButton, VirtualKeyCode};
+
+/// Version of hot model interface. Bump it, when functions or `InputState` fields change.
+pub const API_VERSION: u32 = 1;
+
+/// Callback, receiving saved model state from library.
+pub type WriteFn = unsafe extern "C" fn(out: *mut c_void, data: *const u8, len: usize);
+
+/// Input devices state, collected from input events.
+/// Per tick values (just pressed/released, deltas) are reset at the beginning of every tick.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InputState {
+    pub(crate) keys_down: HashSet<VirtualKeyCode>,
+    pub(crate) keys_pressed: HashSet<VirtualKeyCode>,
+    pub(crate) keys_released: HashSet<VirtualKeyCode>,
+    pub(crate) buttons_down: HashSet<MouseButton>,
+    pub(crate) buttons_pressed: HashSet<MouseButton>,
+    pub(crate) buttons_released: HashSet<MouseButton>,
+    pub(crate) cursor_position: Option<(f64, f64)>,
+    pub(crate) mouse_delta: (f64, f64),
+    pub(crate) scroll_delta: (f64, f64),
+    pub(crate) touches: HashMap<u64, (f64, f64)>,
+}
+
+impl InputState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forgets per tick values.
+    pub fn begin_tick(&mut self) {
+        self.keys_pressed.clear();
+        self.keys_released.clear();
+        self.buttons_pressed.clear();
+        self.buttons_released.clear();
+        self.mouse_delta = (0.0, 0.0);
+        self.scroll_delta = (0.0, 0.0);
+    }
+
+    pub fn is_down(&self, key: VirtualKeyCode) -> bool {
+        self.keys_down.contains(&key)
+    }
+
+    /// Key was pressed during this tick.
+    pub fn just_pressed(&self, key: VirtualKeyCode) -> bool {
+        self.keys_pressed.contains(&key)
+    }
+
+    /// Key was released during this tick.
+    pub fn just_released(&self, key: VirtualKeyCode) -> bool {
+        self.keys_released.contains(&key)
+    }
+
+    pub fn is_button_down(&self, button: MouseButton) -> bool {
+        self.buttons_down.contains(&button)
+    }
+
+    /// Mouse button was pressed during this tick.
+    pub fn button_just_pressed(&self, button: MouseButton) -> bool {
+        self.buttons_pressed.contains(&button)
+    }
+
+    /// Mouse button was released during this tick.
+    pub fn button_just_released(&self, button: MouseButton) -> bool {
+        self.buttons_released.contains(&button)
+    }
+
+    /// Last known cursor position in window.
+    pub fn cursor_position(&self) -> Option<(f64, f64)> {
+        self.cursor_position
+    }
+
+    /// Raw mouse motion accumulated during this tick.
+    pub fn mouse_delta(&self) -> (f64, f64) {
+        self.mouse_delta
+    }
+
+    /// Scroll accumulated during this tick.
+    pub fn scroll_delta(&self) -> (f64, f64) {
+        self.scroll_delta
+    }
+
+    /// Position of finger, if it touches screen.
+    pub fn touch(&self, finger: u64) -> Option<(f64, f64)> {
+        self.touches.get(&finger).copied()
+    }
+
+    /// Fingers touching screen and their positions.
+    pub fn touches(&self) -> impl Iterator<Item = (u64, (f64, f64))> + '_ {
+        self.touches
+            .iter()
+            .map(|(finger, position)| (*finger, *position))
+    }
+}
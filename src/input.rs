@@ -1,18 +1,37 @@
+use crate::gestures::Gesture;
 use std::convert::TryFrom;
 use std::time::Instant;
 use winit::event::{
-    DeviceEvent, DeviceId, ElementState, Event, MouseButton, MouseScrollDelta, VirtualKeyCode,
-    WindowEvent,
+    DeviceEvent, DeviceId, ElementState, Event, Force, MouseButton, MouseScrollDelta, Touch,
+    TouchPhase, VirtualKeyCode, WindowEvent,
 };
+use winit::window::WindowId;
 
 #[derive(Debug, Clone)]
 pub struct Input {
     happen_at: Instant,
     device_id: Option<DeviceId>,
+    /// Window, which received input. None for raw device events and input not bound to window.
+    window_id: Option<WindowId>,
     event: InputEvent,
 }
 
 impl Input {
+    pub fn new(happen_at: Instant, device_id: Option<DeviceId>, event: InputEvent) -> Self {
+        Self {
+            happen_at,
+            device_id,
+            window_id: None,
+            event,
+        }
+    }
+
+    /// Returns the same input, received by window.
+    pub fn in_window(mut self, window_id: Option<WindowId>) -> Self {
+        self.window_id = window_id;
+        self
+    }
+
     pub fn event(&self) -> &InputEvent {
         &self.event
     }
@@ -24,6 +43,78 @@ impl Input {
     pub fn happen_at(&self) -> &Instant {
         &self.happen_at
     }
+
+    pub fn window_id(&self) -> Option<WindowId> {
+        self.window_id
+    }
+
+    /// Returns input with the same origin, but other event.
+    pub fn with_event(&self, event: InputEvent) -> Self {
+        Self {
+            happen_at: self.happen_at,
+            device_id: self.device_id,
+            window_id: self.window_id,
+            event,
+        }
+    }
+
+    /// Merges `next` into this input, if both are mouse moves, scrolls in the same units, text or
+    /// moves of the same finger of the same device in the same window. Relative deltas are summed,
+    /// positions are replaced, text is appended. Returns false if can't merge.
+    pub fn coalesce(&mut self, next: &Input) -> bool {
+        if self.device_id != next.device_id || self.window_id != next.window_id {
+            return false;
+        }
+        match (&mut self.event, &next.event) {
+            (
+                InputEvent::RawMouseMove { delta },
+                InputEvent::RawMouseMove { delta: next_delta },
+            ) => {
+                delta.0 += next_delta.0;
+                delta.1 += next_delta.1;
+            }
+            (
+                InputEvent::Scroll { delta, unit },
+                InputEvent::Scroll {
+                    delta: next_delta,
+                    unit: next_unit,
+                },
+            ) if unit == next_unit => {
+                delta.0 += next_delta.0;
+                delta.1 += next_delta.1;
+            }
+            (
+                InputEvent::CursorMove { position },
+                InputEvent::CursorMove {
+                    position: next_position,
+                },
+            ) => *position = *next_position,
+            (InputEvent::Text(text), InputEvent::Text(next_text)) => text.push_str(next_text),
+            (
+                InputEvent::Touch {
+                    phase: TouchPhase::Moved,
+                    finger,
+                    position,
+                    pressure,
+                    altitude,
+                },
+                InputEvent::Touch {
+                    phase: TouchPhase::Moved,
+                    finger: next_finger,
+                    position: next_position,
+                    pressure: next_pressure,
+                    altitude: next_altitude,
+                },
+            ) if finger == next_finger => {
+                *position = *next_position;
+                *pressure = *next_pressure;
+                *altitude = *next_altitude;
+            }
+            _ => return false,
+        }
+        self.happen_at = next.happen_at;
+        true
+    }
 }
 
 impl From<(Option<DeviceId>, InputEvent)> for Input {
@@ -31,6 +122,7 @@ impl From<(Option<DeviceId>, InputEvent)> for Input {
         Self {
             happen_at: Instant::now(),
             device_id: input.0,
+            window_id: None,
             event: input.1,
         }
     }
@@ -50,21 +142,70 @@ pub enum InputEvent {
     },
     Scroll {
         delta: (f64, f64),
+        unit: ScrollUnit,
     },
     MouseButton {
         button: MouseButton,
         state: ElementState,
     },
     Symbol(char),
+    /// Text committed in text input mode.
+    /// Input method composition is not supported: winit 0.22 doesn't report it,
+    /// so only text committed by input method arrives, as symbols.
+    Text(String),
+    /// Text pasted from clipboard in text input mode.
+    Paste(String),
+    /// Finger or pen touched, moved on, left touchscreen, or touch was cancelled by system.
+    Touch {
+        phase: TouchPhase,
+        /// Unique identifier of finger while it touches screen.
+        finger: u64,
+        position: (f64, f64),
+        /// Normalized to range from 0.0 to 1.0, if device supports it.
+        pressure: Option<f64>,
+        /// Pen altitude in radians, if device reports it. Pi/2 means perpendicular to screen.
+        altitude: Option<f64>,
+    },
+    /// Recognized from other input events.
+    Gesture(Gesture),
+    /// Tool window, requested by model, is opened. Comes with its `WindowId`.
+    WindowOpened {
+        title: String,
+    },
+    /// Tool window, opened by model, is closed. Comes with its `WindowId`.
+    WindowClosed,
+}
+
+/// What scroll delta is measured in. Touchpads usually scroll by pixels, mouse wheels by lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScrollUnit {
+    Pixels,
+    Lines,
 }
 
 impl From<MouseScrollDelta> for InputEvent {
     fn from(delta: MouseScrollDelta) -> Self {
-        let delta = match delta {
-            MouseScrollDelta::PixelDelta(pos) => pos.into(),
-            MouseScrollDelta::LineDelta(x, y) => (x as f64, y as f64),
+        let (delta, unit) = match delta {
+            MouseScrollDelta::PixelDelta(pos) => (pos.into(), ScrollUnit::Pixels),
+            MouseScrollDelta::LineDelta(x, y) => ((x as f64, y as f64), ScrollUnit::Lines),
         };
-        InputEvent::Scroll { delta }
+        InputEvent::Scroll { delta, unit }
+    }
+}
+
+impl From<&Touch> for InputEvent {
+    fn from(touch: &Touch) -> Self {
+        let altitude = match touch.force {
+            Some(Force::Calibrated { altitude_angle, .. }) => altitude_angle,
+            _ => None,
+        };
+        InputEvent::Touch {
+            phase: touch.phase,
+            finger: touch.id,
+            position: touch.location.into(),
+            pressure: touch.force.map(|f| f.normalized()),
+            altitude,
+        }
     }
 }
 
@@ -77,58 +218,14 @@ impl From<(MouseButton, ElementState)> for InputEvent {
     }
 }
 
-impl TryFrom<&Event<'_, ()>> for Input {
+impl<T> TryFrom<&Event<'_, T>> for Input {
     type Error = ();
 
-    fn try_from(event: &Event<()>) -> Result<Self, Self::Error> {
+    fn try_from(event: &Event<T>) -> Result<Self, Self::Error> {
         match event {
-            Event::WindowEvent { event, .. } => match event {
-                WindowEvent::ReceivedCharacter(c) => {
-                    let event = InputEvent::Symbol(*c);
-                    Ok((None, event).into())
-                }
-                WindowEvent::KeyboardInput {
-                    input, device_id, ..
-                } => {
-                    if let Some(key) = input.virtual_keycode {
-                        let event = InputEvent::Keyboard {
-                            key,
-                            state: input.state,
-                        };
-                        return Ok((Some(*device_id), event).into());
-                    }
-                    Err(())
-                }
-                WindowEvent::CursorMoved {
-                    device_id,
-                    position,
-                    ..
-                } => {
-                    let event = InputEvent::CursorMove {
-                        position: (*position).into(),
-                    };
-                    Ok((Some(*device_id), event).into())
-                }
-                WindowEvent::MouseWheel {
-                    device_id, delta, ..
-                } => {
-                    let event = (*delta).into();
-                    Ok((Some(*device_id), event).into())
-                }
-                WindowEvent::MouseInput {
-                    device_id,
-                    button,
-                    state,
-                    ..
-                } => {
-                    let event = InputEvent::MouseButton {
-                        button: *button,
-                        state: *state,
-                    };
-                    Ok((Some(*device_id), event).into())
-                }
-                _ => Err(()),
-            },
+            Event::WindowEvent { event, window_id } => {
+                window_input(event).map(|input| input.in_window(Some(*window_id)))
+            }
             Event::DeviceEvent {
                 event: DeviceEvent::MouseMotion { delta },
                 device_id,
@@ -140,3 +237,127 @@ impl TryFrom<&Event<'_, ()>> for Input {
         }
     }
 }
+
+/// Converts window event into input, not bound to window yet.
+fn window_input(event: &WindowEvent) -> Result<Input, ()> {
+    match event {
+        WindowEvent::ReceivedCharacter(c) => {
+            let event = InputEvent::Symbol(*c);
+            Ok((None, event).into())
+        }
+        WindowEvent::KeyboardInput {
+            input, device_id, ..
+        } => {
+            if let Some(key) = input.virtual_keycode {
+                let event = InputEvent::Keyboard {
+                    key,
+                    state: input.state,
+                };
+                return Ok((Some(*device_id), event).into());
+            }
+            Err(())
+        }
+        WindowEvent::CursorMoved {
+            device_id,
+            position,
+            ..
+        } => {
+            let event = InputEvent::CursorMove {
+                position: (*position).into(),
+            };
+            Ok((Some(*device_id), event).into())
+        }
+        WindowEvent::MouseWheel {
+            device_id, delta, ..
+        } => {
+            let event = (*delta).into();
+            Ok((Some(*device_id), event).into())
+        }
+        WindowEvent::MouseInput {
+            device_id,
+            button,
+            state,
+            ..
+        } => {
+            let event = InputEvent::MouseButton {
+                button: *button,
+                state: *state,
+            };
+            Ok((Some(*device_id), event).into())
+        }
+        WindowEvent::Touch(touch) => Ok((Some(touch.device_id), touch.into()).into()),
+        _ => Err(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(event: InputEvent) -> Input {
+        Input::new(Instant::now(), None, event)
+    }
+
+    fn scroll(delta: (f64, f64), unit: ScrollUnit) -> Input {
+        input(InputEvent::Scroll { delta, unit })
+    }
+
+    #[test]
+    fn motion_is_merged() {
+        let mut moved = input(InputEvent::RawMouseMove { delta: (1.0, 2.0) });
+        assert!(moved.coalesce(&input(InputEvent::RawMouseMove { delta: (3.0, -1.0) })));
+        match moved.event() {
+            InputEvent::RawMouseMove { delta } => assert_eq!(*delta, (4.0, 1.0)),
+            event => panic!("unexpected event {:?}", event),
+        }
+
+        let mut cursor = input(InputEvent::CursorMove {
+            position: (1.0, 1.0),
+        });
+        let next = input(InputEvent::CursorMove {
+            position: (5.0, 6.0),
+        });
+        assert!(cursor.coalesce(&next));
+        assert_eq!(cursor.happen_at(), next.happen_at());
+        match cursor.event() {
+            InputEvent::CursorMove { position } => assert_eq!(*position, (5.0, 6.0)),
+            event => panic!("unexpected event {:?}", event),
+        }
+    }
+
+    #[test]
+    fn scrolls_are_merged_only_in_the_same_units() {
+        let mut lines = scroll((0.0, 1.0), ScrollUnit::Lines);
+        assert!(lines.coalesce(&scroll((0.0, 2.0), ScrollUnit::Lines)));
+        assert!(!lines.coalesce(&scroll((0.0, 40.0), ScrollUnit::Pixels)));
+        match lines.event() {
+            InputEvent::Scroll { delta, unit } => {
+                assert_eq!(*delta, (0.0, 3.0));
+                assert_eq!(*unit, ScrollUnit::Lines);
+            }
+            event => panic!("unexpected event {:?}", event),
+        }
+    }
+
+    #[test]
+    fn other_events_are_not_merged() {
+        let key = |state| InputEvent::Keyboard {
+            key: VirtualKeyCode::A,
+            state,
+        };
+        let mut pressed = input(key(ElementState::Pressed));
+        assert!(!pressed.coalesce(&input(key(ElementState::Released))));
+
+        let touch = |phase, finger| InputEvent::Touch {
+            phase,
+            finger,
+            position: (0.0, 0.0),
+            pressure: None,
+            altitude: None,
+        };
+        let mut moved = input(touch(TouchPhase::Moved, 1));
+        assert!(!moved.coalesce(&input(touch(TouchPhase::Moved, 2))));
+        assert!(!moved.coalesce(&input(touch(TouchPhase::Ended, 1))));
+        assert!(moved.coalesce(&input(touch(TouchPhase::Moved, 1))));
+    }
+}
@@ -0,0 +1,65 @@
+use crate::input::{Input, InputEvent};
+use winit::event::{ElementState, TouchPhase};
+
+pub use crate::hot_model_api::InputState;
+
+/// Collecting state from events is part of application only, so hot model libraries don't depend on events.
+impl InputState {
+    /// Updates state with input event.
+    pub fn apply(&mut self, input: &Input) {
+        match input.event() {
+            InputEvent::Keyboard { key, state } => match state {
+                ElementState::Pressed => {
+                    if self.keys_down.insert(*key) {
+                        self.keys_pressed.insert(*key);
+                    }
+                }
+                ElementState::Released => {
+                    if self.keys_down.remove(key) {
+                        self.keys_released.insert(*key);
+                    }
+                }
+            },
+            InputEvent::MouseButton { button, state } => match state {
+                ElementState::Pressed => {
+                    if self.buttons_down.insert(*button) {
+                        self.buttons_pressed.insert(*button);
+                    }
+                }
+                ElementState::Released => {
+                    if self.buttons_down.remove(button) {
+                        self.buttons_released.insert(*button);
+                    }
+                }
+            },
+            InputEvent::CursorMove { position } => self.cursor_position = Some(*position),
+            InputEvent::RawMouseMove { delta } => {
+                self.mouse_delta.0 += delta.0;
+                self.mouse_delta.1 += delta.1;
+            }
+            InputEvent::Scroll { delta, .. } => {
+                self.scroll_delta.0 += delta.0;
+                self.scroll_delta.1 += delta.1;
+            }
+            InputEvent::Touch {
+                phase,
+                finger,
+                position,
+                ..
+            } => match phase {
+                TouchPhase::Started | TouchPhase::Moved => {
+                    self.touches.insert(*finger, *position);
+                }
+                TouchPhase::Ended | TouchPhase::Cancelled => {
+                    self.touches.remove(finger);
+                }
+            },
+            InputEvent::Symbol(_)
+            | InputEvent::Text(_)
+            | InputEvent::Paste(_)
+            | InputEvent::Gesture(_)
+            | InputEvent::WindowOpened { .. }
+            | InputEvent::WindowClosed => {}
+        }
+    }
+}
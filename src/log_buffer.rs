@@ -0,0 +1,39 @@
+use slog::{Drain, Never, OwnedKVList, Record};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+/// Slog drain, keeping the most recent log lines in memory, e.g. to show them in debug overlay.
+#[derive(Debug, Clone)]
+pub struct LogBuffer {
+    lines: Arc<Mutex<VecDeque<String>>>,
+    capacity: usize,
+}
+
+impl LogBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            lines: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity,
+        }
+    }
+
+    /// Kept lines, the oldest first.
+    pub fn lines(&self) -> Vec<String> {
+        self.lines.lock().unwrap().iter().cloned().collect()
+    }
+}
+
+impl Drain for LogBuffer {
+    type Ok = ();
+    type Err = Never;
+
+    fn log(&self, record: &Record, _: &OwnedKVList) -> Result<Self::Ok, Self::Err> {
+        let line = format!("{} {}", record.level().as_short_str(), record.msg());
+        let mut lines = self.lines.lock().unwrap();
+        lines.push_back(line);
+        while lines.len() > self.capacity {
+            lines.pop_front();
+        }
+        Ok(())
+    }
+}
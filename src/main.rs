@@ -1,94 +1,547 @@
 #![allow(dead_code)]
 
+mod console;
+mod debug_controls;
+mod debug_overlay;
+mod debug_ui;
+mod ecs;
 mod error;
+mod gestures;
+mod hot_model_api;
 mod input;
 mod input_logger;
+mod input_state;
+mod log_buffer;
 mod model;
+mod physics;
 mod presenter;
 mod renderer;
+mod text_input;
+mod touch_emulation;
 mod utils;
+mod versioned_file;
+mod window_geometry;
+mod windows;
 
-use winit::dpi::PhysicalSize;
-use winit::event_loop::{ControlFlow, EventLoop};
-use winit::window::{Window, WindowBuilder};
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::event_loop::{ControlFlow, EventLoop, EventLoopProxy};
+use winit::window::{Fullscreen, Window, WindowBuilder, WindowId};
 
 use settings_path::*;
-use slog::{info, trace, warn, Logger};
+use slog::{info, o, trace, warn, Drain, Duplicate, Level, LevelFilter, Logger};
 use sloggers::{file::FileLoggerBuilder, types::TimeZone, Build};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use error::init::InitError;
 use error::log_init::LogInitError;
 use sloggers::types::Severity;
 use winit::error::OsError;
 
+use crate::console::Console;
+use crate::debug_controls::{DebugControls, DebugMode};
+use crate::debug_overlay::DebugOverlay;
+use crate::gestures::{GestureConfig, GestureRecognizer};
+use crate::input::{Input, InputEvent};
+use crate::log_buffer::LogBuffer;
+use crate::model::async_bonds::AsyncBonds;
+use crate::model::ball_model::BallModel;
 use crate::model::counter_model::CounterModel;
-use crate::model::model_manager::{Command, ModelManager, OuterBonds};
+use crate::model::dylib_model::DylibModel;
+use crate::model::ecs_counter::EcsCounterModel;
+use crate::model::model_manager::{Command, CommandSender, ModelManager, OuterBonds};
+use crate::model::registry::{InputRouter, ModelId, ModelRegistry};
+use crate::model::request::Request;
+use crate::model::scheduler::Scheduler;
+use crate::model::script_model::ScriptModel;
+use crate::model::supervisor::{Decision, Policy, Supervisor};
+use crate::model::Model;
+use crate::presenter::DRAWS_DEBUG_UI;
+use crate::text_input::{Handled, TextInput};
+use crate::touch_emulation::TouchEmulation;
 use crate::utils::show_error_message;
+use crate::window_geometry::WindowGeometry;
+use crate::windows::Windows;
+use futures::executor::block_on;
+use futures::future::Either;
+use futures::{stream, StreamExt};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::any::Any;
 use std::convert::TryInto;
+use std::sync::mpsc::Receiver;
 use std::sync::Arc;
-use std::time::Duration;
-use winit::event::{Event, WindowEvent};
+use std::time::{Duration, Instant};
+use winit::event::{ElementState, Event, VirtualKeyCode, WindowEvent};
+
+const WINDOW_TITLE: &str = "InfrastructurePrototype";
+
+/// File in settings directory, where main window geometry is kept between sessions.
+const WINDOW_GEOMETRY_FILE: &str = "window";
+
+const COUNTER_MODEL: ModelId = ModelId("counter");
+const HOT_MODEL: ModelId = ModelId("hot");
+const SCRIPT_MODEL: ModelId = ModelId("script");
+/// Physics demo. Runs behind other models and gets input only when focused by `FOCUS_KEY`.
+const BALL_MODEL: ModelId = ModelId("ball");
+/// ECS demo, counting key presses. Like physics demo, gets input only when focused.
+const ECS_MODEL: ModelId = ModelId("ecs");
+
+/// Environment variable with path of hot model library. If it's set, hot model is run
+/// along with counter model and takes unfocused input instead of it.
+const HOT_MODEL_VAR: &str = "HOT_MODEL_LIBRARY";
+/// Environment variable with path of model script. If it's set, script model is run
+/// along with counter model and takes unfocused input instead of it, unless hot model runs.
+const SCRIPT_MODEL_VAR: &str = "SCRIPT_MODEL_FILE";
+
+/// Gives input focus to the next model by priority. After the last one unfocused routing returns.
+const FOCUS_KEY: VirtualKeyCode = VirtualKeyCode::F2;
+
+/// Send mouse events emulated by primary touch along with touch events.
+const EMULATE_MOUSE_WITH_TOUCH: bool = true;
+
+/// Count of threads running model managers.
+const SCHEDULER_WORKERS: usize = 2;
+
+/// Normal count of model updates per second.
+const TICK_RATE: f64 = 60.0;
+
+/// Debug overlay and console are redrawn with this period while shown.
+const DEBUG_FRAME_PERIOD: Duration = Duration::from_millis(16);
+
+/// Count of recent log lines, kept for debug overlay.
+const LOG_BUFFER_LINES: usize = 64;
+
+/// Events sent to main event loop from other threads.
+#[derive(Debug)]
+enum AppEvent {
+    /// Application can't continue and must exit.
+    Exit,
+    /// Model with given id requested something from application.
+    Request(ModelId, Request),
+}
 
 fn main() {
-    let (logger, event_loop, _window, mm_bonds) = init().unwrap_or_else(|e| {
+    let Initialized {
+        logger,
+        log_buffer,
+        settings_dir,
+        event_loop,
+        window,
+        mut geometry,
+        mm_bonds,
+        hot_bonds,
+        ball_bonds,
+        ecs_bonds,
+        script_bonds,
+        scheduler,
+    } = init().unwrap_or_else(|e| {
         let message = format!("Initialization error occurred: {}", e);
         show_error_message("Initialization error", message.as_str());
-        panic!(message);
+        panic!("{}", message);
     });
 
-    let (input_tx, _command_tx, _notification_rx, model_rx) = (
+    let (input_tx, command_tx, notification_rx, model_rx, request_rx, status) = (
         mm_bonds.input_tx,
         mm_bonds.command_tx,
         mm_bonds.notification_rx,
         mm_bonds.model_rx,
+        mm_bonds.request_rx,
+        mm_bonds.status,
+    );
+    let mut registry = ModelRegistry::new(logger.clone());
+    registry.register(COUNTER_MODEL, 0, input_tx, model_rx);
+    forward_requests(COUNTER_MODEL, request_rx, event_loop.create_proxy());
+    let mut model_exits = vec![exit_model(command_tx.clone())];
+    let ball_tx = register_model(
+        &mut registry,
+        BALL_MODEL,
+        -1,
+        ball_bonds,
+        event_loop.create_proxy(),
+        &logger,
+    );
+    model_exits.push(exit_model(ball_tx));
+    let ecs_tx = register_model(
+        &mut registry,
+        ECS_MODEL,
+        -2,
+        ecs_bonds,
+        event_loop.create_proxy(),
+        &logger,
+    );
+    model_exits.push(exit_model(ecs_tx));
+    if let Some(hot_bonds) = hot_bonds {
+        let proxy = event_loop.create_proxy();
+        let command_tx = register_model(&mut registry, HOT_MODEL, 2, hot_bonds, proxy, &logger);
+        model_exits.push(exit_model(command_tx));
+    }
+    if let Some(script_bonds) = script_bonds {
+        let proxy = event_loop.create_proxy();
+        let command_tx =
+            register_model(&mut registry, SCRIPT_MODEL, 1, script_bonds, proxy, &logger);
+        model_exits.push(exit_model(command_tx));
+    }
+    let mut scheduler = Some(scheduler);
+    let (mut input_router, model_feed) = registry.split();
+
+    let mut debug_controls = DebugControls::new(
+        command_tx.clone(),
+        status.clone(),
+        TICK_RATE,
+        logger.clone(),
+    );
+    let mut console = Console::new(
+        command_tx.clone(),
+        status.clone(),
+        settings_dir.clone(),
+        logger.clone(),
     );
+    let mut debug_overlay = DebugOverlay::new(
+        window.id(),
+        command_tx.clone(),
+        status,
+        log_buffer,
+        TICK_RATE,
+        logger.clone(),
+    );
+    let mut supervisor = Supervisor::new(
+        command_tx,
+        notification_rx,
+        Policy::Restart { attempts: 3 },
+        CounterModel::new(),
+        logger.clone(),
+    );
+
+    let mut windows = Windows::new(window, logger.clone());
+    let mut title = String::from(WINDOW_TITLE);
+    let mut text_input = TextInput::new(logger.clone());
+    let mut touch_emulation = TouchEmulation::new();
+    let mut gestures = GestureRecognizer::new(GestureConfig::default());
+    gestures.add_combo(CounterModel::reset_combo());
+    let proxy: EventLoopProxy<AppEvent> = event_loop.create_proxy();
     std::thread::spawn(move || loop {
-        if let Ok(model) = model_rx.try_recv() {
-            println!("Got model. Count: {:?}", model.count());
+        for published in model_feed.poll() {
+            if let Some(model) = published.downcast::<CounterModel>() {
+                println!("Got model {}. Count: {:?}", published.id, model.count());
+                supervisor.remember(&model);
+            }
+        }
+        if let Some(Decision::Abort) = supervisor.supervise() {
+            let message = supervisor.abort_reason().unwrap_or("Model manager failed");
+            show_error_message("Model manager error", message);
+            proxy.send_event(AppEvent::Exit).ok();
+            break;
         }
         std::thread::sleep(Duration::from_millis(16));
     });
 
     info!(logger, "Initialization done");
 
-    event_loop.run(move |event, _, control_flow| {
-        *control_flow = ControlFlow::Wait;
+    let mut shown_mode = debug_controls.mode();
+    // Debug overlay status and console prompt, shown in title, while presenter can't draw them.
+    let mut shown_overlay: Option<String> = None;
+    event_loop.run(move |event, target, control_flow| {
+        for gesture in gestures.poll(Instant::now()) {
+            input_router.route(gesture);
+        }
+        // Debug mode changes, when model manager interprets command, whoever sent it.
+        let mode = debug_controls.mode();
+        if mode != shown_mode {
+            shown_mode = mode;
+            show_title(windows.main(), &title, mode, shown_overlay.as_deref());
+        }
+        let debug_frame_at = if console.is_open() || debug_overlay.is_visible() {
+            Some(Instant::now() + DEBUG_FRAME_PERIOD)
+        } else {
+            None
+        };
+        let deadline = match (gestures.deadline(), debug_frame_at) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        *control_flow = match deadline {
+            Some(deadline) => ControlFlow::WaitUntil(deadline),
+            None => ControlFlow::Wait,
+        };
+
+        match event {
+            Event::WindowEvent {
+                event: WindowEvent::Resized(_),
+                window_id,
+            }
+            | Event::WindowEvent {
+                event: WindowEvent::Moved(_),
+                window_id,
+            } if windows.is_main(window_id) => {
+                geometry.update(windows.main());
+                return;
+            }
+            Event::MainEventsCleared => {
+                if let Some(draw_list) = debug_overlay.frame(Instant::now(), &input_router.queued())
+                {
+                    trace!(
+                        logger,
+                        "Debug overlay frame: {} draw commands",
+                        draw_list.commands.len()
+                    );
+                }
+                let summaries: Vec<String> = vec![debug_overlay.summary(), console.summary()]
+                    .into_iter()
+                    .flatten()
+                    .collect();
+                let overlay = Some(summaries.join(" | "))
+                    .filter(|_| !DRAWS_DEBUG_UI && !summaries.is_empty());
+                if overlay != shown_overlay {
+                    shown_overlay = overlay;
+                    show_title(windows.main(), &title, shown_mode, shown_overlay.as_deref());
+                }
+                if let Some(draw_list) = console.frame() {
+                    trace!(
+                        logger,
+                        "Console frame: {} draw commands",
+                        draw_list.commands.len()
+                    );
+                }
+                return;
+            }
+            Event::LoopDestroyed => {
+                geometry.update(windows.main());
+                let path = settings_dir.join(WINDOW_GEOMETRY_FILE);
+                if let Err(e) = geometry.save(&path) {
+                    warn!(logger, "Can't save window geometry: {}", e);
+                }
+                model_exits.drain(..).for_each(|exit| exit());
+                if let Some(scheduler) = scheduler.take() {
+                    scheduler.join();
+                }
+                return;
+            }
+            Event::UserEvent(AppEvent::Exit) => {
+                info!(logger, "Exit requested. Exiting...");
+                *control_flow = ControlFlow::Exit;
+                return;
+            }
+            Event::UserEvent(AppEvent::Request(_, Request::Exit)) => {
+                info!(logger, "Model requested exit. Exiting...");
+                *control_flow = ControlFlow::Exit;
+                return;
+            }
+            Event::UserEvent(AppEvent::Request(_, Request::SetTitle(new_title))) => {
+                title = new_title;
+                show_title(windows.main(), &title, shown_mode, shown_overlay.as_deref());
+                return;
+            }
+            Event::UserEvent(AppEvent::Request(id, Request::OpenWindow { title, size })) => {
+                match windows.open(target, &title, size) {
+                    Ok(window_id) => {
+                        input_router.bind_window(window_id, Some(id));
+                        let opened: Input = (None, InputEvent::WindowOpened { title }).into();
+                        input_router.send(id, opened.in_window(Some(window_id)));
+                    }
+                    Err(e) => warn!(logger, "Can't open window: {}", e),
+                }
+                return;
+            }
+            Event::UserEvent(AppEvent::Request(_, Request::CloseWindow(window_id))) => {
+                if !close_window(&mut windows, &mut input_router, window_id) {
+                    warn!(logger, "Can't close window {:?}", window_id);
+                }
+                return;
+            }
+            Event::UserEvent(AppEvent::Request(_, request)) => {
+                apply_request(request, windows.main(), &mut text_input, &logger);
+                return;
+            }
+            _ => {}
+        }
+
+        if let Handled::Shortcut(paste) = text_input.handle_event(&event) {
+            if let Some(paste) = paste {
+                route(&input_router, &mut gestures, paste);
+            }
+            return;
+        }
 
         if let Ok(input) = (&event).try_into() {
-            input_tx.send(input).unwrap_or_else(|e| {
-                warn!(logger, "Can't send input event, because: {}", e);
-            });
+            let input = text_input.convert(input);
+            if debug_overlay.handle(&input) || console.handle(&input) {
+                return;
+            }
+            if debug_controls.handle(&input) {
+                return;
+            }
+            if let InputEvent::Keyboard {
+                key: FOCUS_KEY,
+                state,
+            } = input.event()
+            {
+                if *state == ElementState::Pressed {
+                    let focus = input_router.focus_next();
+                    info!(logger, "Input focus: {:?}", focus);
+                }
+                return;
+            }
+            if EMULATE_MOUSE_WITH_TOUCH {
+                for emulated in touch_emulation.emulate(&input) {
+                    route(&input_router, &mut gestures, emulated);
+                }
+            }
+            route(&input_router, &mut gestures, input);
             return;
         }
 
         if let Event::WindowEvent {
             event: WindowEvent::CloseRequested,
-            ..
+            window_id,
         } = event
         {
-            info!(logger, "Exiting...");
-            *control_flow = ControlFlow::Exit;
+            if windows.is_main(window_id) {
+                info!(logger, "Exiting...");
+                *control_flow = ControlFlow::Exit;
+            } else {
+                close_window(&mut windows, &mut input_router, window_id);
+            }
         }
     });
 }
 
+/// Applies model request to application.
+fn apply_request(request: Request, window: &Window, text_input: &mut TextInput, logger: &Logger) {
+    trace!(logger, "Applying model request: {:?}", request);
+    let result = match request {
+        Request::TextInput(_) => {
+            text_input.apply(&request);
+            Ok(())
+        }
+        Request::GrabCursor(grab) => window.set_cursor_grab(grab),
+        Request::HideCursor(hide) => {
+            window.set_cursor_visible(!hide);
+            Ok(())
+        }
+        Request::SetCursorIcon(icon) => {
+            window.set_cursor_icon(icon);
+            Ok(())
+        }
+        Request::WarpCursor(position) => {
+            window.set_cursor_position(PhysicalPosition::new(position.0, position.1))
+        }
+        Request::RelativeMouse(enabled) => {
+            window.set_cursor_visible(!enabled);
+            window.set_cursor_grab(enabled)
+        }
+        Request::Resize((width, height)) => {
+            window.set_inner_size(PhysicalSize::new(width, height));
+            Ok(())
+        }
+        Request::SetFullscreen(enabled) => {
+            set_fullscreen(window, enabled);
+            Ok(())
+        }
+        Request::ToggleFullscreen => {
+            set_fullscreen(window, window.fullscreen().is_none());
+            Ok(())
+        }
+        Request::SetDecorations(enabled) => {
+            window.set_decorations(enabled);
+            Ok(())
+        }
+        Request::SetTitle(_)
+        | Request::OpenWindow { .. }
+        | Request::CloseWindow(_)
+        | Request::Exit => {
+            warn!(
+                logger,
+                "Request must be handled by event loop: {:?}", request
+            );
+            Ok(())
+        }
+    };
+    result.unwrap_or_else(|e| warn!(logger, "Can't apply model request: {}", e));
+}
+
+/// Enters borderless fullscreen on monitor, containing window, or leaves fullscreen.
+fn set_fullscreen(window: &Window, enabled: bool) {
+    let fullscreen = if enabled {
+        Some(Fullscreen::Borderless(window.current_monitor()))
+    } else {
+        None
+    };
+    window.set_fullscreen(fullscreen);
+}
+
+/// Shows title, requested by model, along with debug mode, if it's not normal,
+/// and debug overlay status or console prompt, if they are shown.
+fn show_title(window: &Window, title: &str, mode: DebugMode, overlay: Option<&str>) {
+    let mut shown = title.to_string();
+    if !mode.is_normal() {
+        shown = format!("{} [{}]", shown, mode);
+    }
+    if let Some(overlay) = overlay {
+        shown = format!("{} - {}", shown, overlay);
+    }
+    window.set_title(&shown);
+}
+
+/// Closes tool window and tells model, which opened it. Returns false if there is no such window.
+fn close_window(windows: &mut Windows, router: &mut InputRouter, window_id: WindowId) -> bool {
+    if !windows.close(window_id) {
+        return false;
+    }
+    if let Some(owner) = router.window_owner(window_id) {
+        router.bind_window(window_id, None);
+        let closed: Input = (None, InputEvent::WindowClosed).into();
+        router.send(owner, closed.in_window(Some(window_id)));
+    }
+    true
+}
+
+/// Sends input to model, followed by gestures it completes.
+fn route(router: &InputRouter, gestures: &mut GestureRecognizer, input: Input) {
+    let completed = gestures.feed(&input);
+    router.route(input);
+    for gesture in completed {
+        router.route(gesture);
+    }
+}
+
+/// Basis structures, created by `init`.
+struct Initialized {
+    logger: Logger,
+    /// Recent log lines, collected by logger.
+    log_buffer: LogBuffer,
+    settings_dir: PathBuf,
+    event_loop: EventLoop<AppEvent>,
+    window: Window,
+    /// Geometry, the main window was restored with.
+    geometry: WindowGeometry,
+    mm_bonds: OuterBonds<CounterModel>,
+    /// Bonds of hot model manager, if hot model library is given.
+    hot_bonds: Option<OuterBonds<DylibModel>>,
+    ball_bonds: OuterBonds<BallModel>,
+    ecs_bonds: OuterBonds<EcsCounterModel>,
+    /// Bonds of script model manager, if model script is given.
+    script_bonds: Option<OuterBonds<ScriptModel>>,
+    /// Runs all model managers. Must be joined on exit.
+    scheduler: Scheduler,
+}
+
 /// Basis structures initialization
-fn init() -> Result<(Logger, EventLoop<()>, Window, OuterBonds<CounterModel>), InitError> {
+fn init() -> Result<Initialized, InitError> {
     let mut save_path = default_settings_path()?;
     save_path.push("InfrastructurePrototype");
 
     // Init logger
-    let logger = init_logger(save_path)?;
+    let (logger, log_buffer) = init_logger(save_path.clone())?;
     info!(logger, "=============== START NEW SESSION ===============");
     trace!(logger, "Logger initilized");
 
     // Init event loop
-    let event_loop = EventLoop::new();
+    let event_loop = EventLoop::with_user_event();
     trace!(logger, "Event loop initialized");
 
     // Init window
-    let window = init_window(&event_loop)?;
+    let geometry = load_window_geometry(&save_path, &logger);
+    let window = init_window(&event_loop, &geometry, &logger)?;
     trace!(logger, "Window initialized");
 
     // Init input printer
@@ -98,23 +551,143 @@ fn init() -> Result<(Logger, EventLoop<()>, Window, OuterBonds<CounterModel>), I
 
     let (model_manager, bonds) = ModelManager::new(Arc::new(CounterModel::new()), logger.clone());
 
-    std::thread::spawn(|| model_manager.run());
+    let scheduler = Scheduler::new(SCHEDULER_WORKERS, logger.clone());
+    scheduler.spawn(model_manager);
+    bonds
+        .command_tx
+        .send(Command::SetTickRate(Some(TICK_RATE)))
+        .unwrap();
     bonds.command_tx.send(Command::Run).unwrap();
 
-    Ok((logger, event_loop, window, bonds))
+    let ball_bonds = spawn_model(&scheduler, BallModel::new(), &logger);
+    let ecs_bonds = spawn_model(&scheduler, EcsCounterModel::new(), &logger);
+    let hot_bonds = std::env::var_os(HOT_MODEL_VAR).map(|path| {
+        info!(logger, "Running hot model from {:?}", path);
+        spawn_model(&scheduler, DylibModel::new(PathBuf::from(path)), &logger)
+    });
+    let script_bonds = std::env::var_os(SCRIPT_MODEL_VAR).map(|path| {
+        info!(logger, "Running script model from {:?}", path);
+        spawn_model(&scheduler, ScriptModel::new(PathBuf::from(path)), &logger)
+    });
+
+    Ok(Initialized {
+        logger,
+        log_buffer,
+        settings_dir: save_path,
+        event_loop,
+        window,
+        geometry,
+        mm_bonds: bonds,
+        hot_bonds,
+        ball_bonds,
+        ecs_bonds,
+        script_bonds,
+        scheduler,
+    })
+}
+
+/// Spawns running manager of model, which works along with counter model.
+fn spawn_model<M>(scheduler: &Scheduler, model: M, logger: &Logger) -> OuterBonds<M>
+where
+    M: Model + Serialize + DeserializeOwned + Send + Sync + 'static,
+    M::PriorResult: Send,
+{
+    let (model_manager, bonds) = ModelManager::new(Arc::new(model), logger.clone());
+    scheduler.spawn(model_manager);
+    bonds
+        .command_tx
+        .send(Command::SetTickRate(Some(TICK_RATE)))
+        .unwrap();
+    bonds.command_tx.send(Command::Run).unwrap();
+    bonds
+}
+
+/// Registers model, spawned by `spawn_model`, forwards its requests and logs its notifications.
+/// Returns command sender of its manager.
+fn register_model<M: Any + Send + Sync>(
+    registry: &mut ModelRegistry,
+    id: ModelId,
+    priority: i32,
+    bonds: OuterBonds<M>,
+    proxy: EventLoopProxy<AppEvent>,
+    logger: &Logger,
+) -> CommandSender<M> {
+    let AsyncBonds {
+        input_tx,
+        command_tx,
+        notifications,
+        models,
+        requests,
+        ..
+    } = AsyncBonds::new(bonds);
+    registry.register(id, priority, input_tx, models);
+    let logger = logger.clone();
+    // Single thread waits for both, until model manager exits.
+    std::thread::spawn(move || {
+        let mut events =
+            stream::select(notifications.map(Either::Left), requests.map(Either::Right));
+        block_on(async {
+            while let Some(event) = events.next().await {
+                match event {
+                    Either::Left(notification) => {
+                        warn!(logger, "Model {:?}: {:?}", id, notification)
+                    }
+                    Either::Right(request) => {
+                        if proxy.send_event(AppEvent::Request(id, request)).is_err() {
+                            break;
+                        }
+                    }
+                }
+            }
+        })
+    });
+    command_tx
+}
+
+/// Sends requests of model to event loop, until model manager or event loop exits.
+fn forward_requests(id: ModelId, request_rx: Receiver<Request>, proxy: EventLoopProxy<AppEvent>) {
+    std::thread::spawn(move || {
+        for request in request_rx.iter() {
+            if proxy.send_event(AppEvent::Request(id, request)).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+/// Returns closure, which tells model manager to exit, so scheduler can be joined.
+fn exit_model<M: 'static>(command_tx: CommandSender<M>) -> Box<dyn FnOnce()> {
+    Box::new(move || {
+        command_tx.send(Command::Exit).ok();
+    })
+}
+
+/// Loads main window geometry, saved by previous session, or returns default one.
+fn load_window_geometry(save_path: &Path, logger: &Logger) -> WindowGeometry {
+    let path = save_path.join(WINDOW_GEOMETRY_FILE);
+    if !path.exists() {
+        return WindowGeometry::default();
+    }
+    WindowGeometry::load(&path).unwrap_or_else(|e| {
+        warn!(logger, "Can't load window geometry: {}", e);
+        WindowGeometry::default()
+    })
 }
 
 /// Window initialization
-fn init_window(event_loop: &EventLoop<()>) -> Result<Window, OsError> {
-    let window_builder = WindowBuilder::default()
-        .with_title("InfrastructurePrototype")
-        .with_inner_size(PhysicalSize::new(800, 600));
-    let window = window_builder.build(&event_loop)?;
+fn init_window(
+    event_loop: &EventLoop<AppEvent>,
+    geometry: &WindowGeometry,
+    logger: &Logger,
+) -> Result<Window, OsError> {
+    let window_builder = WindowBuilder::default().with_title(WINDOW_TITLE);
+    let window = geometry.build(window_builder, event_loop, logger)?;
     Ok(window)
 }
 
 /// Logger initialization
-fn init_logger(save_path: PathBuf) -> Result<Logger, LogInitError> {
+/// Logger writes to file and keeps recent lines of info level and above in log buffer.
+fn init_logger(save_path: PathBuf) -> Result<(Logger, LogBuffer), LogInitError> {
     let log_dir = save_path.join("logs");
     let log_path = save_path.join("logs\\log");
     std::fs::create_dir_all(log_dir)?;
@@ -123,5 +696,8 @@ fn init_logger(save_path: PathBuf) -> Result<Logger, LogInitError> {
         .rotate_size(10 * 2u64.pow(20))
         .level(Severity::Trace)
         .build()?;
-    Ok(logger)
+    let log_buffer = LogBuffer::new(LOG_BUFFER_LINES);
+    let drain = Duplicate::new(logger, LevelFilter::new(log_buffer.clone(), Level::Info));
+    let logger = Logger::root(drain.ignore_res(), o!());
+    Ok((logger, log_buffer))
 }
@@ -0,0 +1,212 @@
+use crate::model::input_queue::InputSender;
+use crate::model::model_manager::{Command, CommandSender, Notification, OuterBonds};
+use crate::model::request::Request;
+use crate::model::status::StatusWatch;
+use futures::channel::oneshot;
+use futures::task::AtomicWaker;
+use futures::Stream;
+use std::pin::Pin;
+use std::sync::mpsc::{channel, Receiver, SendError, Sender, TryRecvError};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+/// Alternative to OuterBonds for async code: models, notifications and requests are streams,
+/// and sent commands can be awaited until model manager interprets them.
+/// Streams are woken by model manager itself, so no threads are spawned.
+pub struct AsyncBonds<M> {
+    pub input_tx: InputSender,
+    pub command_tx: CommandSender<M>,
+    pub notifications: BondStream<Notification>,
+    pub models: BondStream<Arc<M>>,
+    pub requests: BondStream<Request>,
+    pub status: StatusWatch,
+}
+
+impl<M> AsyncBonds<M> {
+    pub fn new(bonds: OuterBonds<M>) -> Self {
+        let wakers = bonds.wakers;
+        Self {
+            input_tx: bonds.input_tx,
+            command_tx: bonds.command_tx,
+            notifications: BondStream::new(bonds.notification_rx, wakers.notification),
+            models: BondStream::new(bonds.model_rx, wakers.model),
+            requests: BondStream::new(bonds.request_rx, wakers.request),
+            status: bonds.status,
+        }
+    }
+
+    /// Sends command. Returned future resolves when model manager interprets it,
+    /// or to `Canceled` error if model manager exits before that.
+    pub fn command(
+        &self,
+        command: Command<M>,
+    ) -> Result<oneshot::Receiver<()>, SendError<Command<M>>> {
+        let (ack_tx, ack_rx) = oneshot::channel();
+        self.command_tx.send(command)?;
+        self.command_tx.send(Command::Callback(Box::new(move || {
+            ack_tx.send(()).ok();
+        })))?;
+        Ok(ack_rx)
+    }
+}
+
+/// Wakers of outer bonds receivers, woken by model manager after it sends to them.
+#[derive(Debug, Default)]
+pub(crate) struct BondWakers {
+    notification: Arc<AtomicWaker>,
+    model: Arc<AtomicWaker>,
+    request: Arc<AtomicWaker>,
+}
+
+impl BondWakers {
+    pub(crate) fn notification_channel<T>(&self) -> (WakingSender<T>, Receiver<T>) {
+        waking_channel(&self.notification)
+    }
+
+    pub(crate) fn model_channel<T>(&self) -> (WakingSender<T>, Receiver<T>) {
+        waking_channel(&self.model)
+    }
+
+    pub(crate) fn request_channel<T>(&self) -> (WakingSender<T>, Receiver<T>) {
+        waking_channel(&self.request)
+    }
+}
+
+fn waking_channel<T>(waker: &Arc<AtomicWaker>) -> (WakingSender<T>, Receiver<T>) {
+    let (tx, rx) = channel();
+    let tx = WakingSender {
+        tx,
+        waker: waker.clone(),
+    };
+    (tx, rx)
+}
+
+/// Sender, which wakes stream of receiving end after every send and when it's dropped.
+#[derive(Debug)]
+pub(crate) struct WakingSender<T> {
+    tx: Sender<T>,
+    waker: Arc<AtomicWaker>,
+}
+
+impl<T> WakingSender<T> {
+    pub(crate) fn send(&self, value: T) -> Result<(), SendError<T>> {
+        self.tx.send(value)?;
+        self.waker.wake();
+        Ok(())
+    }
+}
+
+impl<T> Drop for WakingSender<T> {
+    fn drop(&mut self) {
+        // Stream must see disconnected channel, when it's woken, so sender is dropped first.
+        let (detached, _) = channel();
+        drop(std::mem::replace(&mut self.tx, detached));
+        self.waker.wake();
+    }
+}
+
+/// Receiver of model manager channel as stream. Ends when model manager exits.
+#[derive(Debug)]
+pub struct BondStream<T> {
+    rx: Receiver<T>,
+    waker: Arc<AtomicWaker>,
+}
+
+impl<T> BondStream<T> {
+    fn new(rx: Receiver<T>, waker: Arc<AtomicWaker>) -> Self {
+        Self { rx, waker }
+    }
+
+    /// Returns received value without waiting, like `Receiver::try_recv`.
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        self.rx.try_recv()
+    }
+}
+
+impl<T> Stream for BondStream<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let poll = |rx: &Receiver<T>| match rx.try_recv() {
+            Ok(value) => Poll::Ready(Some(value)),
+            Err(TryRecvError::Disconnected) => Poll::Ready(None),
+            Err(TryRecvError::Empty) => Poll::Pending,
+        };
+        if let Poll::Ready(item) = poll(&self.rx) {
+            return Poll::Ready(item);
+        }
+        // Checks again after registering, so value sent in between isn't missed.
+        self.waker.register(cx.waker());
+        poll(&self.rx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::{Input, InputEvent};
+    use crate::model::counter_model::CounterModel;
+    use crate::model::model_manager::ModelManager;
+    use futures::executor::block_on;
+    use futures::StreamExt;
+    use slog::{o, Discard, Logger};
+    use std::time::{Duration, Instant};
+    use winit::event::{ElementState, VirtualKeyCode};
+
+    fn bonds() -> (ModelManager<CounterModel>, AsyncBonds<CounterModel>) {
+        let logger = Logger::root(Discard, o!());
+        let (manager, bonds) = ModelManager::new(Arc::new(CounterModel::new()), logger);
+        (manager, AsyncBonds::new(bonds))
+    }
+
+    #[test]
+    fn model_is_awaited_without_polling() {
+        let (mut manager, mut bonds) = bonds();
+        let ticking = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(10));
+            manager.tick_now();
+            manager
+        });
+        let model = block_on(bonds.models.next()).unwrap();
+        assert_eq!(model.count(), 0);
+        drop(model);
+
+        // Stream ends, when model manager exits.
+        drop(ticking.join().unwrap());
+        assert!(block_on(bonds.models.next()).is_none());
+        assert!(block_on(bonds.notifications.next()).is_none());
+    }
+
+    #[test]
+    fn requests_are_streamed() {
+        let (mut manager, mut bonds) = bonds();
+        let key = |state| InputEvent::Keyboard {
+            key: VirtualKeyCode::Up,
+            state,
+        };
+        for state in [ElementState::Pressed, ElementState::Released] {
+            let input = Input::new(Instant::now(), None, key(state));
+            bonds.input_tx.send(input).unwrap();
+        }
+        manager.tick_now();
+        let request = block_on(bonds.requests.next());
+        assert_eq!(request, Some(Request::SetTitle(String::from("Count: 1"))));
+    }
+
+    #[test]
+    fn command_is_acknowledged_after_it_is_interpreted() {
+        let (mut manager, bonds) = bonds();
+        let ack = bonds.command(Command::SetTickRate(Some(30.0))).unwrap();
+        let running = std::thread::spawn(move || {
+            manager.run_once();
+            manager
+        });
+        block_on(ack).unwrap();
+        drop(running.join().unwrap());
+        let rate = bonds.status.get().tick_rate.unwrap();
+        assert!((rate - 30.0).abs() < 1e-3);
+
+        let ack = bonds.command(Command::Run);
+        assert!(ack.is_err() || block_on(ack.unwrap()).is_err());
+    }
+}
@@ -0,0 +1,133 @@
+use super::jobs::JobSystem;
+use super::{Model, State};
+use crate::input::Input;
+use crate::input_state::InputState;
+use crate::physics::body::{BodyHandle, Collider, RigidBody};
+use crate::physics::shape::Shape;
+use crate::physics::vector::Vec2;
+use crate::physics::world::{CollisionEvent, PhysicsWorld};
+use serde::{Deserialize, Serialize};
+use winit::event::VirtualKeyCode;
+
+/// Tick rate, physics runs with until model manager tells its own one.
+const DEFAULT_TICK_RATE: f64 = 60.0;
+const GRAVITY: Vec2 = Vec2::new(0.0, -9.8);
+/// Velocity, Space gives to ball.
+const KICK: Vec2 = Vec2::new(0.0, 5.0);
+
+/// Ball, which bounces on floor and is kicked up by Space.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BallModel {
+    physics: PhysicsWorld,
+    ball: BodyHandle,
+    floor: BodyHandle,
+    bounces: u32,
+}
+
+impl BallModel {
+    pub fn new() -> Self {
+        let mut physics = PhysicsWorld::for_tick_rate(DEFAULT_TICK_RATE).with_gravity(GRAVITY);
+        let floor = physics.add(RigidBody::fixed(Collider::new(Shape::aabb(10.0, 1.0))));
+        let ball = physics.add(
+            RigidBody::dynamic(Collider::new(Shape::circle(0.25)).with_restitution(0.8))
+                .with_position(Vec2::new(0.0, 2.0)),
+        );
+        Self {
+            physics,
+            ball,
+            floor,
+            bounces: 0,
+        }
+    }
+
+    /// Times ball hit floor.
+    pub fn bounces(&self) -> u32 {
+        self.bounces
+    }
+
+    pub fn height(&self) -> f64 {
+        self.physics
+            .get(self.ball)
+            .map_or(0.0, |ball| ball.position.y)
+    }
+
+    pub fn physics(&self) -> &PhysicsWorld {
+        &self.physics
+    }
+}
+
+impl Model for BallModel {
+    type PriorResult = ();
+
+    fn prior(&self, _: Option<Self::PriorResult>, _: &JobSystem) -> State<Self::PriorResult> {
+        State::Running(None)
+    }
+
+    fn update(
+        &mut self,
+        _: Option<Self::PriorResult>,
+        _: impl Iterator<Item = Input>,
+        input_state: &InputState,
+        _: &JobSystem,
+    ) -> State<Self::PriorResult> {
+        if input_state.just_pressed(VirtualKeyCode::Space) {
+            if let Some(ball) = self.physics.get_mut(self.ball) {
+                ball.velocity = KICK;
+            }
+        }
+        self.physics.tick();
+        for event in self.physics.take_events() {
+            if let CollisionEvent::Started { a, b, .. } = event {
+                if (a, b) == (self.floor, self.ball) || (a, b) == (self.ball, self.floor) {
+                    self.bounces += 1;
+                }
+            }
+        }
+        State::Running(None)
+    }
+
+    /// As fast as possible, physics keeps its previous rate.
+    fn set_tick_rate(&mut self, rate: Option<f64>) {
+        if let Some(rate) = rate {
+            self.physics.set_tick_rate(rate);
+        }
+    }
+
+    fn variables(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("bounces", self.bounces.to_string()),
+            ("height", format!("{:.2}", self.height())),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::harness::Harness;
+    use crate::model::model_manager::Command;
+
+    #[test]
+    fn physics_follows_tick_rate() {
+        let mut harness = Harness::new(BallModel::new());
+        harness.command(Command::SetTickRate(Some(30.0))).tick();
+        // Tick period is kept as duration, so rate is rounded.
+        let timestep = harness.inspect(|model| model.physics().timestep());
+        assert!((timestep - 1.0 / 30.0).abs() < 1e-6);
+
+        // As fast as possible keeps previous timestep.
+        harness.command(Command::SetTickRate(None)).tick();
+        assert_eq!(
+            harness.inspect(|model| model.physics().timestep()),
+            timestep
+        );
+    }
+
+    #[test]
+    fn ball_bounces_on_floor() {
+        let mut harness = Harness::new(BallModel::new());
+        harness.ticks(60);
+        assert!(harness.inspect(|model| model.bounces()) >= 1);
+        assert!(harness.inspect(|model| model.height()) > 0.5);
+    }
+}
@@ -0,0 +1,40 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Source of current time for model manager.
+#[derive(Debug, Clone, Default)]
+pub enum Clock {
+    #[default]
+    System,
+    /// Time, which moves only when told, so model manager schedule is deterministic.
+    Virtual(Arc<Mutex<Instant>>),
+}
+
+impl Clock {
+    /// Virtual clock, which starts at the current system time.
+    pub fn virtual_now() -> Self {
+        Clock::Virtual(Arc::new(Mutex::new(Instant::now())))
+    }
+
+    pub fn now(&self) -> Instant {
+        match self {
+            Clock::System => Instant::now(),
+            Clock::Virtual(now) => *now.lock().unwrap(),
+        }
+    }
+
+    /// Moves virtual time forward. System time can't be moved.
+    pub fn advance(&self, duration: Duration) {
+        if let Clock::Virtual(now) = self {
+            *now.lock().unwrap() += duration;
+        }
+    }
+
+    /// Sets virtual time, if it is later than current one.
+    pub fn advance_to(&self, at: Instant) {
+        if let Clock::Virtual(now) = self {
+            let mut now = now.lock().unwrap();
+            *now = (*now).max(at);
+        }
+    }
+}
@@ -1,11 +1,23 @@
+use super::jobs::JobSystem;
+use super::request::Request;
+use super::{Model, State};
+use crate::gestures::{Combo, ComboKind, Gesture};
 use crate::input::{Input, InputEvent};
+use crate::input_state::InputState;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
 use winit::event::{ElementState, VirtualKeyCode};
-use super::{State, Model};
 
+/// Name of combo, which resets counter.
+pub const RESET_COMBO: &str = "counter reset";
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct CounterModel {
     counter: i32,
     up_state: ElementState,
     down_state: ElementState,
+    #[serde(skip)]
+    requests: Vec<Request>,
 }
 
 impl CounterModel {
@@ -14,6 +26,7 @@ impl CounterModel {
             counter: 0,
             up_state: ElementState::Released,
             down_state: ElementState::Released,
+            requests: Vec::new(),
         }
     }
 
@@ -34,12 +47,23 @@ impl CounterModel {
     pub fn count(&self) -> i32 {
         self.counter
     }
+
+    /// Combo, which resets counter: Backspace pressed twice quickly.
+    /// Application registers it in gesture recognizer.
+    pub fn reset_combo() -> Combo {
+        Combo {
+            name: RESET_COMBO,
+            kind: ComboKind::Sequence,
+            keys: vec![VirtualKeyCode::Back, VirtualKeyCode::Back],
+            window: Duration::from_millis(400),
+        }
+    }
 }
 
 impl Model for CounterModel {
     type PriorResult = ();
 
-    fn prior(&self, _: Option<Self::PriorResult>) -> State<Self::PriorResult> {
+    fn prior(&self, _: Option<Self::PriorResult>, _: &JobSystem) -> State<Self::PriorResult> {
         State::Running(None)
     }
 
@@ -47,17 +71,81 @@ impl Model for CounterModel {
         &mut self,
         _: Option<Self::PriorResult>,
         inputs: impl Iterator<Item = Input>,
+        input_state: &InputState,
+        _: &JobSystem,
     ) -> State<Self::PriorResult> {
+        let counter = self.counter;
+        // Every press counts, even if key was pressed several times during tick.
         for input in inputs {
-            if let InputEvent::Keyboard { key, state } = input.event() {
-                if *key == VirtualKeyCode::Up {
-                    self.up_event(*state)
-                }
-                if *key == VirtualKeyCode::Down {
-                    self.down_event(*state)
+            match input.event() {
+                InputEvent::Keyboard { key, state } => {
+                    if *key == VirtualKeyCode::Up {
+                        self.up_event(*state)
+                    }
+                    if *key == VirtualKeyCode::Down {
+                        self.down_event(*state)
+                    }
                 }
+                InputEvent::Gesture(Gesture::Combo(RESET_COMBO)) => self.counter = 0,
+                _ => {}
             }
         }
+        if self.counter != counter {
+            self.requests
+                .push(Request::SetTitle(format!("Count: {}", self.counter)));
+        }
+        if input_state.just_pressed(VirtualKeyCode::Escape) {
+            self.requests.push(Request::Exit);
+        }
         State::Running(None)
     }
+
+    fn take_requests(&mut self) -> Vec<Request> {
+        std::mem::take(&mut self.requests)
+    }
+
+    fn variables(&self) -> Vec<(&'static str, String)> {
+        vec![("counter", self.counter.to_string())]
+    }
+
+    fn set_variable(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "counter" => {
+                self.counter = value.parse().map_err(|e| format!("{}", e))?;
+                Ok(())
+            }
+            _ => Err(format!("Unknown variable: {}", name)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::harness::Harness;
+
+    #[test]
+    fn counts_every_press_during_tick() {
+        let mut harness = Harness::new(CounterModel::new());
+        harness
+            .press(VirtualKeyCode::Up)
+            .release(VirtualKeyCode::Up)
+            .press(VirtualKeyCode::Up)
+            .tick();
+        assert_eq!(harness.inspect(|model| model.count()), 2);
+
+        // Key repeat doesn't count while key is held.
+        harness.press(VirtualKeyCode::Up).tick();
+        assert_eq!(harness.inspect(|model| model.count()), 2);
+    }
+
+    #[test]
+    fn reset_combo_resets_counter() {
+        let mut harness = Harness::new(CounterModel::new());
+        harness.press(VirtualKeyCode::Up).tick();
+        harness
+            .input(InputEvent::Gesture(Gesture::Combo(RESET_COMBO)))
+            .tick();
+        assert_eq!(harness.inspect(|model| model.count()), 0);
+    }
 }
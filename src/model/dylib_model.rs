@@ -0,0 +1,318 @@
+//! Model with logic in dynamic library, which is reloaded when library file changes.
+//!
+//! Library is a cdylib exporting C functions of hot model interface version `API_VERSION`:
+//!
+//! - `hot_model_api_version() -> u32`
+//! - `hot_model_create(state: *const u8, len: usize) -> *mut c_void` restores model from state,
+//!   saved by `hot_model_save`, or creates new model if `len` is 0. Returns null on failure.
+//! - `hot_model_destroy(model: *mut c_void)`
+//! - `hot_model_update(model: *mut c_void, input_state: *const u8, len: usize) -> bool` updates
+//!   model with bincode encoded `InputState`. Returns false when model is finished.
+//! - `hot_model_save(model: *const c_void, out: *mut c_void, write: WriteFn)` saves model state
+//!   by calling `write(out, data, len)` one or more times.
+//!
+//! Functions are called from different threads, so model must not depend on thread it was
+//! created on, e.g. use thread locals. Calls are never concurrent: host serializes them.
+//!
+//! Library is copied before loading, so original file can be rebuilt while copy is in use.
+//! `API_VERSION`, `WriteFn` and `InputState` are defined in `hot_model_api.rs`, which library
+//! includes too. See `examples/hot_counter.rs` for library, implementing this interface.
+
+use super::error::reload::ReloadError;
+use super::file_watch::FileWatch;
+use super::jobs::JobSystem;
+use super::{Model, State};
+use crate::hot_model_api::{WriteFn, API_VERSION};
+use crate::input::Input;
+use crate::input_state::InputState;
+use libloading::Library;
+use serde::de::Deserializer;
+use serde::ser::{Error as _, Serializer};
+use serde::{Deserialize, Serialize};
+use std::ffi::c_void;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+type ApiVersionFn = unsafe extern "C" fn() -> u32;
+type CreateFn = unsafe extern "C" fn(state: *const u8, len: usize) -> *mut c_void;
+type DestroyFn = unsafe extern "C" fn(model: *mut c_void);
+type UpdateFn =
+    unsafe extern "C" fn(model: *mut c_void, input_state: *const u8, len: usize) -> bool;
+type SaveFn = unsafe extern "C" fn(model: *const c_void, out: *mut c_void, write: WriteFn);
+
+/// Counter of library copies, making their names unique.
+static COPIES: AtomicUsize = AtomicUsize::new(0);
+
+/// Functions of loaded library, used after model is created.
+struct Api {
+    destroy: DestroyFn,
+    update: UpdateFn,
+    save: SaveFn,
+}
+
+/// Library loaded from copy, and model created by it.
+struct Loaded {
+    model: *mut c_void,
+    api: Api,
+    copy: PathBuf,
+    /// Taken out and unloaded after model is destroyed.
+    library: Option<Library>,
+}
+
+// Hot model interface requires library model to be usable from any thread.
+unsafe impl Send for Loaded {}
+
+impl Loaded {
+    /// Loads copy of library at `path` and creates model from `state`.
+    fn load(path: &Path, state: &[u8]) -> Result<Self, ReloadError> {
+        let copy = copy_path(path);
+        std::fs::copy(path, &copy)?;
+        let loaded = unsafe { Self::open(&copy, state) };
+        match loaded {
+            Ok((library, model, api)) => Ok(Self {
+                model,
+                api,
+                copy,
+                library: Some(library),
+            }),
+            Err(e) => {
+                std::fs::remove_file(&copy).ok();
+                Err(e)
+            }
+        }
+    }
+
+    /// Loads library, checks its interface and creates model.
+    unsafe fn open(path: &Path, state: &[u8]) -> Result<(Library, *mut c_void, Api), ReloadError> {
+        let library = Library::new(path)?;
+        let version = library.get::<ApiVersionFn>(b"hot_model_api_version\0")?();
+        if version != API_VERSION {
+            return Err(ReloadError::UnsupportedVersion(version));
+        }
+        let create = *library.get::<CreateFn>(b"hot_model_create\0")?;
+        let destroy = *library.get::<DestroyFn>(b"hot_model_destroy\0")?;
+        let update = *library.get::<UpdateFn>(b"hot_model_update\0")?;
+        let save = *library.get::<SaveFn>(b"hot_model_save\0")?;
+        let model = create(state.as_ptr(), state.len());
+        if model.is_null() {
+            return Err(ReloadError::StateRejected);
+        }
+        let api = Api {
+            destroy,
+            update,
+            save,
+        };
+        Ok((library, model, api))
+    }
+
+    fn save(&self) -> Vec<u8> {
+        let mut state = Vec::new();
+        unsafe {
+            (self.api.save)(
+                self.model,
+                &mut state as *mut Vec<u8> as *mut c_void,
+                write_state,
+            )
+        };
+        state
+    }
+}
+
+impl Drop for Loaded {
+    fn drop(&mut self) {
+        unsafe { (self.api.destroy)(self.model) };
+        // Copy can be removed only after library is unloaded.
+        drop(self.library.take());
+        std::fs::remove_file(&self.copy).ok();
+    }
+}
+
+unsafe extern "C" fn write_state(out: *mut c_void, data: *const u8, len: usize) {
+    let state = &mut *(out as *mut Vec<u8>);
+    state.extend_from_slice(std::slice::from_raw_parts(data, len));
+}
+
+fn copy_path(path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let copy = COPIES.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir().join(format!("{}-{}-{}", std::process::id(), copy, name))
+}
+
+/// Model, which logic is in dynamic library at `path`. Library is reloaded between ticks,
+/// when its file changes, and new logic continues with saved state of the old one.
+/// If library can't be loaded, model keeps working with previous library, or does nothing.
+pub struct DylibModel {
+    library: FileWatch,
+    /// Locked, so library calls on shared model, e.g. saving, are not concurrent.
+    loaded: Option<Mutex<Loaded>>,
+    /// State to restore model from, when library is loaded next time.
+    pending_state: Vec<u8>,
+}
+
+impl DylibModel {
+    /// Model is created by library on first update.
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            library: FileWatch::new(path),
+            loaded: None,
+            pending_state: Vec::new(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        self.library.path()
+    }
+
+    /// Loads library and moves state of current model to new one.
+    fn load(&mut self) -> Result<(), ReloadError> {
+        let state = match &mut self.loaded {
+            Some(loaded) => loaded.get_mut().unwrap().save(),
+            None => std::mem::take(&mut self.pending_state),
+        };
+        match Loaded::load(self.library.path(), &state) {
+            Ok(loaded) => {
+                self.loaded = Some(Mutex::new(loaded));
+                Ok(())
+            }
+            Err(e) => {
+                if self.loaded.is_none() {
+                    self.pending_state = state;
+                }
+                Err(e)
+            }
+        }
+    }
+}
+
+impl Model for DylibModel {
+    type PriorResult = ();
+
+    fn prior(&self, _: Option<Self::PriorResult>, _: &JobSystem) -> State<Self::PriorResult> {
+        State::Running(None)
+    }
+
+    /// Library gets only input state: input events are not passed to it.
+    fn update(
+        &mut self,
+        _: Option<Self::PriorResult>,
+        _: impl Iterator<Item = Input>,
+        input_state: &InputState,
+        _: &JobSystem,
+    ) -> State<Self::PriorResult> {
+        let loaded = match &mut self.loaded {
+            Some(loaded) => loaded.get_mut().unwrap(),
+            None => return State::Running(None),
+        };
+        let input_state = bincode::serialize(input_state).expect("can't encode input state");
+        let running =
+            unsafe { (loaded.api.update)(loaded.model, input_state.as_ptr(), input_state.len()) };
+        if running {
+            State::Running(None)
+        } else {
+            State::Finished
+        }
+    }
+
+    /// Broken library is not tried again until it changes.
+    fn reload(&mut self) -> Result<(), ReloadError> {
+        if !self.library.changed()? {
+            return Ok(());
+        }
+        self.load()
+    }
+
+    /// Deserialized model has no library, so it's loaded at once.
+    fn restore(&mut self) -> Result<(), ReloadError> {
+        self.library.mark_current()?;
+        self.load()
+    }
+}
+
+/// Serialized form of `DylibModel`: library path and model state, saved by library.
+#[derive(Serialize, Deserialize)]
+struct Saved {
+    path: PathBuf,
+    state: Vec<u8>,
+}
+
+impl Serialize for DylibModel {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let state = match &self.loaded {
+            Some(loaded) => loaded.lock().unwrap().save(),
+            None => self.pending_state.clone(),
+        };
+        if self.path().to_str().is_none() {
+            return Err(S::Error::custom("library path is not valid unicode"));
+        }
+        Saved {
+            path: self.path().to_path_buf(),
+            state,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for DylibModel {
+    /// Library is loaded on next reload check, so deserialization can't fail because of it.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let saved = Saved::deserialize(deserializer)?;
+        let mut model = DylibModel::new(saved.path);
+        model.pending_state = saved.state;
+        Ok(model)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::InputEvent;
+    use std::time::Instant;
+    use winit::event::{ElementState, VirtualKeyCode};
+
+    /// Example library. Cargo builds it along with tests, next to directory of test executable.
+    fn example_library() -> PathBuf {
+        let exe = std::env::current_exe().unwrap();
+        let name = format!(
+            "{}hot_counter{}",
+            std::env::consts::DLL_PREFIX,
+            std::env::consts::DLL_SUFFIX
+        );
+        let path = exe.parent().unwrap().parent().unwrap().join("examples");
+        path.join(name)
+    }
+
+    fn pressed(key: VirtualKeyCode) -> InputState {
+        let event = InputEvent::Keyboard {
+            key,
+            state: ElementState::Pressed,
+        };
+        let mut input_state = InputState::new();
+        input_state.apply(&Input::new(Instant::now(), None, event));
+        input_state
+    }
+
+    fn saved_count(model: &DylibModel) -> i32 {
+        let saved: Saved = bincode::deserialize(&bincode::serialize(model).unwrap()).unwrap();
+        bincode::deserialize(&saved.state).unwrap()
+    }
+
+    #[test]
+    fn reloaded_library_continues_with_saved_state() {
+        let jobs = JobSystem::new(1);
+        let mut model = DylibModel::new(example_library());
+        model.restore().unwrap();
+        let up = pressed(VirtualKeyCode::Up);
+        for _ in 0..2 {
+            let state = model.update(None, std::iter::empty(), &up, &jobs);
+            assert!(matches!(state, State::Running(None)));
+        }
+        assert_eq!(saved_count(&model), 2);
+
+        model.load().unwrap();
+        assert_eq!(saved_count(&model), 2);
+        let escape = pressed(VirtualKeyCode::Escape);
+        let state = model.update(None, std::iter::empty(), &escape, &jobs);
+        assert!(matches!(state, State::Finished));
+    }
+}
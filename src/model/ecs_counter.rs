@@ -0,0 +1,111 @@
+use super::ecs_model::{EcsModel, EcsSetup};
+use super::request::Request;
+use crate::ecs::schedule::{system, Access, Schedule};
+use crate::ecs::storage::Component;
+use crate::ecs::world::World;
+use crate::input::InputEvent;
+use serde::{Deserialize, Serialize};
+use winit::event::ElementState;
+
+/// ECS demo, counting key presses and showing their count in title.
+pub type EcsCounterModel = EcsModel<EcsCounter>;
+
+/// Key presses, counted by entity.
+#[derive(Serialize, Deserialize)]
+struct Presses(u32);
+
+impl Component for Presses {
+    const NAME: &'static str = "presses";
+}
+
+/// Count of presses, shown in title.
+#[derive(Serialize, Deserialize)]
+struct Shown(u32);
+
+impl Component for Shown {
+    const NAME: &'static str = "shown";
+}
+
+pub struct EcsCounter;
+
+impl EcsSetup for EcsCounter {
+    fn register(world: &mut World) {
+        world.register::<Presses>();
+        world.register::<Shown>();
+    }
+
+    fn populate(world: &mut World) {
+        let entity = world.create();
+        world.insert(entity, Presses(0));
+        world.insert(entity, Shown(0));
+    }
+
+    fn schedule() -> Schedule {
+        let mut schedule = Schedule::new();
+        schedule
+            .add(system(
+                "count_presses",
+                Access::new().write::<Presses>(),
+                |context| {
+                    let presses = context
+                        .inputs()
+                        .iter()
+                        .filter(|input| {
+                            matches!(
+                                input.event(),
+                                InputEvent::Keyboard {
+                                    state: ElementState::Pressed,
+                                    ..
+                                }
+                            )
+                        })
+                        .count() as u32;
+                    for (_, count) in context.write::<Presses>().iter_mut() {
+                        count.0 += presses;
+                    }
+                },
+            ))
+            // Title is requested only when count changes, so other models' titles stay.
+            .add_prior(system(
+                "show_presses",
+                Access::new().read::<Presses>().read::<Shown>(),
+                |context| {
+                    let shown = context.read::<Shown>();
+                    for (entity, presses) in context.read::<Presses>().iter() {
+                        let count = presses.0;
+                        if shown.get(entity).is_some_and(|shown| shown.0 == count) {
+                            continue;
+                        }
+                        let title = format!("Key presses: {}", count);
+                        context.commands().request(Request::SetTitle(title));
+                        context.commands().insert(entity, Shown(count));
+                    }
+                },
+            ));
+        schedule
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::harness::Harness;
+    use winit::event::VirtualKeyCode;
+
+    #[test]
+    fn title_is_requested_when_count_changes() {
+        let mut harness = Harness::new(EcsCounterModel::new());
+        harness.ticks(2);
+        assert!(harness.take_requests().is_empty());
+
+        harness
+            .press(VirtualKeyCode::A)
+            .press(VirtualKeyCode::B)
+            .tick()
+            .ticks(3);
+        assert_eq!(
+            harness.take_requests(),
+            vec![Request::SetTitle(String::from("Key presses: 2"))]
+        );
+    }
+}
@@ -0,0 +1,231 @@
+use super::jobs::JobSystem;
+use super::request::Request;
+use super::{Model, State};
+use crate::ecs::schedule::{Commands, Phase, Schedule};
+use crate::ecs::world::{SavedWorld, World};
+use crate::input::Input;
+use crate::input_state::InputState;
+use serde::de::{Deserializer, Error as _};
+use serde::ser::{Error as _, Serializer};
+use serde::{Deserialize, Serialize};
+use std::marker::PhantomData;
+
+/// Describes world of ECS model: its components, initial entities and systems.
+pub trait EcsSetup: 'static {
+    /// Registers all components, used by systems.
+    fn register(world: &mut World);
+    /// Creates entities of new world.
+    fn populate(world: &mut World);
+    fn schedule() -> Schedule;
+}
+
+/// Model, which state is world of entities and logic is systems.
+/// Read-only systems run in prior, and their commands are applied at the beginning of update.
+/// Then update systems run, and their commands are applied after all of them are done.
+pub struct EcsModel<S> {
+    world: World,
+    schedule: Schedule,
+    /// Input state of last update, available to systems in prior.
+    input_state: InputState,
+    requests: Vec<Request>,
+    setup: PhantomData<fn() -> S>,
+}
+
+impl<S: EcsSetup> EcsModel<S> {
+    pub fn new() -> Self {
+        let mut model = Self::empty();
+        S::populate(&mut model.world);
+        model
+    }
+
+    /// Model with registered components, but without entities.
+    fn empty() -> Self {
+        let mut world = World::new();
+        S::register(&mut world);
+        let schedule = S::schedule();
+        schedule.check(&world);
+        Self {
+            world,
+            schedule,
+            input_state: InputState::new(),
+            requests: Vec::new(),
+            setup: PhantomData,
+        }
+    }
+
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    pub fn schedule(&self) -> &Schedule {
+        &self.schedule
+    }
+}
+
+impl<S: EcsSetup> Default for EcsModel<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: EcsSetup> Model for EcsModel<S> {
+    type PriorResult = Commands;
+
+    fn prior(&self, _: Option<Self::PriorResult>, jobs: &JobSystem) -> State<Self::PriorResult> {
+        let commands = self
+            .schedule
+            .run(Phase::Prior, &self.world, &[], &self.input_state, jobs);
+        State::Running(Some(commands))
+    }
+
+    fn update(
+        &mut self,
+        prior_result: Option<Self::PriorResult>,
+        inputs: impl Iterator<Item = Input>,
+        input_state: &InputState,
+        jobs: &JobSystem,
+    ) -> State<Self::PriorResult> {
+        if let Some(commands) = prior_result {
+            let requests = commands.apply(&mut self.world);
+            self.requests.extend(requests);
+        }
+        let inputs: Vec<Input> = inputs.collect();
+        let commands = self
+            .schedule
+            .run(Phase::Update, &self.world, &inputs, input_state, jobs);
+        let requests = commands.apply(&mut self.world);
+        self.requests.extend(requests);
+        self.input_state = input_state.clone();
+        State::Running(None)
+    }
+
+    fn take_requests(&mut self) -> Vec<Request> {
+        std::mem::take(&mut self.requests)
+    }
+}
+
+impl<S: EcsSetup> Serialize for EcsModel<S> {
+    fn serialize<Se: Serializer>(&self, serializer: Se) -> Result<Se::Ok, Se::Error> {
+        self.world
+            .save()
+            .map_err(Se::Error::custom)?
+            .serialize(serializer)
+    }
+}
+
+impl<'de, S: EcsSetup> Deserialize<'de> for EcsModel<S> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let saved = SavedWorld::deserialize(deserializer)?;
+        let mut model = Self::empty();
+        model.world.load(saved).map_err(D::Error::custom)?;
+        Ok(model)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::schedule::{system, Access};
+    use crate::ecs::storage::Component;
+    use crate::input::InputEvent;
+    use crate::model::harness::Harness;
+    use winit::event::{ElementState, VirtualKeyCode};
+
+    #[derive(Serialize, Deserialize)]
+    struct Count(i32);
+
+    impl Component for Count {
+        const NAME: &'static str = "count";
+    }
+
+    /// Counts presses of Up and shows count in title.
+    struct Counter;
+
+    impl EcsSetup for Counter {
+        fn register(world: &mut World) {
+            world.register::<Count>();
+        }
+
+        fn populate(world: &mut World) {
+            let entity = world.create();
+            world.insert(entity, Count(0));
+        }
+
+        fn schedule() -> Schedule {
+            let mut schedule = Schedule::new();
+            schedule
+                .add(system(
+                    "count_presses",
+                    Access::new().write::<Count>(),
+                    |context| {
+                        let presses = context
+                            .inputs()
+                            .iter()
+                            .filter(|input| {
+                                matches!(
+                                    input.event(),
+                                    InputEvent::Keyboard {
+                                        key: VirtualKeyCode::Up,
+                                        state: ElementState::Pressed,
+                                    }
+                                )
+                            })
+                            .count() as i32;
+                        for (_, count) in context.write::<Count>().iter_mut() {
+                            count.0 += presses;
+                        }
+                    },
+                ))
+                .add_prior(system(
+                    "show_count",
+                    Access::new().read::<Count>(),
+                    |context| {
+                        for (_, count) in context.read::<Count>().iter() {
+                            let title = format!("Count: {}", count.0);
+                            context.commands().request(Request::SetTitle(title));
+                        }
+                    },
+                ));
+            schedule
+        }
+    }
+
+    fn count(model: &EcsModel<Counter>) -> i32 {
+        model
+            .world()
+            .read::<Count>()
+            .iter()
+            .map(|(_, count)| count.0)
+            .sum()
+    }
+
+    #[test]
+    fn systems_update_world() {
+        let mut harness = Harness::new(EcsModel::<Counter>::new());
+        harness
+            .press(VirtualKeyCode::Up)
+            .release(VirtualKeyCode::Up)
+            .press(VirtualKeyCode::Up)
+            .tick()
+            .tick();
+        assert_eq!(harness.inspect(count), 2);
+        // Prior sees world as it was before update.
+        assert_eq!(
+            harness.take_requests(),
+            vec![
+                Request::SetTitle(String::from("Count: 0")),
+                Request::SetTitle(String::from("Count: 2")),
+            ]
+        );
+    }
+
+    #[test]
+    fn saved_model_loads_back() {
+        let mut harness = Harness::new(EcsModel::<Counter>::new());
+        harness.press(VirtualKeyCode::Up).tick();
+        let bytes = harness.inspect(|model| bincode::serialize(model).unwrap());
+        let loaded: EcsModel<Counter> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(count(&loaded), 1);
+        assert_eq!(loaded.world().entities().len(), 1);
+    }
+}
@@ -0,0 +1,21 @@
+use crate::model::jobs::JobId;
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, PartialEq)]
+pub enum JobError {
+    /// Dependency was added to another task graph.
+    ForeignJob(JobId),
+}
+
+impl Error for JobError {}
+
+impl fmt::Display for JobError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JobError::ForeignJob(id) => {
+                write!(f, "Job {:?} belongs to another task graph", id)
+            }
+        }
+    }
+}
@@ -1,15 +1,27 @@
+pub mod job;
 pub mod model_not_free;
+pub mod reload;
+pub mod snapshot;
+pub mod tick_rate;
 pub mod update;
 
+use model_not_free::ModelNotFreeError;
+use reload::ReloadError;
+use snapshot::SnapshotError;
 use std::error::Error;
 use std::fmt;
 use std::sync::mpsc::RecvError;
+use tick_rate::TickRateError;
 use update::UpdateError;
 
 #[derive(Debug)]
 pub enum ModelManagerError {
     UpdateError(UpdateError),
     RecvError(RecvError),
+    Snapshot(SnapshotError),
+    ModelNotFree(ModelNotFreeError),
+    Reload(ReloadError),
+    TickRate(TickRateError),
 }
 
 impl Error for ModelManagerError {
@@ -17,6 +29,10 @@ impl Error for ModelManagerError {
         match self {
             ModelManagerError::UpdateError(e) => Some(e),
             ModelManagerError::RecvError(e) => Some(e),
+            ModelManagerError::Snapshot(e) => Some(e),
+            ModelManagerError::ModelNotFree(e) => Some(e),
+            ModelManagerError::Reload(e) => Some(e),
+            ModelManagerError::TickRate(e) => Some(e),
         }
     }
 }
@@ -38,3 +54,27 @@ impl From<RecvError> for ModelManagerError {
         ModelManagerError::RecvError(e)
     }
 }
+
+impl From<SnapshotError> for ModelManagerError {
+    fn from(e: SnapshotError) -> Self {
+        ModelManagerError::Snapshot(e)
+    }
+}
+
+impl From<ModelNotFreeError> for ModelManagerError {
+    fn from(e: ModelNotFreeError) -> Self {
+        ModelManagerError::ModelNotFree(e)
+    }
+}
+
+impl From<ReloadError> for ModelManagerError {
+    fn from(e: ReloadError) -> Self {
+        ModelManagerError::Reload(e)
+    }
+}
+
+impl From<TickRateError> for ModelManagerError {
+    fn from(e: TickRateError) -> Self {
+        ModelManagerError::TickRate(e)
+    }
+}
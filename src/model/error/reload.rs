@@ -0,0 +1,55 @@
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug)]
+pub enum ReloadError {
+    Io(std::io::Error),
+    Library(libloading::Error),
+    /// Library was built for other version of hot model interface.
+    UnsupportedVersion(u32),
+    /// Library can't restore model from state, saved by previous library.
+    StateRejected,
+    /// Script can't be compiled or failed while running.
+    Script(Box<rhai::EvalAltResult>),
+}
+
+impl Error for ReloadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReloadError::Io(e) => Some(e),
+            ReloadError::Library(e) => Some(e),
+            ReloadError::Script(e) => Some(e),
+            ReloadError::UnsupportedVersion(_) | ReloadError::StateRejected => None,
+        }
+    }
+}
+
+impl fmt::Display for ReloadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReloadError::UnsupportedVersion(v) => {
+                write!(f, "Unsupported hot model interface version: {}", v)
+            }
+            ReloadError::StateRejected => write!(f, "Reloaded model can't restore saved state"),
+            _ => fmt::Display::fmt(self.source().unwrap(), f),
+        }
+    }
+}
+
+impl From<std::io::Error> for ReloadError {
+    fn from(e: std::io::Error) -> Self {
+        ReloadError::Io(e)
+    }
+}
+
+impl From<libloading::Error> for ReloadError {
+    fn from(e: libloading::Error) -> Self {
+        ReloadError::Library(e)
+    }
+}
+
+impl From<Box<rhai::EvalAltResult>> for ReloadError {
+    fn from(e: Box<rhai::EvalAltResult>) -> Self {
+        ReloadError::Script(e)
+    }
+}
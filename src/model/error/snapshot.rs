@@ -0,0 +1,34 @@
+use crate::error::versioned_file::VersionedFileError;
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    File(VersionedFileError),
+    /// Model manager was made for model, which can't be serialized.
+    NotSerializable,
+}
+
+impl Error for SnapshotError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SnapshotError::File(e) => Some(e),
+            SnapshotError::NotSerializable => None,
+        }
+    }
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::NotSerializable => write!(f, "Model can't be saved"),
+            _ => fmt::Display::fmt(self.source().unwrap(), f),
+        }
+    }
+}
+
+impl From<VersionedFileError> for SnapshotError {
+    fn from(e: VersionedFileError) -> Self {
+        SnapshotError::File(e)
+    }
+}
@@ -0,0 +1,18 @@
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug)]
+pub enum TickRateError {
+    /// Rate is not a number, or outside of supported range.
+    OutOfRange(f64),
+}
+
+impl Error for TickRateError {}
+
+impl fmt::Display for TickRateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TickRateError::OutOfRange(rate) => write!(f, "Unsupported tick rate: {}", rate),
+        }
+    }
+}
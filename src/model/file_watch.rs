@@ -0,0 +1,60 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// File is checked for changes not more often.
+const CHECK_PERIOD: Duration = Duration::from_millis(500);
+/// Changed file is reported only after it was not modified for this time, so it's fully written.
+const SETTLE_TIME: Duration = Duration::from_millis(300);
+
+/// Notices changes of file by polling its modification time.
+#[derive(Debug, Clone)]
+pub struct FileWatch {
+    path: PathBuf,
+    last_check: Option<Instant>,
+    /// Modification time of file, when change was reported last time.
+    reported: Option<SystemTime>,
+}
+
+impl FileWatch {
+    /// The first check reports existing file as changed.
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            last_check: None,
+            reported: None,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Remembers current version of file as already reported, e.g. when it's read out of turn.
+    pub fn mark_current(&mut self) -> std::io::Result<()> {
+        self.reported = Some(std::fs::metadata(&self.path)?.modified()?);
+        Ok(())
+    }
+
+    /// Returns true once for every change of file, when file is not being written anymore.
+    /// Missing file is not an error: it may be being rebuilt.
+    pub fn changed(&mut self) -> std::io::Result<bool> {
+        let now = Instant::now();
+        if self.last_check.is_some_and(|at| now < at + CHECK_PERIOD) {
+            return Ok(false);
+        }
+        self.last_check = Some(now);
+
+        let metadata = match std::fs::metadata(&self.path) {
+            Ok(metadata) => metadata,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(e),
+        };
+        let modified = metadata.modified()?;
+        let settled = modified.elapsed().is_ok_and(|age| age >= SETTLE_TIME);
+        if !settled || self.reported == Some(modified) {
+            return Ok(false);
+        }
+        self.reported = Some(modified);
+        Ok(true)
+    }
+}
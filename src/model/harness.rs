@@ -0,0 +1,269 @@
+use crate::input::{Input, InputEvent};
+use crate::model::clock::Clock;
+use crate::model::model_manager::{Command, ModelManager, Notification, OuterBonds, Schedule};
+use crate::model::request::Request;
+use crate::model::Model;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use slog::{o, Discard, Logger};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use winit::event::{ElementState, MouseButton, VirtualKeyCode};
+
+/// Default virtual time between two ticks.
+pub const DEFAULT_TICK_PERIOD: Duration = Duration::from_millis(16);
+
+/// Single step of input script.
+#[derive(Debug, Clone)]
+pub enum ScriptStep {
+    /// Send input event, happened at current virtual time.
+    Input(InputEvent),
+    /// Make given count of model updates.
+    Wait(u32),
+    /// Move virtual time forward without updating model.
+    Advance(Duration),
+}
+
+/// Runs model manager on current thread with scripted input and virtual time,
+/// so model behavior can be checked without event loop and window.
+/// Model manager reads the same virtual time, so its schedule is deterministic.
+pub struct Harness<M: Model> {
+    manager: ModelManager<M>,
+    bonds: OuterBonds<M>,
+    clock: Clock,
+    tick_period: Duration,
+    latest: Option<Arc<M>>,
+    notifications: Vec<Notification>,
+}
+
+impl<M: Model + Serialize + DeserializeOwned> Harness<M> {
+    pub fn new(model: M) -> Self {
+        let logger = Logger::root(Discard, o!());
+        let (mut manager, bonds) = ModelManager::new(Arc::new(model), logger);
+        let clock = Clock::virtual_now();
+        manager.set_clock(clock.clone());
+        Self {
+            manager,
+            bonds,
+            clock,
+            tick_period: DEFAULT_TICK_PERIOD,
+            latest: None,
+            notifications: Vec::new(),
+        }
+    }
+
+    /// Sets virtual time between two ticks.
+    pub fn with_tick_period(mut self, tick_period: Duration) -> Self {
+        self.tick_period = tick_period;
+        self
+    }
+
+    /// Current virtual time.
+    pub fn now(&self) -> Instant {
+        self.clock.now()
+    }
+
+    /// Sends input event, happened at current virtual time.
+    pub fn input(&mut self, event: InputEvent) -> &mut Self {
+        self.input_at(self.now(), event)
+    }
+
+    /// Sends input event, happened at given time.
+    pub fn input_at(&mut self, happen_at: Instant, event: InputEvent) -> &mut Self {
+        self.bonds
+            .input_tx
+            .send(Input::new(happen_at, None, event))
+            .expect("model manager dropped input queue");
+        self
+    }
+
+    pub fn press(&mut self, key: VirtualKeyCode) -> &mut Self {
+        self.key(key, ElementState::Pressed)
+    }
+
+    pub fn release(&mut self, key: VirtualKeyCode) -> &mut Self {
+        self.key(key, ElementState::Released)
+    }
+
+    pub fn click(&mut self, button: MouseButton) -> &mut Self {
+        self.input((button, ElementState::Pressed).into())
+            .input((button, ElementState::Released).into())
+    }
+
+    fn key(&mut self, key: VirtualKeyCode, state: ElementState) -> &mut Self {
+        self.input(InputEvent::Keyboard { key, state })
+    }
+
+    /// Moves virtual time forward without updating model.
+    pub fn advance(&mut self, duration: Duration) -> &mut Self {
+        self.clock.advance(duration);
+        self
+    }
+
+    /// Makes single model update and moves virtual time for tick period.
+    pub fn tick(&mut self) -> &mut Self {
+        self.ticks(1)
+    }
+
+    /// Makes `n` model updates, moving virtual time for tick period after each.
+    /// Tick rate of model manager doesn't matter: updates are made at once.
+    pub fn ticks(&mut self, n: u32) -> &mut Self {
+        for _ in 0..n {
+            self.unshare();
+            self.manager.tick_now();
+            self.collect();
+            self.clock.advance(self.tick_period);
+        }
+        self
+    }
+
+    /// Lets model manager run by its own schedule for `duration` of virtual time,
+    /// so model is updated at tick rate of manager. Tick rate must be limited.
+    pub fn run_for(&mut self, duration: Duration) -> &mut Self {
+        let end = self.now() + duration;
+        loop {
+            self.unshare();
+            match self.manager.run_once() {
+                Schedule::At(at) if at <= end => {
+                    assert!(
+                        at > self.now(),
+                        "virtual time can't run with unlimited tick rate"
+                    );
+                    self.clock.advance_to(at);
+                }
+                _ => break,
+            }
+        }
+        self.clock.advance_to(end);
+        self.collect();
+        self
+    }
+
+    /// Sends command to model manager and lets it interpret the command.
+    pub fn command(&mut self, command: Command<M>) -> &mut Self {
+        self.unshare();
+        self.bonds
+            .command_tx
+            .send(command)
+            .expect("model manager dropped command channel");
+        self.manager.run_once();
+        self.collect();
+        self
+    }
+
+    /// Runs every step of script in order.
+    pub fn run(&mut self, script: impl IntoIterator<Item = ScriptStep>) -> &mut Self {
+        for step in script {
+            match step {
+                ScriptStep::Input(event) => self.input(event),
+                ScriptStep::Wait(ticks) => self.ticks(ticks),
+                ScriptStep::Advance(duration) => self.advance(duration),
+            };
+        }
+        self
+    }
+
+    /// Calls `f` with the current model.
+    pub fn inspect<R>(&mut self, f: impl FnOnce(&M) -> R) -> R {
+        self.collect();
+        let manager = &self.manager;
+        let model = self.latest.get_or_insert_with(|| manager.shared_model());
+        f(model)
+    }
+
+    /// Takes notifications sent by model manager.
+    pub fn take_notifications(&mut self) -> Vec<Notification> {
+        self.collect();
+        std::mem::take(&mut self.notifications)
+    }
+
+    /// Takes requests, made by model.
+    pub fn take_requests(&mut self) -> Vec<Request> {
+        self.bonds.request_rx.try_iter().collect()
+    }
+
+    /// Drops models, shared by manager, so it can change model.
+    fn unshare(&mut self) {
+        self.collect();
+        self.latest = None;
+    }
+
+    fn collect(&mut self) {
+        if let Some(model) = self.bonds.model_rx.try_iter().last() {
+            self.latest = Some(model);
+        }
+
+        self.notifications
+            .extend(self.bonds.notification_rx.try_iter());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::counter_model::CounterModel;
+
+    #[test]
+    fn pressed_key_updates_model() {
+        let mut harness = Harness::new(CounterModel::new());
+        harness.press(VirtualKeyCode::Up).tick();
+        assert_eq!(harness.inspect(|model| model.count()), 1);
+        assert_eq!(
+            harness.take_requests(),
+            vec![Request::SetTitle(String::from("Count: 1"))]
+        );
+    }
+
+    #[test]
+    fn ticks_ignore_tick_rate() {
+        let mut harness = Harness::new(CounterModel::new());
+        harness.command(Command::SetTickRate(Some(1.0)));
+        harness.run(vec![
+            ScriptStep::Input(InputEvent::Keyboard {
+                key: VirtualKeyCode::Up,
+                state: ElementState::Pressed,
+            }),
+            ScriptStep::Wait(1),
+            ScriptStep::Input(InputEvent::Keyboard {
+                key: VirtualKeyCode::Up,
+                state: ElementState::Released,
+            }),
+            ScriptStep::Input(InputEvent::Keyboard {
+                key: VirtualKeyCode::Up,
+                state: ElementState::Pressed,
+            }),
+            ScriptStep::Wait(3),
+        ]);
+        assert_eq!(harness.inspect(|model| model.count()), 2);
+        assert_eq!(harness.take_requests().len(), 2);
+        assert!(harness.take_notifications().is_empty());
+    }
+
+    #[test]
+    fn apply_after_inspect_changes_model() {
+        let mut harness = Harness::new(CounterModel::new());
+        harness.tick();
+        harness.inspect(|model| model.count());
+        harness.command(Command::Apply(Box::new(|model: &mut CounterModel| {
+            model.set_variable("counter", "5").unwrap();
+        })));
+        harness.tick();
+        assert_eq!(harness.inspect(|model| model.count()), 5);
+    }
+
+    #[test]
+    fn manager_schedule_follows_virtual_time() {
+        let mut harness = Harness::new(CounterModel::new());
+        harness
+            .command(Command::SetTickRate(Some(10.0)))
+            .command(Command::Run);
+        let start = harness.now();
+        harness.run_for(Duration::from_secs(1));
+        assert_eq!(harness.now() - start, Duration::from_secs(1));
+        assert_eq!(harness.bonds.status.get().tick, 10);
+
+        harness.command(Command::SetTickRate(Some(20.0)));
+        harness.run_for(Duration::from_millis(500));
+        assert_eq!(harness.bonds.status.get().tick, 20);
+    }
+}
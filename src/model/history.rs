@@ -0,0 +1,225 @@
+use crate::input::Input;
+use crate::input_state::InputState;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// How long history is kept by default.
+pub const DEFAULT_LENGTH: Duration = Duration::from_secs(10);
+
+/// Count of ticks between two model checkpoints by default.
+pub const DEFAULT_CHECKPOINT_PERIOD: u64 = 30;
+
+/// Serialized model state at the beginning of tick.
+struct Checkpoint {
+    tick: u64,
+    made_at: Instant,
+    state: Vec<u8>,
+    input_state: InputState,
+}
+
+/// Ring buffer of model checkpoints and inputs, used to rewind model to one of recent ticks.
+pub struct History {
+    length: Duration,
+    checkpoint_period: u64,
+    checkpoints: VecDeque<Checkpoint>,
+    /// Inputs passed to model on tick, starting from tick of the oldest checkpoint.
+    inputs: VecDeque<(u64, Vec<Input>)>,
+}
+
+/// Everything needed to rebuild model state at `target` tick.
+pub struct Replay<'a> {
+    /// Tick to rebuild model at.
+    pub target: u64,
+    /// Tick of checkpoint to start from.
+    pub start: u64,
+    /// Serialized model state at `start` tick.
+    pub state: &'a [u8],
+    /// Input state at `start` tick.
+    pub input_state: &'a InputState,
+    /// Inputs of ticks from `start` to `target`.
+    pub inputs: Vec<Vec<Input>>,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self::new(DEFAULT_LENGTH, DEFAULT_CHECKPOINT_PERIOD)
+    }
+}
+
+impl History {
+    pub fn new(length: Duration, checkpoint_period: u64) -> Self {
+        Self {
+            length,
+            checkpoint_period: checkpoint_period.max(1),
+            checkpoints: VecDeque::new(),
+            inputs: VecDeque::new(),
+        }
+    }
+
+    /// Is checkpoint expected at the beginning of `tick`.
+    pub fn needs_checkpoint(&self, tick: u64) -> bool {
+        match self.checkpoints.back() {
+            Some(last) => tick >= last.tick + self.checkpoint_period,
+            None => true,
+        }
+    }
+
+    /// Stores model state at the beginning of `tick`, made at `made_at`, and forgets too old entries.
+    pub fn checkpoint(
+        &mut self,
+        tick: u64,
+        made_at: Instant,
+        state: Vec<u8>,
+        input_state: InputState,
+    ) {
+        if let Some(last) = self.checkpoints.back() {
+            if last.tick == tick {
+                self.checkpoints.pop_back();
+            }
+        }
+        self.checkpoints.push_back(Checkpoint {
+            tick,
+            made_at,
+            state,
+            input_state,
+        });
+        self.forget_old(made_at);
+    }
+
+    /// Stores inputs passed to model on `tick`.
+    pub fn record_inputs(&mut self, tick: u64, inputs: Vec<Input>) {
+        if !self.checkpoints.is_empty() {
+            self.inputs.push_back((tick, inputs));
+        }
+    }
+
+    /// Forgets everything.
+    pub fn clear(&mut self) {
+        self.checkpoints.clear();
+        self.inputs.clear();
+    }
+
+    /// The oldest tick model can be rewound to.
+    pub fn oldest_tick(&self) -> Option<u64> {
+        self.checkpoints.front().map(|c| c.tick)
+    }
+
+    /// Returns data required to rebuild model at `target` tick.
+    /// If `target` is older than history, the oldest available tick is used.
+    pub fn replay(&self, target: u64) -> Option<Replay<'_>> {
+        let target = target.max(self.oldest_tick()?);
+        let checkpoint = self.checkpoints.iter().rev().find(|c| c.tick <= target)?;
+        let inputs = self
+            .inputs
+            .iter()
+            .filter(|(tick, _)| *tick >= checkpoint.tick && *tick < target)
+            .map(|(_, inputs)| inputs.clone())
+            .collect();
+        Some(Replay {
+            target,
+            start: checkpoint.tick,
+            state: &checkpoint.state,
+            input_state: &checkpoint.input_state,
+            inputs,
+        })
+    }
+
+    /// Forgets everything recorded after model was at `tick`.
+    pub fn truncate(&mut self, tick: u64) {
+        while self.checkpoints.back().is_some_and(|c| c.tick > tick) {
+            self.checkpoints.pop_back();
+        }
+        while self.inputs.back().is_some_and(|(t, _)| *t >= tick) {
+            self.inputs.pop_back();
+        }
+    }
+
+    /// Forgets checkpoints, which are not needed to rewind for history length from `now`,
+    /// but keeps at least one.
+    fn forget_old(&mut self, now: Instant) {
+        while self.checkpoints.len() > 1 && now - self.checkpoints[1].made_at > self.length {
+            self.checkpoints.pop_front();
+        }
+        if let Some(oldest) = self.oldest_tick() {
+            while self.inputs.front().is_some_and(|(t, _)| *t < oldest) {
+                self.inputs.pop_front();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::InputEvent;
+    use winit::event::{ElementState, VirtualKeyCode};
+
+    fn press(at: Instant) -> Vec<Input> {
+        let event = InputEvent::Keyboard {
+            key: VirtualKeyCode::Up,
+            state: ElementState::Pressed,
+        };
+        vec![Input::new(at, None, event)]
+    }
+
+    /// Records ticks `0..ticks` with checkpoint at every `period` tick, one tick per 100 ms.
+    fn recorded(length: Duration, period: u64, ticks: u64) -> (History, Instant) {
+        let start = Instant::now();
+        let mut history = History::new(length, period);
+        for tick in 0..ticks {
+            let now = start + Duration::from_millis(100 * tick);
+            if history.needs_checkpoint(tick) {
+                history.checkpoint(tick, now, vec![tick as u8], InputState::new());
+            }
+            history.record_inputs(tick, press(now));
+        }
+        (history, start)
+    }
+
+    #[test]
+    fn forgets_what_is_older_than_length() {
+        let (history, _) = recorded(Duration::from_millis(1000), 5, 40);
+        // Checkpoints are at 0, 5, .. 35. The one at 25 is 1 second old, so 20 is still needed.
+        assert_eq!(history.oldest_tick(), Some(20));
+        assert_eq!(history.checkpoints.len(), 4);
+        assert_eq!(history.inputs.front().map(|(tick, _)| *tick), Some(20));
+        assert_eq!(history.inputs.len(), 20);
+    }
+
+    #[test]
+    fn rewind_past_history_stops_at_oldest_tick() {
+        let (history, _) = recorded(Duration::from_millis(1000), 5, 40);
+        let replay = history.replay(3).unwrap();
+        assert_eq!((replay.target, replay.start), (20, 20));
+        assert_eq!(replay.state, &[20]);
+        assert!(replay.inputs.is_empty());
+    }
+
+    #[test]
+    fn replay_has_inputs_from_checkpoint_to_target() {
+        let (history, start) = recorded(DEFAULT_LENGTH, 5, 20);
+        let replay = history.replay(13).unwrap();
+        assert_eq!((replay.target, replay.start), (13, 10));
+        assert_eq!(replay.state, &[10]);
+        let happen_at: Vec<Instant> = replay
+            .inputs
+            .iter()
+            .map(|inputs| *inputs[0].happen_at())
+            .collect();
+        let expected: Vec<Instant> = (10..13)
+            .map(|tick| start + Duration::from_millis(100 * tick))
+            .collect();
+        assert_eq!(happen_at, expected);
+    }
+
+    #[test]
+    fn truncated_history_records_new_branch() {
+        let (mut history, start) = recorded(DEFAULT_LENGTH, 5, 20);
+        history.truncate(12);
+        assert!(!history.needs_checkpoint(12));
+        history.record_inputs(12, press(start));
+        let replay = history.replay(13).unwrap();
+        assert_eq!(replay.inputs.len(), 3);
+        assert_eq!(*replay.inputs[2][0].happen_at(), start);
+    }
+}
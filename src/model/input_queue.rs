@@ -0,0 +1,220 @@
+use crate::input::{Input, InputEvent};
+use std::collections::VecDeque;
+use std::sync::mpsc::SendError;
+use std::sync::{Arc, Mutex};
+use winit::event::{ElementState, TouchPhase};
+
+/// Default count of inputs, queue can hold.
+pub const DEFAULT_CAPACITY: usize = 1024;
+
+/// How input queue deals with inputs, that are not taken in time.
+#[derive(Debug, Clone, Copy)]
+pub struct QueuePolicy {
+    /// Maximum count of queued inputs. Beyond it the oldest motion is dropped first, then the
+    /// oldest other input. Releases are never dropped, so keys can't stay pressed.
+    pub capacity: usize,
+    /// Merge consecutive mouse moves and scrolls into one event.
+    pub coalesce: bool,
+}
+
+impl Default for QueuePolicy {
+    fn default() -> Self {
+        Self {
+            capacity: DEFAULT_CAPACITY,
+            coalesce: true,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Queue {
+    inputs: VecDeque<Input>,
+    dropped: usize,
+    policy: QueuePolicy,
+    receiver_alive: bool,
+}
+
+/// Creates bounded input queue.
+pub fn input_queue(policy: QueuePolicy) -> (InputSender, InputReceiver) {
+    let queue = Arc::new(Mutex::new(Queue {
+        inputs: VecDeque::new(),
+        dropped: 0,
+        policy,
+        receiver_alive: true,
+    }));
+    (InputSender(queue.clone()), InputReceiver(queue))
+}
+
+/// Sending end of input queue. Never blocks: drops old inputs instead.
+#[derive(Debug, Clone)]
+pub struct InputSender(Arc<Mutex<Queue>>);
+
+impl InputSender {
+    /// Queues input. Returns it back as error if receiver was dropped.
+    pub fn send(&self, input: Input) -> Result<(), SendError<Input>> {
+        let mut queue = self.0.lock().unwrap();
+        if !queue.receiver_alive {
+            return Err(SendError(input));
+        }
+        if queue.policy.coalesce {
+            if let Some(last) = queue.inputs.back_mut() {
+                if last.coalesce(&input) {
+                    return Ok(());
+                }
+            }
+        }
+        queue.inputs.push_back(input);
+        while queue.inputs.len() > queue.policy.capacity.max(1) {
+            let inputs = &queue.inputs;
+            let victim = inputs
+                .iter()
+                .position(is_motion)
+                .or_else(|| inputs.iter().position(|input| !is_release(input)));
+            match victim {
+                Some(index) => {
+                    queue.inputs.remove(index);
+                    queue.dropped += 1;
+                }
+                // Only releases are left, queue grows beyond capacity.
+                None => break,
+            }
+        }
+        Ok(())
+    }
+
+    /// Count of inputs, waiting to be taken.
+    pub fn queued(&self) -> usize {
+        self.0.lock().unwrap().inputs.len()
+    }
+}
+
+/// Motion is dropped first: the next one brings cursor or finger to actual position anyway.
+fn is_motion(input: &Input) -> bool {
+    match input.event() {
+        InputEvent::RawMouseMove { .. }
+        | InputEvent::CursorMove { .. }
+        | InputEvent::Scroll { .. } => true,
+        InputEvent::Touch { phase, .. } => *phase == TouchPhase::Moved,
+        _ => false,
+    }
+}
+
+/// Input, which ends something pressed before. Dropping it would leave key pressed forever.
+fn is_release(input: &Input) -> bool {
+    match input.event() {
+        InputEvent::Keyboard { state, .. } | InputEvent::MouseButton { state, .. } => {
+            *state == ElementState::Released
+        }
+        InputEvent::Touch { phase, .. } => {
+            *phase == TouchPhase::Ended || *phase == TouchPhase::Cancelled
+        }
+        _ => false,
+    }
+}
+
+/// Receiving end of input queue.
+#[derive(Debug)]
+pub struct InputReceiver(Arc<Mutex<Queue>>);
+
+impl InputReceiver {
+    /// Takes all queued inputs and count of inputs dropped since previous call.
+    pub fn take(&self) -> (Vec<Input>, usize) {
+        let mut queue = self.0.lock().unwrap();
+        let inputs = queue.inputs.drain(..).collect();
+        let dropped = std::mem::take(&mut queue.dropped);
+        (inputs, dropped)
+    }
+}
+
+impl Drop for InputReceiver {
+    fn drop(&mut self) {
+        if let Ok(mut queue) = self.0.lock() {
+            queue.receiver_alive = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+    use winit::event::VirtualKeyCode;
+
+    fn input(event: InputEvent) -> Input {
+        Input::new(Instant::now(), None, event)
+    }
+
+    fn key(key: VirtualKeyCode, state: ElementState) -> Input {
+        input(InputEvent::Keyboard { key, state })
+    }
+
+    fn moved(x: f64) -> Input {
+        input(InputEvent::CursorMove { position: (x, 0.0) })
+    }
+
+    fn policy(capacity: usize, coalesce: bool) -> QueuePolicy {
+        QueuePolicy { capacity, coalesce }
+    }
+
+    #[test]
+    fn consecutive_moves_are_coalesced() {
+        let (tx, rx) = input_queue(policy(8, true));
+        tx.send(moved(1.0)).unwrap();
+        tx.send(moved(2.0)).unwrap();
+        tx.send(key(VirtualKeyCode::A, ElementState::Pressed))
+            .unwrap();
+        tx.send(moved(3.0)).unwrap();
+        assert_eq!(tx.queued(), 3);
+
+        let (inputs, dropped) = rx.take();
+        assert_eq!(dropped, 0);
+        match inputs[0].event() {
+            InputEvent::CursorMove { position } => assert_eq!(*position, (2.0, 0.0)),
+            event => panic!("unexpected event {:?}", event),
+        }
+    }
+
+    #[test]
+    fn overflow_drops_motion_first_and_counts_it() {
+        let (tx, rx) = input_queue(policy(3, false));
+        tx.send(key(VirtualKeyCode::A, ElementState::Pressed))
+            .unwrap();
+        tx.send(moved(1.0)).unwrap();
+        tx.send(key(VirtualKeyCode::B, ElementState::Pressed))
+            .unwrap();
+        tx.send(moved(2.0)).unwrap();
+        tx.send(key(VirtualKeyCode::C, ElementState::Pressed))
+            .unwrap();
+        assert_eq!(tx.queued(), 3);
+
+        let (inputs, dropped) = rx.take();
+        assert_eq!(dropped, 2);
+        assert!(inputs.iter().all(|input| !is_motion(input)));
+        assert_eq!(rx.take().1, 0);
+    }
+
+    #[test]
+    fn releases_are_never_dropped() {
+        let (tx, rx) = input_queue(policy(2, false));
+        tx.send(key(VirtualKeyCode::A, ElementState::Pressed))
+            .unwrap();
+        tx.send(key(VirtualKeyCode::A, ElementState::Released))
+            .unwrap();
+        tx.send(key(VirtualKeyCode::B, ElementState::Released))
+            .unwrap();
+        tx.send(key(VirtualKeyCode::C, ElementState::Released))
+            .unwrap();
+
+        let (inputs, dropped) = rx.take();
+        assert_eq!(dropped, 1);
+        assert_eq!(inputs.len(), 3);
+        assert!(inputs.iter().all(is_release));
+    }
+
+    #[test]
+    fn send_fails_after_receiver_is_dropped() {
+        let (tx, rx) = input_queue(QueuePolicy::default());
+        drop(rx);
+        assert!(tx.send(moved(1.0)).is_err());
+    }
+}
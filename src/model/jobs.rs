@@ -0,0 +1,454 @@
+use crate::model::error::job::JobError;
+use std::any::Any;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::thread::JoinHandle;
+
+/// Source of unique task graph identifiers.
+static NEXT_GRAPH: AtomicUsize = AtomicUsize::new(0);
+
+/// Identifier of job in task graph, used to declare dependencies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JobId {
+    graph: usize,
+    index: usize,
+}
+
+/// Result of job. Available after task graph is run, or inside jobs dependent on it.
+pub struct JobResult<T>(Arc<Mutex<Option<T>>>);
+
+impl<T> JobResult<T> {
+    /// Takes result out. Returns None if job was not done yet or result was already taken.
+    pub fn take(&self) -> Option<T> {
+        self.0.lock().unwrap().take()
+    }
+}
+
+impl<T> Clone for JobResult<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+struct Job<'a> {
+    run: Box<dyn FnOnce() + Send + 'a>,
+    dependents: Vec<usize>,
+    dependencies: usize,
+}
+
+/// Set of jobs with dependencies between them.
+/// Jobs may borrow data, that outlives graph, e.g. model in `Model::prior`.
+pub struct TaskGraph<'a> {
+    id: usize,
+    jobs: Vec<Job<'a>>,
+}
+
+impl<'a> Default for TaskGraph<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> TaskGraph<'a> {
+    pub fn new() -> Self {
+        Self {
+            id: NEXT_GRAPH.fetch_add(1, Ordering::Relaxed),
+            jobs: Vec::new(),
+        }
+    }
+
+    /// Adds job, that will be run after all jobs from `after` are done.
+    /// Results of dependencies can be taken inside job using their `JobResult`s.
+    /// Fails, if some dependency was added to another graph.
+    pub fn add<T, F>(&mut self, after: &[JobId], job: F) -> Result<(JobId, JobResult<T>), JobError>
+    where
+        T: Send + 'a,
+        F: FnOnce() -> T + Send + 'a,
+    {
+        if let Some(foreign) = after.iter().find(|dependency| dependency.graph != self.id) {
+            return Err(JobError::ForeignJob(*foreign));
+        }
+        let id = self.jobs.len();
+        let result = JobResult(Arc::new(Mutex::new(None)));
+        let output = result.clone();
+        for dependency in after {
+            self.jobs[dependency.index].dependents.push(id);
+        }
+        self.jobs.push(Job {
+            run: Box::new(move || {
+                let value = job();
+                *output.0.lock().unwrap() = Some(value);
+            }),
+            dependents: Vec::new(),
+            dependencies: after.len(),
+        });
+        let id = JobId {
+            graph: self.id,
+            index: id,
+        };
+        Ok((id, result))
+    }
+
+    pub fn len(&self) -> usize {
+        self.jobs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
+    }
+}
+
+/// Runs task graphs on several threads. Clones share the same worker threads,
+/// which are started by the first run and stopped when the last clone is dropped.
+#[derive(Clone)]
+pub struct JobSystem {
+    pool: Arc<Pool>,
+}
+
+impl Default for JobSystem {
+    fn default() -> Self {
+        let workers = std::thread::available_parallelism().map_or(1, |n| n.get());
+        Self::new(workers)
+    }
+}
+
+impl fmt::Debug for JobSystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JobSystem")
+            .field("workers", &self.pool.workers)
+            .finish()
+    }
+}
+
+/// Job with lifetime erased. `JobSystem::run` doesn't return until it is done or dropped,
+/// so data it borrows is alive while it runs.
+type ErasedJob = Box<dyn FnOnce() + Send + 'static>;
+
+/// Progress of single task graph run.
+struct Progress {
+    jobs: Vec<Option<ErasedJob>>,
+    dependents: Vec<Vec<usize>>,
+    dependencies: Vec<usize>,
+    left: usize,
+    panic: Option<Box<dyn Any + Send>>,
+}
+
+#[derive(Default)]
+struct Runs {
+    progress: HashMap<usize, Progress>,
+    /// Jobs, which dependencies are done, as graph and job index.
+    ready: VecDeque<(usize, usize)>,
+    closing: bool,
+}
+
+struct Shared {
+    runs: Mutex<Runs>,
+    changed: Condvar,
+}
+
+/// Worker threads, shared by clones of job system.
+struct Pool {
+    workers: usize,
+    shared: Arc<Shared>,
+    threads: Mutex<Vec<JoinHandle<()>>>,
+}
+
+impl Pool {
+    /// Starts worker threads, if they are not started yet. Thread, calling `run`, is a worker too.
+    fn start(&self) {
+        let mut threads = self.threads.lock().unwrap();
+        if !threads.is_empty() {
+            return;
+        }
+        for i in 1..self.workers {
+            let shared = self.shared.clone();
+            let thread = std::thread::Builder::new()
+                .name(format!("job worker {}", i))
+                .spawn(move || work(&shared, None))
+                .expect("can't spawn job worker");
+            threads.push(thread);
+        }
+    }
+}
+
+impl Drop for Pool {
+    fn drop(&mut self) {
+        self.shared.runs.lock().unwrap().closing = true;
+        self.shared.changed.notify_all();
+        for thread in self.threads.get_mut().unwrap().drain(..) {
+            thread.join().ok();
+        }
+    }
+}
+
+impl JobSystem {
+    pub fn new(workers: usize) -> Self {
+        Self {
+            pool: Arc::new(Pool {
+                workers: workers.max(1),
+                shared: Arc::new(Shared {
+                    runs: Mutex::new(Runs::default()),
+                    changed: Condvar::new(),
+                }),
+                threads: Mutex::new(Vec::new()),
+            }),
+        }
+    }
+
+    pub fn workers(&self) -> usize {
+        self.pool.workers
+    }
+
+    /// Runs all jobs of graph, respecting dependencies, and returns when all of them are done.
+    /// If some job panics, panic is resumed here after other jobs are done.
+    pub fn run(&self, graph: TaskGraph<'_>) {
+        if graph.is_empty() {
+            return;
+        }
+        if graph.len() > 1 {
+            self.pool.start();
+        }
+
+        let left = graph.len();
+        let mut progress = Progress {
+            jobs: Vec::with_capacity(left),
+            dependents: Vec::with_capacity(left),
+            dependencies: Vec::with_capacity(left),
+            left,
+            panic: None,
+        };
+        let shared = &self.pool.shared;
+        let mut runs = lock(shared);
+        // Created before any job is queued, so every way out of this function passes it.
+        let joined = Joined {
+            shared,
+            graph: graph.id,
+        };
+        for (index, job) in graph.jobs.into_iter().enumerate() {
+            if job.dependencies == 0 {
+                runs.ready.push_back((graph.id, index));
+            }
+            // SAFETY: job borrows data for the lifetime of graph, which outlives this call.
+            // Erased job is only reachable through `progress` of this graph, and `joined`
+            // doesn't let this call return or unwind while it's there:
+            // - jobs are run inside `catch_unwind`, so worker, which took job, always
+            //   comes back, marks it done and wakes this thread, even if job panics;
+            // - normal return passes `Joined::join`, which waits for all jobs to be done;
+            // - if this thread unwinds, e.g. on poisoned lock, `Joined::drop` drops jobs,
+            //   which didn't start, on this thread and waits for those, which are running.
+            // Panic of job is resumed only after that, so no job outlives borrowed data.
+            let run: ErasedJob = unsafe { std::mem::transmute(job.run) };
+            progress.jobs.push(Some(run));
+            progress.dependents.push(job.dependents);
+            progress.dependencies.push(job.dependencies);
+        }
+        runs.progress.insert(graph.id, progress);
+        shared.changed.notify_all();
+        drop(runs);
+
+        work(shared, Some(graph.id));
+
+        if let Some(panic) = joined.join() {
+            panic::resume_unwind(panic);
+        }
+    }
+}
+
+/// Locks runs. Jobs are never run under the lock, so poisoning means bug in job system itself,
+/// but joining graph must still be possible then.
+fn lock(shared: &Shared) -> MutexGuard<'_, Runs> {
+    shared.runs.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Waits until no job of graph can run anymore, and forgets the graph. Dropped jobs,
+/// which didn't start, release their borrows on the thread, that owns borrowed data.
+struct Joined<'a> {
+    shared: &'a Shared,
+    graph: usize,
+}
+
+impl Joined<'_> {
+    /// Joins graph and returns panic of its job, if any.
+    fn join(self) -> Option<Box<dyn Any + Send>> {
+        let panic = self.finish();
+        std::mem::forget(self);
+        panic
+    }
+
+    fn finish(&self) -> Option<Box<dyn Any + Send>> {
+        let mut runs = lock(self.shared);
+        let graph = self.graph;
+        runs.ready.retain(|(ready, _)| *ready != graph);
+        let cancelled: Vec<ErasedJob> = match runs.progress.get_mut(&graph) {
+            Some(progress) => progress.jobs.iter_mut().filter_map(Option::take).collect(),
+            None => return None,
+        };
+        if let Some(progress) = runs.progress.get_mut(&graph) {
+            progress.left -= cancelled.len();
+        }
+        drop(runs);
+        drop(cancelled);
+
+        let mut runs = lock(self.shared);
+        while runs
+            .progress
+            .get(&graph)
+            .is_some_and(|progress| progress.left > 0)
+        {
+            runs = self
+                .shared
+                .changed
+                .wait(runs)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+        runs.progress
+            .remove(&graph)
+            .and_then(|progress| progress.panic)
+    }
+}
+
+impl Drop for Joined<'_> {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+/// Takes ready jobs of any graph and runs them. Returns when graph `until` is done,
+/// or when job system is dropped, if `until` is None.
+fn work(shared: &Shared, until: Option<usize>) {
+    let mut runs = lock(shared);
+    loop {
+        let done = match until {
+            Some(graph) => runs
+                .progress
+                .get(&graph)
+                .map_or(true, |progress| progress.left == 0),
+            None => runs.closing,
+        };
+        if done {
+            return;
+        }
+        let (graph, index) = match runs.ready.pop_front() {
+            Some(ready) => ready,
+            None => {
+                runs = shared
+                    .changed
+                    .wait(runs)
+                    .unwrap_or_else(PoisonError::into_inner);
+                continue;
+            }
+        };
+        // Job may be already dropped by `Joined`, if its graph was abandoned.
+        let job = match runs.progress.get_mut(&graph) {
+            Some(progress) => match progress.jobs[index].take() {
+                Some(job) => job,
+                None => continue,
+            },
+            None => continue,
+        };
+        drop(runs);
+
+        let result = panic::catch_unwind(AssertUnwindSafe(job));
+
+        runs = lock(shared);
+        let progress = runs
+            .progress
+            .get_mut(&graph)
+            .expect("graph is kept until its running jobs are done");
+        if let Err(panic) = result {
+            progress.panic.get_or_insert(panic);
+        }
+        progress.left -= 1;
+        let mut ready = Vec::new();
+        for dependent in std::mem::take(&mut progress.dependents[index]) {
+            progress.dependencies[dependent] -= 1;
+            if progress.dependencies[dependent] == 0 {
+                ready.push((graph, dependent));
+            }
+        }
+        runs.ready.extend(ready);
+        shared.changed.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn dependents_run_after_dependencies() {
+        let jobs = JobSystem::new(4);
+        let mut graph = TaskGraph::new();
+        let (a, a_result) = graph.add(&[], || 2).unwrap();
+        let (b, b_result) = graph.add(&[], || 3).unwrap();
+        let (_, sum) = graph
+            .add(&[a, b], move || {
+                a_result.take().unwrap() + b_result.take().unwrap()
+            })
+            .unwrap();
+        jobs.run(graph);
+        assert_eq!(sum.take(), Some(5));
+    }
+
+    #[test]
+    fn foreign_dependency_is_rejected() {
+        let mut first = TaskGraph::new();
+        let (foreign, _) = first.add(&[], || ()).unwrap();
+        let mut second = TaskGraph::new();
+        assert_eq!(
+            second.add(&[foreign], || ()).err(),
+            Some(JobError::ForeignJob(foreign))
+        );
+    }
+
+    #[test]
+    fn workers_are_reused_between_runs() {
+        let jobs = JobSystem::new(3);
+        let threads = Mutex::new(HashSet::new());
+        for _ in 0..20 {
+            let mut graph = TaskGraph::new();
+            for _ in 0..8 {
+                graph
+                    .add(&[], || {
+                        threads.lock().unwrap().insert(std::thread::current().id());
+                    })
+                    .unwrap();
+            }
+            jobs.run(graph);
+        }
+        assert!(threads.into_inner().unwrap().len() <= jobs.workers());
+    }
+
+    #[test]
+    fn panic_is_resumed_after_other_jobs_are_done() {
+        let jobs = JobSystem::new(4);
+        let done = AtomicUsize::new(0);
+        let mut graph = TaskGraph::new();
+        let (failed, _) = graph.add(&[], || panic!("job failed")).unwrap();
+        for _ in 0..8 {
+            graph
+                .add(&[], || {
+                    std::thread::sleep(std::time::Duration::from_millis(1));
+                    done.fetch_add(1, Ordering::SeqCst);
+                })
+                .unwrap();
+        }
+        graph
+            .add(&[failed], || {
+                done.fetch_add(1, Ordering::SeqCst);
+            })
+            .unwrap();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| jobs.run(graph)));
+        assert!(result.is_err());
+        assert_eq!(done.load(Ordering::SeqCst), 9);
+        // Pool is still usable after panic.
+        let mut graph = TaskGraph::new();
+        let (_, answer) = graph.add(&[], || 42).unwrap();
+        jobs.run(graph);
+        assert_eq!(answer.take(), Some(42));
+    }
+}
@@ -1,21 +1,85 @@
 use crate::input::Input;
+use crate::input_state::InputState;
+use error::reload::ReloadError;
+use jobs::JobSystem;
+use request::Request;
 
+pub mod async_bonds;
+pub mod ball_model;
+pub mod clock;
 pub mod counter_model;
+pub mod dylib_model;
+pub mod ecs_counter;
+pub mod ecs_model;
 pub mod error;
+pub mod file_watch;
+pub mod harness;
+pub mod history;
+pub mod input_queue;
+pub mod jobs;
 pub mod model_manager;
+pub mod registry;
+pub mod request;
+pub mod scheduler;
+pub mod script_model;
+pub mod snapshot;
+pub mod status;
+pub mod supervisor;
 
 pub enum State<Result> {
     Running(Option<Result>),
-    Finished
+    Finished,
 }
 
 pub trait Model {
     type PriorResult: Sized;
 
-    fn prior(&self, prior_result: Option<Self::PriorResult>) -> State<Self::PriorResult>;
+    /// Runs while model is shared with presenter. Heavy read-only work can be spread over `jobs`.
+    fn prior(
+        &self,
+        prior_result: Option<Self::PriorResult>,
+        jobs: &JobSystem,
+    ) -> State<Self::PriorResult>;
+
+    /// Runs with exclusive access to model. `inputs` are events recieved since previous update
+    /// and `input_state` is devices state after them. Work can be spread over `jobs`.
     fn update(
         &mut self,
         prior_result: Option<Self::PriorResult>,
         inputs: impl Iterator<Item = Input>,
+        input_state: &InputState,
+        jobs: &JobSystem,
     ) -> State<Self::PriorResult>;
+
+    /// Returns requests to application, made since previous call. Called after every update.
+    fn take_requests(&mut self) -> Vec<Request> {
+        Vec::new()
+    }
+
+    /// Called before update, when tick rate of model manager changes, and before the first update
+    /// of new model. Lets model advance its simulation in real time. None means as fast as possible.
+    fn set_tick_rate(&mut self, _rate: Option<f64>) {}
+
+    /// Called between ticks, before update. Lets model swap its logic, e.g. reload library.
+    /// On error model must keep working with old logic.
+    fn reload(&mut self) -> Result<(), ReloadError> {
+        Ok(())
+    }
+
+    /// Called after model is deserialized from snapshot or history, before it's updated.
+    /// Model, which logic is not serialized with it, must load logic here or fail.
+    fn restore(&mut self) -> Result<(), ReloadError> {
+        Ok(())
+    }
+
+    /// Names and current values of variables, which can be read and changed from developer console.
+    fn variables(&self) -> Vec<(&'static str, String)> {
+        Vec::new()
+    }
+
+    /// Sets variable from developer console. Returns error message if variable is unknown,
+    /// or value can't be parsed.
+    fn set_variable(&mut self, name: &str, _value: &str) -> Result<(), String> {
+        Err(format!("Unknown variable: {}", name))
+    }
 }
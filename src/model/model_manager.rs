@@ -1,76 +1,264 @@
 use crate::input::Input;
+use crate::input_state::InputState;
+use crate::model::async_bonds::{BondWakers, WakingSender};
+use crate::model::clock::Clock;
 use crate::model::error::model_not_free::ModelNotFreeError;
+use crate::model::error::snapshot::SnapshotError;
+use crate::model::error::tick_rate::TickRateError;
 use crate::model::error::update::UpdateError;
 use crate::model::error::ModelManagerError;
-use crate::model::Model;
+use crate::model::history::{History, Replay};
+use crate::model::input_queue::{input_queue, InputReceiver, InputSender, QueuePolicy};
+use crate::model::jobs::JobSystem;
+use crate::model::request::Request;
+use crate::model::status::{Status, StatusWatch};
+use crate::model::{snapshot, Model, State as ModelState};
 use crate::utils;
-use crate::utils::wait_for;
-use slog::{debug, error, trace, warn, Logger};
-use std::sync::mpsc::{channel, Receiver, Sender};
-use std::sync::Arc;
-use std::time::Duration;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use slog::{debug, error, info, trace, warn, Logger};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, SendError, Sender};
+use std::sync::{Arc, Mutex};
+use std::task::Waker;
+use std::time::{Duration, Instant};
+
+/// How often model is checked to be released by presenter, when it's needed mutable.
+const MODEL_CHECK_PERIOD: Duration = Duration::from_micros(500);
+/// Model manager stops waiting for model released by presenter and reports error after this time.
+const MODEL_WAIT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Supported range of tick rates, updates per second.
+pub const MIN_TICK_RATE: f64 = 0.01;
+pub const MAX_TICK_RATE: f64 = 100_000.0;
+
+/// Returns period between ticks for tick rate, if rate is in supported range.
+pub fn tick_period(rate: f64) -> Result<Duration, TickRateError> {
+    if !(MIN_TICK_RATE..=MAX_TICK_RATE).contains(&rate) {
+        return Err(TickRateError::OutOfRange(rate));
+    }
+    Ok(Duration::from_secs_f64(1.0 / rate))
+}
 
 /// Commands from outer code.
-#[derive(Debug)]
-pub enum Command {
+pub enum Command<M> {
     /// Stop model updating loop and wait for next commands.
     Stop,
     /// Run model updating loop.
     Run,
     /// Exit from model updating loop.
     Exit,
+    /// Replace model with given one. Model manager state is not changed.
+    Reset(M),
+    /// Save model snapshot to file.
+    Save(PathBuf),
+    /// Replace model with one loaded from snapshot file. Model manager state is not changed.
+    Load(PathBuf),
+    /// Make exactly `n` model updates and stop.
+    Step(u32),
+    /// Set count of model updates per second. None means as fast as possible.
+    /// Rate outside of `MIN_TICK_RATE..=MAX_TICK_RATE` is rejected with error.
+    SetTickRate(Option<f64>),
+    /// Call closure with mutable model between updates. Model manager state is not changed.
+    Apply(Box<dyn FnOnce(&mut M) + Send>),
+    /// Call closure with current model, even while it's shared. Model is not changed,
+    /// so neither checkpoint is made, nor model manager waits for model to be free.
+    Query(Box<dyn FnOnce(&M) + Send>),
+    /// Return model to state it had given count of ticks ago, if it is still in history.
+    /// Model manager state is not changed.
+    Rewind(u64),
+    /// Call closure when all previously sent commands are interpreted.
+    Callback(Box<dyn FnOnce() + Send>),
+}
+
+impl<M> fmt::Debug for Command<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Command::Stop => write!(f, "Stop"),
+            Command::Run => write!(f, "Run"),
+            Command::Exit => write!(f, "Exit"),
+            Command::Reset(_) => write!(f, "Reset(..)"),
+            Command::Save(path) => write!(f, "Save({:?})", path),
+            Command::Load(path) => write!(f, "Load({:?})", path),
+            Command::Step(n) => write!(f, "Step({})", n),
+            Command::SetTickRate(rate) => write!(f, "SetTickRate({:?})", rate),
+            Command::Apply(_) => write!(f, "Apply(..)"),
+            Command::Query(_) => write!(f, "Query(..)"),
+            Command::Rewind(ticks) => write!(f, "Rewind({})", ticks),
+            Command::Callback(_) => write!(f, "Callback(..)"),
+        }
+    }
 }
 
 /// Notifications to outer code
 #[derive(Debug)]
 pub enum Notification {
     Error(ModelManagerError),
+    /// Input queue overflowed and given count of the oldest inputs was dropped.
+    InputDropped(usize),
+}
+
+/// When model manager wants to be run again.
+#[derive(Debug, Clone, Copy)]
+pub enum Schedule {
+    /// Next update is expected at given time.
+    At(Instant),
+    /// Model manager is stopped and does nothing until next command.
+    OnCommand,
+    /// Model manager exited.
+    Exit,
 }
 
 /// Current model updating loop state
-#[derive(Debug)]
-enum State {
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum State {
     Running,
+    /// Running until given count of updates is done.
+    Stepping(u32),
     Stoped,
     Exitting,
 }
 
 /// Manager, that controll model by calling it's trait methods in correct order, at correct time.
-#[derive(Debug)]
-pub struct ModelManager<M> {
+pub struct ModelManager<M: Model> {
     model: Arc<M>,
+    prior_result: Option<M::PriorResult>,
+    /// Result of shared calculations of tick, which waits for model to be released by presenter.
+    prepared: Option<ModelState<M::PriorResult>>,
+    /// Command, which waits for model to be released by presenter.
+    postponed: Option<Command<M>>,
+    /// When model manager started waiting for model to be released by presenter.
+    busy_since: Option<Instant>,
     inner_bonds: InnerBonds<M>,
     state: State,
+    tick_period: Option<Duration>,
+    /// Model must be told tick rate before next update.
+    tick_rate_changed: bool,
+    last_tick: Instant,
+    /// Count of model updates done.
+    tick: u64,
+    history: History,
+    /// None for models, which can't be saved. They have no snapshots and history.
+    snapshots: Option<Snapshots<M>>,
+    input_state: InputState,
+    jobs: JobSystem,
+    clock: Clock,
     logger: Logger,
 }
 
+/// Functions, which save and load model of serializable type.
+struct Snapshots<M> {
+    save: fn(&M, &Path) -> Result<(), SnapshotError>,
+    load: fn(&Path) -> Result<M, SnapshotError>,
+    write: fn(&M, &mut Vec<u8>) -> Result<(), SnapshotError>,
+    read: fn(&[u8]) -> Result<M, SnapshotError>,
+}
+
 /// Outer code communicate with ModelManager using this.
 #[derive(Debug)]
 pub struct OuterBonds<M> {
-    pub input_tx: Sender<Input>,
-    pub command_tx: Sender<Command>,
+    pub input_tx: InputSender,
+    pub command_tx: CommandSender<M>,
     pub notification_rx: Receiver<Notification>,
     pub model_rx: Receiver<Arc<M>>,
+    pub request_rx: Receiver<Request>,
+    pub status: StatusWatch,
+    /// Let `AsyncBonds` await receivers.
+    pub(crate) wakers: BondWakers,
+}
+
+/// Sends commands to model manager and wakes it, if it waits for them.
+pub struct CommandSender<M> {
+    tx: Sender<Command<M>>,
+    waker: Arc<Mutex<Option<Waker>>>,
+}
+
+impl<M> CommandSender<M> {
+    pub fn send(&self, command: Command<M>) -> Result<(), SendError<Command<M>>> {
+        self.tx.send(command)?;
+        if let Some(waker) = self.waker.lock().unwrap().as_ref() {
+            waker.wake_by_ref();
+        }
+        Ok(())
+    }
+}
+
+impl<M> CommandSender<M> {
+    /// Sender, which commands are received by returned receiver instead of model manager.
+    #[cfg(test)]
+    pub(crate) fn detached() -> (Self, Receiver<Command<M>>) {
+        let (tx, rx) = channel();
+        let waker = Arc::new(Mutex::new(None));
+        (Self { tx, waker }, rx)
+    }
+}
+
+impl<M> Clone for CommandSender<M> {
+    fn clone(&self) -> Self {
+        Self {
+            tx: self.tx.clone(),
+            waker: self.waker.clone(),
+        }
+    }
+}
+
+impl<M> fmt::Debug for CommandSender<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CommandSender").finish()
+    }
 }
 
 /// ModelManager communicate with outer code using this.
 #[derive(Debug)]
 struct InnerBonds<M> {
-    input_rx: Receiver<Input>,
-    command_rx: Receiver<Command>,
-    notification_tx: Sender<Notification>,
-    model_tx: Sender<Arc<M>>,
+    input_rx: InputReceiver,
+    command_rx: Receiver<Command<M>>,
+    /// Woken by command sender, when command is sent.
+    command_waker: Arc<Mutex<Option<Waker>>>,
+    notification_tx: WakingSender<Notification>,
+    model_tx: WakingSender<Arc<M>>,
+    request_tx: WakingSender<Request>,
+    status: StatusWatch,
 }
 
-impl<M: Model> ModelManager<M> {
+impl<M: Model + Serialize + DeserializeOwned> ModelManager<M> {
+    /// Manager, which can save, load and rewind model.
     pub fn new(model: Arc<M>, logger: Logger) -> (Self, OuterBonds<M>) {
+        let (mut model_manager, outer_bonds) = Self::without_snapshots(model, logger);
+        model_manager.snapshots = Some(Snapshots {
+            save: |model, path| snapshot::save(model, path),
+            load: |path| snapshot::load(path),
+            write: |model, bytes| snapshot::write(model, bytes),
+            read: |mut bytes| snapshot::read(&mut bytes),
+        });
+        (model_manager, outer_bonds)
+    }
+}
+
+impl<M: Model> ModelManager<M> {
+    /// Manager of model, which can't be serialized. Save, Load and Rewind commands fail.
+    pub fn without_snapshots(model: Arc<M>, logger: Logger) -> (Self, OuterBonds<M>) {
         let (outer_bonds, inner_bonds) = <ModelManager<M>>::create_bonds();
 
         trace!(logger, "Creating model manager");
         let model_manager = Self {
             model,
+            prior_result: None,
+            prepared: None,
+            postponed: None,
+            busy_since: None,
             inner_bonds,
             state: State::Stoped,
+            tick_period: None,
+            tick_rate_changed: true,
+            last_tick: Instant::now(),
+            tick: 0,
+            history: History::default(),
+            snapshots: None,
+            input_state: InputState::new(),
+            jobs: JobSystem::default(),
+            clock: Clock::System,
             logger,
         };
         (model_manager, outer_bonds)
@@ -79,40 +267,290 @@ impl<M: Model> ModelManager<M> {
     /// Creates inner and outer parts of communication tools
     fn create_bonds() -> (OuterBonds<M>, InnerBonds<M>) {
         let (command_tx, command_rx) = channel();
-        let (notification_tx, notification_rx) = channel();
-        let (input_tx, input_rx) = channel();
-        let (model_tx, model_rx) = channel();
+        let command_waker = Arc::new(Mutex::new(None));
+        let wakers = BondWakers::default();
+        let (notification_tx, notification_rx) = wakers.notification_channel();
+        let (input_tx, input_rx) = input_queue(QueuePolicy::default());
+        let (model_tx, model_rx) = wakers.model_channel();
+        let (request_tx, request_rx) = wakers.request_channel();
+        let status = StatusWatch::new(Status {
+            state: State::Stoped,
+            tick: 0,
+            tick_rate: None,
+        });
 
         let outer_bonds = OuterBonds {
             input_tx,
-            command_tx,
+            command_tx: CommandSender {
+                tx: command_tx,
+                waker: command_waker.clone(),
+            },
             notification_rx,
             model_rx,
+            request_rx,
+            status: status.clone(),
+            wakers,
         };
 
         let inner_bonds = InnerBonds {
             input_rx,
             command_rx,
+            command_waker,
             notification_tx,
             model_tx,
+            request_tx,
+            status,
         };
         (outer_bonds, inner_bonds)
     }
 
+    /// Replaces job system, model runs its jobs on, e.g. with one shared by all models.
+    /// Own job system of manager starts no threads until model runs jobs on it.
+    pub fn set_jobs(&mut self, jobs: JobSystem) {
+        self.jobs = jobs;
+    }
+
+    /// Replaces source of time, e.g. with virtual clock of harness. Tick period restarts.
+    pub fn set_clock(&mut self, clock: Clock) {
+        self.last_tick = clock.now();
+        self.clock = clock;
+    }
+
+    /// Shares current model, as it would be published to presenter.
+    pub(crate) fn shared_model(&self) -> Arc<M> {
+        self.model.clone()
+    }
+
+    /// Sets waker, which is woken every time command is sent.
+    pub fn set_command_waker(&mut self, waker: Waker) {
+        *self.inner_bonds.command_waker.lock().unwrap() = Some(waker);
+    }
+
     /// Interpret recieved commands and return state required by them.
-    fn interpret_commands(&self) -> State {
-        let mut state = State::Running;
-        for command in self.inner_bonds.command_rx.try_iter() {
+    /// Command, which needs model while it's shared, is postponed with all following ones.
+    fn interpret_commands(&mut self) -> State {
+        let mut state = self.state;
+        while let Some(command) = self.next_command() {
+            if let Command::Apply(_) = command {
+                if self.model_busy() {
+                    trace!(self.logger, "Model is shared. Postponing command");
+                    self.postponed = Some(command);
+                    break;
+                }
+            }
             trace!(self.logger, "Got command: {:?}", command);
-            match command {
-                Command::Stop => state = State::Stoped,
-                Command::Run => state = State::Running,
-                Command::Exit => return State::Exitting,
+            state = self.interpret_command(command, state);
+            if let State::Exitting = state {
+                break;
             }
         }
         state
     }
 
+    fn next_command(&mut self) -> Option<Command<M>> {
+        self.postponed
+            .take()
+            .or_else(|| self.inner_bonds.command_rx.try_recv().ok())
+    }
+
+    /// Interpret single command and return state required by it.
+    fn interpret_command(&mut self, command: Command<M>, state: State) -> State {
+        match command {
+            Command::Stop => State::Stoped,
+            Command::Run => State::Running,
+            Command::Exit => State::Exitting,
+            Command::Reset(model) => {
+                self.replace_model(model);
+                state
+            }
+            Command::Save(path) => {
+                info!(self.logger, "Saving model snapshot to {:?}", path);
+                let saved = self
+                    .snapshots()
+                    .and_then(|snapshots| (snapshots.save)(self.model.as_ref(), &path));
+                if let Err(e) = saved {
+                    error!(self.logger, "Can't save model snapshot: {}", e);
+                    self.send_error(e.into());
+                }
+                state
+            }
+            Command::Load(path) => {
+                info!(self.logger, "Loading model snapshot from {:?}", path);
+                match self.load(&path) {
+                    Ok(model) => self.replace_model(model),
+                    Err(e) => {
+                        error!(self.logger, "Can't load model snapshot: {}", e);
+                        self.send_error(e);
+                    }
+                }
+                state
+            }
+            Command::Step(0) => state,
+            Command::Step(n) => State::Stepping(n),
+            Command::SetTickRate(rate) => {
+                info!(self.logger, "Setting tick rate: {:?}", rate);
+                match rate.map(tick_period).transpose() {
+                    Ok(period) => {
+                        self.tick_period = period;
+                        self.tick_rate_changed = true;
+                    }
+                    Err(e) => {
+                        error!(self.logger, "Can't set tick rate: {}", e);
+                        self.send_error(e.into());
+                    }
+                }
+                state
+            }
+            Command::Apply(f) => {
+                trace!(self.logger, "Applying closure to model");
+                match self.mut_model() {
+                    Ok(model) => {
+                        f(model);
+                        // Model was changed not by inputs, so replay must start from here.
+                        self.make_checkpoint();
+                    }
+                    Err(e) => {
+                        error!(self.logger, "Can't apply closure to model: {}", e);
+                        self.send_error(e.into());
+                    }
+                }
+                state
+            }
+            Command::Query(f) => {
+                trace!(self.logger, "Querying model");
+                f(self.model.as_ref());
+                state
+            }
+            Command::Rewind(ticks) => {
+                self.rewind(ticks);
+                state
+            }
+            Command::Callback(f) => {
+                f();
+                state
+            }
+        }
+    }
+
+    /// Replaces model and forgets prior result and history of previous one.
+    fn replace_model(&mut self, model: M) {
+        trace!(self.logger, "Replacing model");
+        self.model = Arc::new(model);
+        self.prior_result = None;
+        self.prepared = None;
+        self.tick_rate_changed = true;
+        self.history.clear();
+    }
+
+    fn snapshots(&self) -> Result<&Snapshots<M>, SnapshotError> {
+        self.snapshots
+            .as_ref()
+            .ok_or(SnapshotError::NotSerializable)
+    }
+
+    /// Loads model from snapshot file and restores its logic.
+    fn load(&self, path: &Path) -> Result<M, ModelManagerError> {
+        let mut model = (self.snapshots()?.load)(path)?;
+        model.restore()?;
+        Ok(model)
+    }
+
+    /// Stores current model state in history. Does nothing for model, which can't be saved.
+    fn make_checkpoint(&mut self) {
+        let write = match &self.snapshots {
+            Some(snapshots) => snapshots.write,
+            None => return,
+        };
+        trace!(self.logger, "Making checkpoint at tick {}", self.tick);
+        let mut state = Vec::new();
+        match write(self.model.as_ref(), &mut state) {
+            Ok(()) => self.history.checkpoint(
+                self.tick,
+                self.clock.now(),
+                state,
+                self.input_state.clone(),
+            ),
+            Err(e) => {
+                error!(self.logger, "Can't make checkpoint: {}", e);
+                self.send_error(e.into());
+            }
+        }
+    }
+
+    /// Rebuilds model state it had `ticks` updates ago from the nearest checkpoint and recorded inputs.
+    fn rewind(&mut self, ticks: u64) {
+        let target = self.tick.saturating_sub(ticks);
+        info!(
+            self.logger,
+            "Rewinding from tick {} to {}", self.tick, target
+        );
+        let replay = match self.history.replay(target) {
+            Some(replay) => replay,
+            None => {
+                warn!(self.logger, "Nothing to rewind to: history is empty");
+                return;
+            }
+        };
+        let target = replay.target;
+        let read = match self.snapshots() {
+            Ok(snapshots) => snapshots.read,
+            Err(e) => {
+                error!(self.logger, "Can't rewind model: {}", e);
+                self.send_error(e.into());
+                return;
+            }
+        };
+        match Self::rebuild(replay, read, &self.jobs) {
+            Ok((model, prior_result, input_state)) => {
+                self.model = Arc::new(model);
+                self.prior_result = prior_result;
+                self.prepared = None;
+                // Model may be rewound to tick, when tick rate was different.
+                self.tick_rate_changed = true;
+                self.input_state = input_state;
+                self.tick = target;
+                self.history.truncate(target);
+                self.inner_bonds
+                    .model_tx
+                    .send(self.model.clone())
+                    .unwrap_or_else(|e| warn!(self.logger, "Can't send rewound model: {}", e));
+            }
+            Err(e) => {
+                error!(self.logger, "Can't rewind model: {}", e);
+                self.send_error(e);
+            }
+        }
+    }
+
+    /// Restores model from checkpoint and updates it with recorded inputs.
+    /// Prior result at checkpoint is not recorded, so replay starts without it.
+    /// Requests, made during replay, were already sent, so they are dropped.
+    fn rebuild(
+        replay: Replay,
+        read: fn(&[u8]) -> Result<M, SnapshotError>,
+        jobs: &JobSystem,
+    ) -> Result<(M, Option<M::PriorResult>, InputState), ModelManagerError> {
+        let mut model = read(replay.state)?;
+        model.restore()?;
+        let mut input_state = replay.input_state.clone();
+        let mut prior_result = None;
+        for inputs in replay.inputs {
+            input_state.begin_tick();
+            inputs.iter().for_each(|input| input_state.apply(input));
+            prior_result = match model.prior(prior_result, jobs) {
+                ModelState::Running(prior_result) => prior_result,
+                ModelState::Finished => return Ok((model, None, input_state)),
+            };
+            let state = model.update(prior_result, inputs.into_iter(), &input_state, jobs);
+            model.take_requests();
+            prior_result = match state {
+                ModelState::Running(prior_result) => prior_result,
+                ModelState::Finished => return Ok((model, None, input_state)),
+            };
+        }
+        Ok((model, prior_result, input_state))
+    }
+
     /// Tries to get mutable reference from Arc to model.
     /// If count of strong references in that Arc is bigger than 1, then returns ModelNotFreeError::ArcGetMutFailed.
     fn try_get_mut_model(&mut self) -> Result<&mut M, ModelNotFreeError> {
@@ -130,56 +568,117 @@ impl<M: Model> ModelManager<M> {
         }
     }
 
-    /// Waits for strong reference count in Arc to model equals to 1.
-    /// Return ModelNotFreeError::WaitTimeoutExceeded if wait time more than timeout.
-    /// Return ModelNotFreeError::ArcGetMutFailed if whatever can't get mut reference by some reason.
-    fn wait_for_mut_model(&mut self) -> Result<&mut M, ModelNotFreeError> {
-        trace!(self.logger, "Start wait for &mut Model");
-        let wait_result = wait_for(
-            || Arc::strong_count(&self.model) == 1,
-            Some(Duration::from_secs(2)),
-            Duration::from_micros(500),
-        );
-        if wait_result {
-            trace!(self.logger, "Got &mut Model");
-            return self.try_get_mut_model();
+    /// Returns true, while presenter holds model and wait time is not exceeded.
+    /// When it returns false, model is free, or `mut_model` reports exceeded wait time.
+    fn model_busy(&mut self) -> bool {
+        if Arc::strong_count(&self.model) == 1 {
+            self.busy_since = None;
+            return false;
+        }
+        let now = self.clock.now();
+        let busy_since = *self.busy_since.get_or_insert(now);
+        if now - busy_since < MODEL_WAIT_TIMEOUT {
+            return true;
         }
         warn!(self.logger, "Wait time for &mut Model exceeded");
-        Err(ModelNotFreeError::WaitTimeoutExceeded)
+        self.busy_since = None;
+        false
+    }
+
+    /// Returns mutable model, if presenter doesn't hold it. Never blocks: use `model_busy` to wait.
+    fn mut_model(&mut self) -> Result<&mut M, ModelNotFreeError> {
+        if Arc::strong_count(&self.model) != 1 {
+            return Err(ModelNotFreeError::WaitTimeoutExceeded);
+        }
+        self.try_get_mut_model()
     }
 
-    /// Clone recieved events into Vec.
+    /// Takes queued input events and reports dropped ones.
     fn take_input_events(&mut self) -> Vec<Input> {
-        self.inner_bonds.input_rx.try_iter().collect()
+        let (inputs, dropped) = self.inner_bonds.input_rx.take();
+        if dropped > 0 {
+            warn!(
+                self.logger,
+                "Input queue overflowed. Dropped {} inputs", dropped
+            );
+            self.send_notification(Notification::InputDropped(dropped));
+        }
+        inputs
     }
 
     /// Updates model before send to presenter.
     fn before_present(
         &mut self,
         prior_result: Option<M::PriorResult>,
-    ) -> Result<Option<M::PriorResult>, ModelNotFreeError> {
+    ) -> Result<ModelState<M::PriorResult>, ModelNotFreeError> {
         trace!(self.logger, "Before present updating start");
+        // Model is taken before inputs, so they are neither lost nor recorded twice,
+        // if it's not free. Nothing shares it till update.
+        let (rate, rate_changed) = (self.tick_rate(), self.tick_rate_changed);
+        let model = self.mut_model()?;
+        if rate_changed {
+            model.set_tick_rate(rate);
+        }
         let input_events = self.take_input_events();
         trace!(self.logger, "Got {:?} input events", input_events.len());
-        let model = self.wait_for_mut_model()?;
-        Ok(model.update(prior_result, input_events.into_iter()))
+        // Model was changed not by inputs, so replay must start from here.
+        if std::mem::take(&mut self.tick_rate_changed) || self.history.needs_checkpoint(self.tick) {
+            self.make_checkpoint();
+        }
+        self.history.record_inputs(self.tick, input_events.clone());
+        self.input_state.begin_tick();
+        for input in &input_events {
+            self.input_state.apply(input);
+        }
+        let input_state = self.input_state.clone();
+        let jobs = self.jobs.clone();
+        let model = self
+            .mut_model()
+            .expect("model is taken at the beginning of update");
+        let reloaded = model.reload();
+        let state = model.update(prior_result, input_events.into_iter(), &input_state, &jobs);
+        let requests = model.take_requests();
+        if let Err(e) = reloaded {
+            warn!(self.logger, "Model reload failed: {}", e);
+            self.send_error(e.into());
+        }
+        self.send_requests(requests);
+        self.tick += 1;
+        Ok(state)
     }
 
     /// Makes some calculations in model while it is shared with presenter.
-    fn while_present(&self, prior_result: Option<M::PriorResult>) -> Option<M::PriorResult> {
+    fn while_present(&self, prior_result: Option<M::PriorResult>) -> ModelState<M::PriorResult> {
         trace!(self.logger, "While present calculations start");
-        self.model.prior(prior_result)
+        self.model.prior(prior_result, &self.jobs)
+    }
+
+    /// Makes calculations of next tick, which don't need mutable model.
+    /// Returns false, if presenter still holds model, so tick must wait.
+    fn prepare_tick(&mut self) -> bool {
+        if self.prepared.is_none() {
+            let prior_result = self.prior_result.take();
+            self.prepared = Some(self.while_present(prior_result));
+        }
+        !self.model_busy()
     }
 
     /// Updates model and sends it to presenter
-    fn update(
-        &mut self,
-        prior_result: Option<M::PriorResult>,
-    ) -> Result<Option<M::PriorResult>, UpdateError> {
-        let prior_result = self.while_present(prior_result);
+    fn update(&mut self) -> Result<ModelState<M::PriorResult>, UpdateError> {
+        let prepared = match self.prepared.take() {
+            Some(prepared) => prepared,
+            None => {
+                let prior_result = self.prior_result.take();
+                self.while_present(prior_result)
+            }
+        };
+        let prior_result = match prepared {
+            ModelState::Running(prior_result) => prior_result,
+            ModelState::Finished => return Ok(ModelState::Finished),
+        };
         trace!(self.logger, "While present calculations done");
 
-        let prior_result = self.before_present(prior_result)?;
+        let state = self.before_present(prior_result)?;
         trace!(
             self.logger,
             "Before present updating done. Sending Arc clone..."
@@ -187,37 +686,108 @@ impl<M: Model> ModelManager<M> {
         self.inner_bonds.model_tx.send(self.model.clone())?;
         trace!(self.logger, "Arc clone sent");
 
-        Ok(prior_result)
+        Ok(state)
     }
 
     pub fn run(mut self) {
         trace!(self.logger, "Starting model manager loop");
-        let mut prior_result = None;
         loop {
-            self.state = self.interpret_commands();
-            trace!(self.logger, "New state is: {:?}", self.state);
-
-            match self.state {
-                State::Running => {
-                    trace!(self.logger, "State::Running: Start updating model");
-                    prior_result = self.update(prior_result).unwrap_or_else(|e| {
-                        error!(self.logger, "Update error: {}", e);
-                        trace!(self.logger, "Stop model manager loop");
-                        self.state = State::Stoped;
-                        trace!(self.logger, "Sending error");
-                        self.send_error(e.into());
-                        None
-                    });
+            match self.run_once() {
+                Schedule::At(at) => {
+                    let now = Instant::now();
+                    if at > now {
+                        std::thread::sleep(at - now);
+                    }
                 }
-                State::Stoped => {
-                    trace!(self.logger, "State::Stopped: waiting for next commands");
-                    self.wait_for_next_commands();
+                Schedule::OnCommand => self.wait_for_next_commands(),
+                Schedule::Exit => break,
+            }
+        }
+    }
+
+    /// Interprets recieved commands and makes single model update if it is time for it.
+    /// Never blocks waiting for commands, tick time or model shared with presenter.
+    /// Returns when it should be called again.
+    pub fn run_once(&mut self) -> Schedule {
+        self.state = self.interpret_commands();
+        trace!(self.logger, "New state is: {:?}", self.state);
+        self.publish_status();
+
+        if self.postponed.is_some() && self.state != State::Exitting {
+            return Schedule::At(self.clock.now() + MODEL_CHECK_PERIOD);
+        }
+        match self.state {
+            State::Running | State::Stepping(_) => {
+                let next_tick_at = self.next_tick_at();
+                if next_tick_at > self.clock.now() {
+                    return Schedule::At(next_tick_at);
                 }
-                State::Exitting => {
-                    trace!(self.logger, "State::Exitting: leaving loop");
-                    break;
+                if !self.prepare_tick() {
+                    return Schedule::At(self.clock.now() + MODEL_CHECK_PERIOD);
                 }
             }
+            State::Stoped => {
+                trace!(self.logger, "State::Stopped: waiting for next commands");
+                return Schedule::OnCommand;
+            }
+            State::Exitting => {
+                trace!(self.logger, "State::Exitting: leaving loop");
+                return Schedule::Exit;
+            }
+        }
+
+        match self.state {
+            State::Running => {
+                trace!(self.logger, "State::Running: Start updating model");
+                self.tick();
+            }
+            State::Stepping(n) => {
+                trace!(self.logger, "State::Stepping: {} updates left", n);
+                self.tick();
+                if let State::Stepping(_) = self.state {
+                    self.state = match n - 1 {
+                        0 => State::Stoped,
+                        n => State::Stepping(n),
+                    };
+                }
+            }
+            _ => {}
+        }
+        self.publish_status();
+        Schedule::At(self.next_tick_at())
+    }
+
+    /// Makes single model update now, whatever state and tick rate are.
+    /// Lets harness update model in its virtual time.
+    pub(crate) fn tick_now(&mut self) {
+        self.tick();
+        self.publish_status();
+    }
+
+    /// Makes single model update.
+    fn tick(&mut self) {
+        self.last_tick = self.clock.now();
+        match self.update() {
+            Ok(ModelState::Running(prior_result)) => self.prior_result = prior_result,
+            Ok(ModelState::Finished) => {
+                trace!(self.logger, "Model finished");
+                self.state = State::Exitting;
+            }
+            Err(e) => {
+                error!(self.logger, "Update error: {}", e);
+                trace!(self.logger, "Stop model manager loop");
+                self.state = State::Stoped;
+                trace!(self.logger, "Sending error");
+                self.send_error(e.into());
+            }
+        }
+    }
+
+    /// Time, when tick period since last tick is passed.
+    fn next_tick_at(&self) -> Instant {
+        match self.tick_period {
+            Some(period) => self.last_tick + period,
+            None => self.last_tick,
         }
     }
 
@@ -227,11 +797,7 @@ impl<M: Model> ModelManager<M> {
         match command {
             Ok(c) => {
                 trace!(self.logger, "Command recieved: {:?}", c);
-                self.state = match c {
-                    Command::Run => State::Running,
-                    Command::Exit => State::Exitting,
-                    _ => State::Stoped,
-                }
+                self.state = self.interpret_command(c, self.state);
             }
             Err(e) => {
                 trace!(self.logger, "Command recieve error: {}", e);
@@ -240,11 +806,39 @@ impl<M: Model> ModelManager<M> {
         }
     }
 
+    fn publish_status(&self) {
+        self.inner_bonds.status.set(Status {
+            state: self.state,
+            tick: self.tick,
+            tick_rate: self.tick_rate(),
+        });
+    }
+
+    /// Updates per second. None means as fast as possible.
+    fn tick_rate(&self) -> Option<f64> {
+        self.tick_period.map(|period| 1.0 / period.as_secs_f64())
+    }
+
+    /// Sends model requests to application.
+    fn send_requests(&self, requests: Vec<Request>) {
+        for request in requests {
+            trace!(self.logger, "Sending model request: {:?}", request);
+            self.inner_bonds
+                .request_tx
+                .send(request)
+                .unwrap_or_else(|e| warn!(self.logger, "Can't send model request: {}", e));
+        }
+    }
+
     fn send_error(&mut self, e: ModelManagerError) {
         trace!(self.logger, "Sending error: {}", e);
+        self.send_notification(Notification::Error(e));
+    }
+
+    fn send_notification(&mut self, notification: Notification) {
         self.inner_bonds
             .notification_tx
-            .send(Notification::Error(e))
+            .send(notification)
             .unwrap_or_else(|e| {
                 let error_message = format!("Can't send notification to main: {}", e);
                 utils::show_error_message("Model manager error", error_message.as_str());
@@ -253,3 +847,138 @@ impl<M: Model> ModelManager<M> {
             });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::InputEvent;
+    use crate::model::counter_model::CounterModel;
+    use crate::model::harness::Harness;
+    use winit::event::{ElementState, VirtualKeyCode};
+
+    #[test]
+    fn rewind_does_not_repeat_requests() {
+        let mut harness = Harness::new(CounterModel::new());
+        harness.press(VirtualKeyCode::Up).tick().ticks(2);
+        assert_eq!(harness.take_requests().len(), 1);
+
+        harness.command(Command::Rewind(2)).tick();
+        assert!(harness.take_notifications().is_empty());
+        assert!(harness.take_requests().is_empty());
+        assert_eq!(harness.inspect(|model| model.count()), 1);
+    }
+
+    #[test]
+    fn rewound_model_equals_model_at_that_tick() {
+        let mut harness = Harness::new(CounterModel::new());
+        for _ in 0..3 {
+            harness.press(VirtualKeyCode::Up).tick();
+            harness.release(VirtualKeyCode::Up).ticks(2);
+        }
+        let saved = |model: &CounterModel| {
+            let mut bytes = Vec::new();
+            snapshot::write(model, &mut bytes).unwrap();
+            bytes
+        };
+        let at_nine = harness.inspect(saved);
+        harness.press(VirtualKeyCode::Down).tick();
+        harness.release(VirtualKeyCode::Down).ticks(4);
+        assert_ne!(harness.inspect(saved), at_nine);
+
+        harness.command(Command::Rewind(5));
+        assert_eq!(harness.inspect(saved), at_nine);
+    }
+
+    #[test]
+    fn busy_model_keeps_inputs_for_next_update() {
+        let logger = Logger::root(slog::Discard, slog::o!());
+        let (mut manager, bonds) = ModelManager::new(Arc::new(CounterModel::new()), logger);
+        manager.tick_now();
+        let shared = bonds.model_rx.recv().unwrap();
+        let up = InputEvent::Keyboard {
+            key: VirtualKeyCode::Up,
+            state: ElementState::Pressed,
+        };
+        bonds
+            .input_tx
+            .send(Input::new(Instant::now(), None, up))
+            .unwrap();
+        manager.tick_now();
+        assert_eq!(manager.tick, 1);
+
+        drop(shared);
+        manager.tick_now();
+        assert_eq!(manager.tick, 2);
+        assert_eq!(bonds.model_rx.recv().unwrap().count(), 1);
+        let replay = manager.history.replay(2).unwrap();
+        assert_eq!(replay.inputs.iter().map(Vec::len).sum::<usize>(), 1);
+    }
+
+    /// Model without serde support.
+    struct Plain;
+
+    impl Model for Plain {
+        type PriorResult = ();
+
+        fn prior(&self, _: Option<()>, _: &JobSystem) -> ModelState<()> {
+            ModelState::Running(None)
+        }
+
+        fn update(
+            &mut self,
+            _: Option<()>,
+            _: impl Iterator<Item = Input>,
+            _: &InputState,
+            _: &JobSystem,
+        ) -> ModelState<()> {
+            ModelState::Running(None)
+        }
+    }
+
+    #[test]
+    fn model_without_snapshots_runs_but_is_not_saved() {
+        let logger = Logger::root(slog::Discard, slog::o!());
+        let (mut manager, bonds) = ModelManager::without_snapshots(Arc::new(Plain), logger);
+        manager.tick_now();
+        assert_eq!(manager.tick, 1);
+
+        let path = std::env::temp_dir().join("plain_model_snapshot");
+        bonds.command_tx.send(Command::Save(path.clone())).unwrap();
+        manager.run_once();
+        assert!(matches!(
+            bonds.notification_rx.try_recv(),
+            Ok(Notification::Error(ModelManagerError::Snapshot(
+                SnapshotError::NotSerializable
+            )))
+        ));
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn query_reads_shared_model_without_checkpoint() {
+        let logger = Logger::root(slog::Discard, slog::o!());
+        let (mut manager, bonds) = ModelManager::new(Arc::new(CounterModel::new()), logger);
+        manager.tick_now();
+        manager.tick_now();
+        let shared = bonds.model_rx.recv().unwrap();
+        let (count_tx, count_rx) = std::sync::mpsc::channel();
+        let query = Command::Query(Box::new(move |model: &CounterModel| {
+            count_tx.send(model.count()).unwrap();
+        }));
+        bonds.command_tx.send(query).unwrap();
+        manager.run_once();
+        assert_eq!(count_rx.try_recv(), Ok(0));
+        assert!(manager.postponed.is_none());
+        // The only checkpoint is still at the first tick.
+        assert_eq!(manager.history.replay(manager.tick).unwrap().start, 0);
+        drop(shared);
+    }
+
+    #[test]
+    fn tick_period_rejects_unsupported_rates() {
+        assert_eq!(tick_period(50.0).unwrap(), Duration::from_millis(20));
+        for rate in &[0.0, -1.0, 1e-20, 1e20, f64::NAN, f64::INFINITY] {
+            assert!(tick_period(*rate).is_err(), "rate {} accepted", rate);
+        }
+    }
+}
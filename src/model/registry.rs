@@ -0,0 +1,310 @@
+use crate::input::Input;
+use crate::model::async_bonds::BondStream;
+use crate::model::input_queue::InputSender;
+use slog::{trace, warn, Logger};
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::mpsc::Receiver;
+use std::sync::Arc;
+use winit::window::WindowId;
+
+/// Stable identifier of model in registry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ModelId(pub &'static str);
+
+impl fmt::Display for ModelId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+/// Model of any type, published by one of registered model managers.
+pub struct Published {
+    pub id: ModelId,
+    model: Arc<dyn Any + Send + Sync>,
+}
+
+impl Published {
+    /// Returns model if it has type `M`.
+    pub fn downcast<M: Any + Send + Sync>(&self) -> Option<Arc<M>> {
+        self.model.clone().downcast().ok()
+    }
+}
+
+/// Type erased receiving end of model manager's model channel.
+pub trait ModelSource: Send {
+    /// Returns the newest recieved model, dropping older ones.
+    fn latest(&self) -> Option<Arc<dyn Any + Send + Sync>>;
+}
+
+impl<M: Any + Send + Sync> ModelSource for Receiver<Arc<M>> {
+    fn latest(&self) -> Option<Arc<dyn Any + Send + Sync>> {
+        let model = self.try_iter().last()?;
+        Some(model)
+    }
+}
+
+impl<M: Any + Send + Sync> ModelSource for BondStream<Arc<M>> {
+    fn latest(&self) -> Option<Arc<dyn Any + Send + Sync>> {
+        let model = std::iter::from_fn(|| self.try_recv().ok()).last()?;
+        Some(model)
+    }
+}
+
+struct Entry {
+    id: ModelId,
+    priority: i32,
+    input_tx: InputSender,
+    source: Box<dyn ModelSource>,
+}
+
+/// Hosts several models, each managed by it's own model manager.
+/// Splits into input router, used by event loop, and model feed, used by presenter.
+pub struct ModelRegistry {
+    entries: Vec<Entry>,
+    logger: Logger,
+}
+
+impl ModelRegistry {
+    pub fn new(logger: Logger) -> Self {
+        Self {
+            entries: Vec::new(),
+            logger,
+        }
+    }
+
+    /// Adds model manager bonds to registry. Models are received from `model_rx`,
+    /// which is model receiver of either outer or async bonds.
+    /// Unfocused input goes to model with the biggest priority, which still runs.
+    pub fn register(
+        &mut self,
+        id: ModelId,
+        priority: i32,
+        input_tx: InputSender,
+        model_rx: impl ModelSource + 'static,
+    ) {
+        trace!(
+            self.logger,
+            "Registering model {} with priority {}",
+            id,
+            priority
+        );
+        if self.entries.iter().any(|e| e.id == id) {
+            warn!(
+                self.logger,
+                "Model {} is already registered. Replacing it", id
+            );
+            self.entries.retain(|e| e.id != id);
+        }
+        self.entries.push(Entry {
+            id,
+            priority,
+            input_tx,
+            source: Box::new(model_rx),
+        });
+        self.entries.sort_by_key(|e| -e.priority);
+    }
+
+    pub fn split(self) -> (InputRouter, ModelFeed) {
+        let mut routes = Vec::with_capacity(self.entries.len());
+        let mut sources = Vec::with_capacity(self.entries.len());
+        for entry in self.entries {
+            routes.push((entry.id, entry.input_tx));
+            sources.push((entry.id, entry.source));
+        }
+        let router = InputRouter {
+            routes,
+            focus: None,
+            windows: HashMap::new(),
+            logger: self.logger.clone(),
+        };
+        let feed = ModelFeed { sources };
+        (router, feed)
+    }
+}
+
+/// Sends input to focused model, or to model bound to window, which received input.
+pub struct InputRouter {
+    /// Sorted by priority, the biggest first.
+    routes: Vec<(ModelId, InputSender)>,
+    focus: Option<ModelId>,
+    windows: HashMap<WindowId, ModelId>,
+    logger: Logger,
+}
+
+impl InputRouter {
+    /// Makes model with `id` recieve all input. None gives input back to the most prior model.
+    pub fn set_focus(&mut self, id: Option<ModelId>) {
+        trace!(self.logger, "Input focus: {:?}", id);
+        self.focus = id;
+    }
+
+    pub fn focus(&self) -> Option<ModelId> {
+        self.focus
+    }
+
+    /// Focuses the next model by priority. After the last one focus is cleared.
+    pub fn focus_next(&mut self) -> Option<ModelId> {
+        let next = match self.focus {
+            None => self.routes.first(),
+            Some(focus) => self.routes.iter().skip_while(|(id, _)| *id != focus).nth(1),
+        };
+        self.set_focus(next.map(|(id, _)| *id));
+        self.focus
+    }
+
+    /// Makes model with `id` recieve input from window, while nothing is focused.
+    /// None makes window input go to the most prior model.
+    pub fn bind_window(&mut self, window_id: WindowId, id: Option<ModelId>) {
+        trace!(self.logger, "Window {:?} is bound to {:?}", window_id, id);
+        match id {
+            Some(id) => self.windows.insert(window_id, id),
+            None => self.windows.remove(&window_id),
+        };
+    }
+
+    /// Model, which window is bound to.
+    pub fn window_owner(&self, window_id: WindowId) -> Option<ModelId> {
+        self.windows.get(&window_id).copied()
+    }
+
+    /// Sends input to model with `id`, whatever is focused.
+    pub fn send(&self, id: ModelId, input: Input) {
+        match self.routes.iter().find(|(route, _)| *route == id) {
+            Some((_, input_tx)) => input_tx.send(input).unwrap_or_else(|e| {
+                warn!(
+                    self.logger,
+                    "Can't send input event to {}, because: {}", id, e
+                );
+            }),
+            None => warn!(self.logger, "No model {} to send input to", id),
+        }
+    }
+
+    /// Count of inputs, waiting to be taken by every registered model.
+    pub fn queued(&self) -> Vec<(ModelId, usize)> {
+        self.routes
+            .iter()
+            .map(|(id, input_tx)| (*id, input_tx.queued()))
+            .collect()
+    }
+
+    /// Sends input to focused model, or to model bound to input's window,
+    /// or to the most prior model if nothing is focused or bound.
+    /// If that model's manager exited, input falls through to the next model by priority.
+    pub fn route(&self, input: Input) {
+        let bound = input
+            .window_id()
+            .and_then(|window_id| self.windows.get(&window_id).copied());
+        let target = self.focus.or(bound);
+        let targeted = self.routes.iter().filter(|(id, _)| Some(*id) == target);
+        let by_priority = self.routes.iter().filter(|(id, _)| Some(*id) != target);
+        let mut input = input;
+        for (id, input_tx) in targeted.chain(by_priority) {
+            match input_tx.send(input) {
+                Ok(()) => return,
+                Err(e) => {
+                    trace!(self.logger, "Model {} doesn't take input anymore", id);
+                    input = e.0;
+                }
+            }
+        }
+        warn!(self.logger, "No model to send input to");
+    }
+}
+
+/// Collects models published by registered model managers.
+pub struct ModelFeed {
+    sources: Vec<(ModelId, Box<dyn ModelSource>)>,
+}
+
+impl ModelFeed {
+    /// Returns the newest model of every model manager, which published something since last poll.
+    /// Model manager can't update model until all references to it are dropped.
+    pub fn poll(&self) -> Vec<Published> {
+        self.sources
+            .iter()
+            .filter_map(|(id, source)| source.latest().map(|model| Published { id: *id, model }))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::InputEvent;
+    use crate::model::input_queue::{input_queue, InputReceiver, QueuePolicy};
+    use slog::{o, Discard};
+    use std::sync::mpsc::channel;
+
+    fn register(registry: &mut ModelRegistry, id: ModelId, priority: i32) -> InputReceiver {
+        let (input_tx, input_rx) = input_queue(QueuePolicy::default());
+        let (_, model_rx) = channel::<Arc<()>>();
+        registry.register(id, priority, input_tx, model_rx);
+        input_rx
+    }
+
+    #[test]
+    fn window_input_goes_to_its_owner() {
+        let mut registry = ModelRegistry::new(Logger::root(Discard, o!()));
+        let main_rx = register(&mut registry, ModelId("main"), 1);
+        let tool_rx = register(&mut registry, ModelId("tool"), 0);
+        let (mut router, _) = registry.split();
+        // SAFETY: dummy id is only compared with itself.
+        let window_id = unsafe { WindowId::dummy() };
+        let input = || Input::from((None, InputEvent::Symbol('a'))).in_window(Some(window_id));
+
+        router.bind_window(window_id, Some(ModelId("tool")));
+        assert_eq!(router.window_owner(window_id), Some(ModelId("tool")));
+        router.route(input());
+        assert_eq!(tool_rx.take().0.len(), 1);
+
+        router.bind_window(window_id, None);
+        router.route(input());
+        assert_eq!(main_rx.take().0.len(), 1);
+
+        router.set_focus(Some(ModelId("main")));
+        router.send(ModelId("tool"), input());
+        assert_eq!(tool_rx.take().0.len(), 1);
+        assert!(main_rx.take().0.is_empty());
+    }
+
+    #[test]
+    fn input_falls_through_to_next_model_by_priority() {
+        let mut registry = ModelRegistry::new(Logger::root(Discard, o!()));
+        let low_rx = register(&mut registry, ModelId("low"), -1);
+        let high_rx = register(&mut registry, ModelId("high"), 2);
+        let middle_rx = register(&mut registry, ModelId("middle"), 1);
+        let (mut router, _) = registry.split();
+        let input = || Input::from((None, InputEvent::Symbol('a')));
+
+        router.route(input());
+        assert_eq!(high_rx.take().0.len(), 1);
+
+        // Manager of the most prior model exited.
+        drop(high_rx);
+        router.route(input());
+        assert_eq!(middle_rx.take().0.len(), 1);
+
+        // Focused model gets input, whatever its priority is.
+        router.set_focus(Some(ModelId("low")));
+        router.route(input());
+        assert_eq!(low_rx.take().0.len(), 1);
+        drop(low_rx);
+        router.route(input());
+        assert_eq!(middle_rx.take().0.len(), 1);
+    }
+
+    #[test]
+    fn focus_cycles_by_priority() {
+        let mut registry = ModelRegistry::new(Logger::root(Discard, o!()));
+        let _low_rx = register(&mut registry, ModelId("low"), -1);
+        let _high_rx = register(&mut registry, ModelId("high"), 2);
+        let (mut router, _) = registry.split();
+        assert_eq!(router.focus_next(), Some(ModelId("high")));
+        assert_eq!(router.focus_next(), Some(ModelId("low")));
+        assert_eq!(router.focus_next(), None);
+        assert_eq!(router.focus_next(), Some(ModelId("high")));
+    }
+}
@@ -0,0 +1,107 @@
+use winit::window::{CursorIcon, WindowId};
+
+/// Requests from model to application. They are applied in main event loop.
+/// Window requests apply to the main window.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Request {
+    /// Enable or disable text input mode. In this mode typed characters come as
+    /// `InputEvent::Text` and clipboard paste comes as `InputEvent::Paste`.
+    TextInput(bool),
+    /// Confine cursor to window, or release it.
+    GrabCursor(bool),
+    /// Hide cursor, while it is over window, or show it.
+    HideCursor(bool),
+    SetCursorIcon(CursorIcon),
+    /// Move cursor to physical position in window, the same units as in `InputEvent::CursorMove`.
+    WarpCursor((f64, f64)),
+    /// Grab and hide cursor, so only `InputEvent::RawMouseMove` is useful. Used for camera control.
+    RelativeMouse(bool),
+    SetTitle(String),
+    /// Change window inner size, in physical pixels.
+    Resize((u32, u32)),
+    /// Enter borderless fullscreen on current monitor, or leave fullscreen.
+    SetFullscreen(bool),
+    ToggleFullscreen,
+    /// Show or hide window decorations (title bar and borders).
+    SetDecorations(bool),
+    /// Open tool window next to the main one. Model gets `InputEvent::WindowOpened`
+    /// with `WindowId` of the window, and all its input, unless other model is focused.
+    /// Window is blank: there is no presenter to draw model into it yet.
+    OpenWindow {
+        title: String,
+        size: (u32, u32),
+    },
+    /// Close tool window. The main window can't be closed.
+    CloseWindow(WindowId),
+    /// Exit application.
+    Exit,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::Input;
+    use crate::input_state::InputState;
+    use crate::model::jobs::JobSystem;
+    use crate::model::model_manager::ModelManager;
+    use crate::model::{Model, State};
+    use slog::{o, Discard, Logger};
+    use std::sync::Arc;
+
+    /// Makes the same requests on every update, numbered by tick.
+    struct Requesting {
+        tick: u32,
+        requests: Vec<Request>,
+    }
+
+    impl Model for Requesting {
+        type PriorResult = ();
+
+        fn prior(&self, _: Option<()>, _: &JobSystem) -> State<()> {
+            State::Running(None)
+        }
+
+        fn update(
+            &mut self,
+            _: Option<()>,
+            _: impl Iterator<Item = Input>,
+            _: &InputState,
+            _: &JobSystem,
+        ) -> State<()> {
+            self.tick += 1;
+            self.requests.push(Request::SetTitle(self.tick.to_string()));
+            self.requests.push(Request::GrabCursor(true));
+            self.requests.push(Request::WarpCursor((1.0, 2.0)));
+            State::Running(None)
+        }
+
+        fn take_requests(&mut self) -> Vec<Request> {
+            std::mem::take(&mut self.requests)
+        }
+    }
+
+    #[test]
+    fn requests_reach_application_in_order() {
+        let model = Requesting {
+            tick: 0,
+            requests: Vec::new(),
+        };
+        let logger = Logger::root(Discard, o!());
+        let (mut manager, bonds) = ModelManager::without_snapshots(Arc::new(model), logger);
+        for _ in 0..2 {
+            manager.tick_now();
+            bonds.model_rx.try_iter().for_each(drop);
+        }
+        let requests: Vec<Request> = bonds.request_rx.try_iter().collect();
+        let expected: Vec<Request> = (1..=2)
+            .flat_map(|tick: u32| {
+                vec![
+                    Request::SetTitle(tick.to_string()),
+                    Request::GrabCursor(true),
+                    Request::WarpCursor((1.0, 2.0)),
+                ]
+            })
+            .collect();
+        assert_eq!(requests, expected);
+    }
+}
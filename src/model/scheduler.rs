@@ -0,0 +1,300 @@
+use crate::model::jobs::JobSystem;
+use crate::model::model_manager::{ModelManager, Schedule};
+use crate::model::Model;
+use slog::{trace, Logger};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::sync::{Arc, Condvar, Mutex, Weak};
+use std::task::{Wake, Waker};
+use std::thread::JoinHandle;
+use std::time::Instant;
+
+/// Something, that can be run step by step by scheduler.
+pub trait Schedulable: Send {
+    fn run_once(&mut self) -> Schedule;
+
+    /// Sets waker, which must be woken, when task returned `Schedule::OnCommand` should run again.
+    /// Task, which never wakes it, stays parked after `Schedule::OnCommand` forever.
+    fn set_waker(&mut self, waker: Waker);
+
+    /// Gives job system, shared by all tasks of scheduler. Tasks, which run no jobs, ignore it.
+    fn set_jobs(&mut self, _jobs: JobSystem) {}
+}
+
+impl<M> Schedulable for ModelManager<M>
+where
+    M: Model + Send + Sync,
+    M::PriorResult: Send,
+{
+    fn run_once(&mut self) -> Schedule {
+        ModelManager::run_once(self)
+    }
+
+    fn set_waker(&mut self, waker: Waker) {
+        self.set_command_waker(waker)
+    }
+
+    fn set_jobs(&mut self, jobs: JobSystem) {
+        ModelManager::set_jobs(self, jobs)
+    }
+}
+
+struct Job {
+    due: Instant,
+    id: usize,
+    task: Box<dyn Schedulable>,
+}
+
+impl PartialEq for Job {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Job {}
+
+impl PartialOrd for Job {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Job {
+    /// The earliest job is the greatest, so it is on top of BinaryHeap.
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .due
+            .cmp(&self.due)
+            .then_with(|| other.id.cmp(&self.id))
+    }
+}
+
+#[derive(Default)]
+struct Queue {
+    jobs: BinaryHeap<Job>,
+    /// Jobs, which wait for waker.
+    parked: HashMap<usize, Job>,
+    /// Jobs, which were woken while not parked, so they must not be parked next time.
+    woken: HashSet<usize>,
+    /// Count of jobs, which are queued or being run now.
+    alive: usize,
+    next_id: usize,
+    closing: bool,
+}
+
+struct Shared {
+    queue: Mutex<Queue>,
+    changed: Condvar,
+}
+
+impl Shared {
+    /// Queues parked job, or remembers to not park it, if it's queued or being run now.
+    fn wake(&self, id: usize) {
+        let mut queue = self.queue.lock().unwrap();
+        match queue.parked.remove(&id) {
+            Some(mut job) => {
+                job.due = Instant::now();
+                queue.jobs.push(job);
+                self.changed.notify_one();
+            }
+            None => {
+                queue.woken.insert(id);
+            }
+        }
+    }
+}
+
+/// Wakes parked job. Doesn't keep scheduler alive.
+struct JobWaker {
+    shared: Weak<Shared>,
+    id: usize,
+}
+
+impl Wake for JobWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        if let Some(shared) = self.shared.upgrade() {
+            shared.wake(self.id);
+        }
+    }
+}
+
+/// Runs many model managers on bounded count of worker threads.
+/// Their models share one job system.
+pub struct Scheduler {
+    shared: Arc<Shared>,
+    jobs: JobSystem,
+    workers: Vec<JoinHandle<()>>,
+    logger: Logger,
+}
+
+impl Scheduler {
+    /// Creates scheduler with its own job system, sized for available parallelism.
+    pub fn new(worker_count: usize, logger: Logger) -> Self {
+        Self::with_jobs(worker_count, JobSystem::default(), logger)
+    }
+
+    /// Creates scheduler, which gives `jobs` to every spawned task.
+    pub fn with_jobs(worker_count: usize, jobs: JobSystem, logger: Logger) -> Self {
+        trace!(logger, "Starting scheduler with {} workers", worker_count);
+        let shared = Arc::new(Shared {
+            queue: Mutex::new(Queue::default()),
+            changed: Condvar::new(),
+        });
+        let workers = (0..worker_count.max(1))
+            .map(|i| {
+                let shared = shared.clone();
+                let logger = logger.clone();
+                std::thread::Builder::new()
+                    .name(format!("scheduler worker {}", i))
+                    .spawn(move || work(shared, logger))
+                    .expect("can't spawn scheduler worker")
+            })
+            .collect();
+        Self {
+            shared,
+            jobs,
+            workers,
+            logger,
+        }
+    }
+
+    /// Adds model manager, or anything else schedulable, to be run by workers.
+    pub fn spawn(&self, mut task: impl Schedulable + 'static) {
+        let mut queue = self.shared.queue.lock().unwrap();
+        let id = queue.next_id;
+        task.set_jobs(self.jobs.clone());
+        task.set_waker(Waker::from(Arc::new(JobWaker {
+            shared: Arc::downgrade(&self.shared),
+            id,
+        })));
+        trace!(self.logger, "Scheduling task {}", id);
+        queue.next_id += 1;
+        queue.alive += 1;
+        queue.jobs.push(Job {
+            due: Instant::now(),
+            id,
+            task: Box::new(task),
+        });
+        self.shared.changed.notify_one();
+    }
+
+    /// Waits until all scheduled tasks exit and stops workers.
+    /// Parked tasks must be woken and told to exit before, or it waits forever.
+    pub fn join(self) {
+        trace!(self.logger, "Waiting for scheduled tasks to exit");
+        self.shared.queue.lock().unwrap().closing = true;
+        self.shared.changed.notify_all();
+        for worker in self.workers {
+            worker.join().ok();
+        }
+    }
+}
+
+/// Worker thread loop: takes the earliest due job, runs it once and puts it back.
+fn work(shared: Arc<Shared>, logger: Logger) {
+    loop {
+        let mut job = {
+            let mut queue = shared.queue.lock().unwrap();
+            loop {
+                if queue.closing && queue.alive == 0 {
+                    trace!(logger, "Scheduler worker exits");
+                    return;
+                }
+                let now = Instant::now();
+                queue = match queue.jobs.peek() {
+                    Some(job) if job.due <= now => break queue.jobs.pop().unwrap(),
+                    Some(job) => {
+                        let wait = job.due - now;
+                        shared.changed.wait_timeout(queue, wait).unwrap().0
+                    }
+                    None => shared.changed.wait(queue).unwrap(),
+                };
+            }
+        };
+
+        let schedule = job.task.run_once();
+
+        let mut queue = shared.queue.lock().unwrap();
+        match schedule {
+            Schedule::At(due) => {
+                job.due = due;
+                queue.jobs.push(job);
+            }
+            Schedule::OnCommand if queue.woken.remove(&job.id) => {
+                job.due = Instant::now();
+                queue.jobs.push(job);
+            }
+            Schedule::OnCommand => {
+                trace!(logger, "Parking scheduled task {}", job.id);
+                queue.parked.insert(job.id, job);
+            }
+            Schedule::Exit => {
+                trace!(logger, "Scheduled task {} exited", job.id);
+                queue.woken.remove(&job.id);
+                queue.alive -= 1;
+            }
+        }
+        shared.changed.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::counter_model::CounterModel;
+    use crate::model::model_manager::{Command, State};
+    use slog::{o, Discard};
+
+    #[test]
+    fn parked_manager_is_woken_by_commands() {
+        let logger = Logger::root(Discard, o!());
+        let scheduler = Scheduler::new(1, logger.clone());
+        let (manager, bonds) = ModelManager::new(Arc::new(CounterModel::new()), logger);
+        scheduler.spawn(manager);
+        while scheduler.shared.queue.lock().unwrap().parked.is_empty() {
+            std::thread::yield_now();
+        }
+
+        bonds.command_tx.send(Command::Step(2)).unwrap();
+        while bonds.status.get().state != State::Stoped || bonds.status.get().tick < 2 {
+            // Presenter releases model, so manager can update it.
+            bonds.model_rx.try_iter().for_each(drop);
+            std::thread::yield_now();
+        }
+        bonds.command_tx.send(Command::Exit).unwrap();
+        scheduler.join();
+        assert_eq!(bonds.status.get().tick, 2);
+    }
+
+    /// Remembers job system, it was given, and exits.
+    struct Probe(Arc<Mutex<Vec<JobSystem>>>);
+
+    impl Schedulable for Probe {
+        fn run_once(&mut self) -> Schedule {
+            Schedule::Exit
+        }
+
+        fn set_waker(&mut self, _: Waker) {}
+
+        fn set_jobs(&mut self, jobs: JobSystem) {
+            self.0.lock().unwrap().push(jobs);
+        }
+    }
+
+    #[test]
+    fn tasks_share_job_system_of_scheduler() {
+        let logger = Logger::root(Discard, o!());
+        let scheduler = Scheduler::with_jobs(2, JobSystem::new(3), logger);
+        let given = Arc::new(Mutex::new(Vec::new()));
+        scheduler.spawn(Probe(given.clone()));
+        scheduler.spawn(Probe(given.clone()));
+        scheduler.join();
+        let given = given.lock().unwrap();
+        assert_eq!(given.len(), 2);
+        assert!(given.iter().all(|jobs| jobs.workers() == 3));
+    }
+}
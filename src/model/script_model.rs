@@ -0,0 +1,541 @@
+//! Model with logic in Rhai script, which is reloaded when script file changes.
+//!
+//! Script defines functions:
+//!
+//! - `init()` (optional) returns object map with initial model state. Empty map is used without it.
+//! - `update(inputs, input_state)` is called every update with model state bound to `this`.
+//!   `inputs` is array of object maps, describing input events, e.g.
+//!   `#{ kind: "Keyboard", key: "Space", pressed: true }`. `input_state` has methods
+//!   `is_down(key)`, `just_pressed(key)`, `just_released(key)`, `is_button_down(button)` and
+//!   `cursor()`, where keys and buttons are named as in winit, e.g. "Space", "Left".
+//!   Returning `false` finishes model.
+//!
+//! Top level statements of script are not run. Model state is kept when script is reloaded.
+
+use super::error::reload::ReloadError;
+use super::file_watch::FileWatch;
+use super::jobs::JobSystem;
+use super::{Model, State};
+use crate::gestures::Gesture;
+use crate::input::{Input, InputEvent, ScrollUnit};
+use crate::input_state::InputState;
+use rhai::{Array, CallFnOptions, Dynamic, Engine, EvalAltResult, Map, Scope, AST};
+use serde::de::{self, Deserializer, IntoDeserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::path::{Path, PathBuf};
+use winit::event::{ElementState, MouseButton};
+
+/// Limit of operations per script call, so endless loop doesn't hang model manager.
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+/// Model, which logic is in Rhai script at `path`. Script is reloaded between ticks, when its
+/// file changes. Failed script is halted until it changes, and its error is reported on reload.
+pub struct ScriptModel {
+    script: FileWatch,
+    engine: Engine,
+    ast: Option<AST>,
+    /// Object map, bound to `this` in script. None until `init` is called.
+    state: Option<Dynamic>,
+    /// Script failed and doesn't run until reloaded.
+    halted: bool,
+    /// Runtime error, not reported yet.
+    failure: Option<Box<EvalAltResult>>,
+}
+
+impl ScriptModel {
+    /// Script is loaded on first reload check.
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            script: FileWatch::new(path),
+            engine: new_engine(),
+            ast: None,
+            state: None,
+            halted: false,
+            failure: None,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        self.script.path()
+    }
+
+    fn call(
+        &mut self,
+        name: &str,
+        args: impl rhai::FuncArgs,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        let ast = self.ast.as_ref().expect("script is not loaded");
+        let state = self.state.get_or_insert_with(|| Map::new().into());
+        let options = CallFnOptions::new().eval_ast(false).bind_this_ptr(state);
+        self.engine
+            .call_fn_with_options(options, &mut Scope::new(), ast, name, args)
+    }
+
+    fn init(&mut self) -> Result<(), Box<EvalAltResult>> {
+        let has_init = self.ast.as_ref().is_some_and(|ast| {
+            ast.iter_functions()
+                .any(|f| f.name == "init" && f.params.is_empty())
+        });
+        let state = if has_init {
+            let state = self.call("init", ())?;
+            if !state.is_map() {
+                return Err(format!("init() returned {} instead of map", state.type_name()).into());
+            }
+            state
+        } else {
+            Map::new().into()
+        };
+        self.state = Some(state);
+        Ok(())
+    }
+
+    /// Runs script update. Returns false if model is finished.
+    fn run(&mut self, inputs: Array, input_state: &InputState) -> Result<bool, Box<EvalAltResult>> {
+        if self.state.is_none() {
+            self.init()?;
+        }
+        let result = self.call("update", (inputs, input_state.clone()))?;
+        Ok(result.as_bool() != Ok(false))
+    }
+
+    fn compile(&mut self) -> Result<(), ReloadError> {
+        let source = std::fs::read_to_string(self.script.path())?;
+        let ast = self
+            .engine
+            .compile(source)
+            .map_err(|e| ReloadError::Script(e.into()))?;
+        self.ast = Some(ast);
+        self.halted = false;
+        Ok(())
+    }
+
+    /// Model state in form, which can be saved.
+    fn saved_state(&self) -> Result<Option<Value>, Box<EvalAltResult>> {
+        self.state
+            .as_ref()
+            .map(|state| rhai::serde::from_dynamic::<Untagged<Value>>(state).map(|v| v.0))
+            .transpose()
+    }
+}
+
+impl Model for ScriptModel {
+    type PriorResult = ();
+
+    fn prior(&self, _: Option<Self::PriorResult>, _: &JobSystem) -> State<Self::PriorResult> {
+        State::Running(None)
+    }
+
+    fn update(
+        &mut self,
+        _: Option<Self::PriorResult>,
+        inputs: impl Iterator<Item = Input>,
+        input_state: &InputState,
+        _: &JobSystem,
+    ) -> State<Self::PriorResult> {
+        if self.ast.is_none() || self.halted {
+            return State::Running(None);
+        }
+        let inputs = inputs
+            .map(|input| input_map(input.event()).into())
+            .collect();
+        match self.run(inputs, input_state) {
+            Ok(true) => State::Running(None),
+            Ok(false) => State::Finished,
+            Err(e) => {
+                self.halted = true;
+                self.failure = Some(e);
+                State::Running(None)
+            }
+        }
+    }
+
+    fn reload(&mut self) -> Result<(), ReloadError> {
+        if let Some(e) = self.failure.take() {
+            return Err(e.into());
+        }
+        if !self.script.changed()? {
+            return Ok(());
+        }
+        self.compile()
+    }
+
+    /// Deserialized model has no compiled script, so it's compiled at once.
+    fn restore(&mut self) -> Result<(), ReloadError> {
+        self.script.mark_current()?;
+        self.compile()
+    }
+}
+
+fn new_engine() -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine
+        .register_type_with_name::<InputState>("InputState")
+        .register_fn("is_down", |state: &mut InputState, key: &str| {
+            parse_name(key).map(|key| state.is_down(key))
+        })
+        .register_fn("just_pressed", |state: &mut InputState, key: &str| {
+            parse_name(key).map(|key| state.just_pressed(key))
+        })
+        .register_fn("just_released", |state: &mut InputState, key: &str| {
+            parse_name(key).map(|key| state.just_released(key))
+        })
+        .register_fn("is_button_down", |state: &mut InputState, button: &str| {
+            parse_name::<MouseButton>(button).map(|button| state.is_button_down(button))
+        })
+        .register_fn("cursor", |state: &mut InputState| -> Dynamic {
+            match state.cursor_position() {
+                Some(position) => point(position).into(),
+                None => Dynamic::UNIT,
+            }
+        });
+    engine
+}
+
+/// Parses key or button by its winit name.
+fn parse_name<'de, T: Deserialize<'de>>(name: &'de str) -> Result<T, Box<EvalAltResult>> {
+    let deserializer: serde::de::value::StrDeserializer<'de, serde::de::value::Error> =
+        name.into_deserializer();
+    T::deserialize(deserializer).map_err(|_| format!("Unknown key or button: {}", name).into())
+}
+
+fn point((x, y): (f64, f64)) -> Array {
+    vec![x.into(), y.into()]
+}
+
+fn name(value: &impl std::fmt::Debug) -> Dynamic {
+    format!("{:?}", value).into()
+}
+
+/// Describes input event for script.
+fn input_map(event: &InputEvent) -> Map {
+    let mut map = Map::new();
+    let mut set = |field: &str, value: Dynamic| {
+        map.insert(field.into(), value);
+    };
+    match event {
+        InputEvent::Keyboard { key, state } => {
+            set("kind", "Keyboard".into());
+            set("key", name(key));
+            set("pressed", (*state == ElementState::Pressed).into());
+        }
+        InputEvent::RawMouseMove { delta } => {
+            set("kind", "RawMouseMove".into());
+            set("delta", point(*delta).into());
+        }
+        InputEvent::CursorMove { position } => {
+            set("kind", "CursorMove".into());
+            set("position", point(*position).into());
+        }
+        InputEvent::Scroll { delta, unit } => {
+            set("kind", "Scroll".into());
+            set("delta", point(*delta).into());
+            set("lines", (*unit == ScrollUnit::Lines).into());
+        }
+        InputEvent::MouseButton { button, state } => {
+            set("kind", "MouseButton".into());
+            set("button", name(button));
+            set("pressed", (*state == ElementState::Pressed).into());
+        }
+        InputEvent::Symbol(symbol) => {
+            set("kind", "Symbol".into());
+            set("text", symbol.to_string().into());
+        }
+        InputEvent::Text(text) => {
+            set("kind", "Text".into());
+            set("text", text.clone().into());
+        }
+        InputEvent::Paste(text) => {
+            set("kind", "Paste".into());
+            set("text", text.clone().into());
+        }
+        InputEvent::Touch {
+            phase,
+            finger,
+            position,
+            ..
+        } => {
+            set("kind", "Touch".into());
+            set("phase", name(phase));
+            set("finger", (*finger as i64).into());
+            set("position", point(*position).into());
+        }
+        InputEvent::Gesture(gesture) => {
+            set("kind", "Gesture".into());
+            set("gesture", gesture_name(gesture).into());
+            if let Gesture::Combo(combo) = gesture {
+                set("combo", combo.to_string().into());
+            }
+        }
+        InputEvent::WindowOpened { title } => {
+            set("kind", "WindowOpened".into());
+            set("text", title.clone().into());
+        }
+        InputEvent::WindowClosed => set("kind", "WindowClosed".into()),
+    }
+    map
+}
+
+/// Name of gesture variant, e.g. "DoubleClick".
+fn gesture_name(gesture: &Gesture) -> String {
+    format!("{:?}", gesture)
+        .split(|c: char| !c.is_alphanumeric())
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Script value, which keeps its type in any serde format, including bincode.
+/// It's converted from and to `Dynamic` with rhai serde support. Function pointers and values
+/// of custom types can't be converted, so model with them in state can't be saved.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+enum Value {
+    Unit,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Char(char),
+    Str(String),
+    Blob(Vec<u8>),
+    Array(Vec<Value>),
+    Map(Vec<(String, Value)>),
+}
+
+/// Value without type tags, as rhai serde support sees it.
+struct Untagged<T>(T);
+
+impl Serialize for Untagged<&Value> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.0 {
+            Value::Unit => serializer.serialize_unit(),
+            Value::Bool(value) => serializer.serialize_bool(*value),
+            Value::Int(value) => serializer.serialize_i64(*value),
+            Value::Float(value) => serializer.serialize_f64(*value),
+            Value::Char(value) => serializer.serialize_char(*value),
+            Value::Str(value) => serializer.serialize_str(value),
+            Value::Blob(value) => serializer.serialize_bytes(value),
+            Value::Array(values) => serializer.collect_seq(values.iter().map(Untagged)),
+            Value::Map(entries) => {
+                serializer.collect_map(entries.iter().map(|(key, value)| (key, Untagged(value))))
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Untagged<Value> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ValueVisitor).map(Untagged)
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "unit, bool, integer, float, character, string, BLOB, array or map"
+        )
+    }
+
+    fn visit_unit<E>(self) -> Result<Value, E> {
+        Ok(Value::Unit)
+    }
+
+    fn visit_bool<E>(self, value: bool) -> Result<Value, E> {
+        Ok(Value::Bool(value))
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<Value, E> {
+        Ok(Value::Int(value))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Value, E> {
+        i64::try_from(value)
+            .map(Value::Int)
+            .map_err(|_| E::custom(format!("integer {} is too large", value)))
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<Value, E> {
+        Ok(Value::Float(value))
+    }
+
+    fn visit_char<E>(self, value: char) -> Result<Value, E> {
+        Ok(Value::Char(value))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Value, E> {
+        Ok(Value::Str(value.to_string()))
+    }
+
+    fn visit_bytes<E>(self, value: &[u8]) -> Result<Value, E> {
+        Ok(Value::Blob(value.to_vec()))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        let mut values = Vec::new();
+        while let Some(Untagged(value)) = seq.next_element()? {
+            values.push(value);
+        }
+        Ok(Value::Array(values))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+        let mut entries = Vec::new();
+        while let Some((key, Untagged(value))) = map.next_entry()? {
+            entries.push((key, value));
+        }
+        Ok(Value::Map(entries))
+    }
+}
+
+/// Serialized form of `ScriptModel`: script path and model state.
+#[derive(Serialize, Deserialize)]
+struct Saved {
+    path: PathBuf,
+    state: Option<Value>,
+}
+
+impl Serialize for ScriptModel {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::Error;
+        Saved {
+            path: self.path().to_path_buf(),
+            state: self.saved_state().map_err(S::Error::custom)?,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ScriptModel {
+    /// Script is loaded on next reload check, so deserialization can't fail because of it.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+        let saved = Saved::deserialize(deserializer)?;
+        let mut model = ScriptModel::new(saved.path);
+        if let Some(state) = saved.state {
+            let state = rhai::serde::to_dynamic(Untagged(&state)).map_err(D::Error::custom)?;
+            model.state = Some(state);
+        }
+        Ok(model)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::harness::Harness;
+    use crate::model::model_manager::Command;
+    use crate::model::snapshot;
+    use winit::event::VirtualKeyCode;
+
+    fn script(name: &str, source: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("{}-{}.rhai", std::process::id(), name));
+        std::fs::write(&path, source).unwrap();
+        path
+    }
+
+    fn field(model: &ScriptModel, name: &str) -> Value {
+        match model.saved_state().unwrap() {
+            Some(Value::Map(entries)) => entries.into_iter().find(|(n, _)| n == name).unwrap().1,
+            state => panic!("state is not map: {:?}", state),
+        }
+    }
+
+    #[test]
+    fn snapshot_keeps_types_of_state_values() {
+        let path = script(
+            "typed_state",
+            r#"
+            fn init() {
+                #{ count: 1, speed: 1.0, letter: 'a', name: "a", nothing: (),
+                   bytes: blob(2, 7), items: [1, 2.5, 'b', #{ on: true }] }
+            }
+            fn update(inputs, input_state) {}
+            "#,
+        );
+        let mut model = ScriptModel::new(path);
+        model.restore().unwrap();
+        model.update(
+            None,
+            std::iter::empty(),
+            &InputState::new(),
+            &JobSystem::new(1),
+        );
+        assert_eq!(field(&model, "count"), Value::Int(1));
+        assert_eq!(field(&model, "speed"), Value::Float(1.0));
+        assert_eq!(field(&model, "letter"), Value::Char('a'));
+
+        let mut bytes = Vec::new();
+        snapshot::write(&model, &mut bytes).unwrap();
+        let mut restored: ScriptModel = snapshot::read(&mut bytes.as_slice()).unwrap();
+        restored.restore().unwrap();
+        assert_eq!(
+            restored.saved_state().unwrap(),
+            model.saved_state().unwrap()
+        );
+    }
+
+    #[test]
+    fn state_with_function_is_not_saved() {
+        let path = script(
+            "function_state",
+            r#"
+            fn init() { #{ callback: Fn("update") } }
+            fn update(inputs, input_state) {}
+            "#,
+        );
+        let mut model = ScriptModel::new(path);
+        model.restore().unwrap();
+        model.update(
+            None,
+            std::iter::empty(),
+            &InputState::new(),
+            &JobSystem::new(1),
+        );
+        assert!(snapshot::write(&model, &mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn rewound_script_state_keeps_types() {
+        let path = script(
+            "rewound_state",
+            r#"
+            fn init() { #{ count: 0, half: 0.0 } }
+            fn update(inputs, input_state) {
+                if input_state.just_pressed("Up") {
+                    this.count += 1;
+                    this.half = this.count / 2.0;
+                }
+            }
+            "#,
+        );
+        // Fresh file is not settled yet, so script is compiled at once.
+        let mut model = ScriptModel::new(path);
+        model.restore().unwrap();
+        let mut harness = Harness::new(model);
+        for _ in 0..3 {
+            harness.press(VirtualKeyCode::Up).tick();
+            harness.release(VirtualKeyCode::Up).tick();
+        }
+        assert_eq!(
+            harness.inspect(|model| field(model, "count")),
+            Value::Int(3)
+        );
+
+        harness.command(Command::Rewind(4)).tick();
+        assert!(harness.take_notifications().is_empty());
+        assert_eq!(
+            harness.inspect(|model| field(model, "count")),
+            Value::Int(1)
+        );
+        assert_eq!(
+            harness.inspect(|model| field(model, "half")),
+            Value::Float(0.5)
+        );
+    }
+}
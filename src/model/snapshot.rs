@@ -0,0 +1,74 @@
+use crate::model::error::snapshot::SnapshotError;
+use crate::versioned_file::FileFormat;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::io::{Read, Write};
+use std::path::Path;
+
+/// Current snapshot format version. Increase it on every incompatible change.
+pub const VERSION: u32 = 2;
+
+const FORMAT: FileFormat = FileFormat {
+    name: "model snapshot",
+    magic: b"IPMS",
+    version: VERSION,
+};
+
+/// Writes model state to file at `path`, prefixed with format header.
+pub fn save<M: Serialize>(model: &M, path: &Path) -> Result<(), SnapshotError> {
+    Ok(FORMAT.save(model, path)?)
+}
+
+/// Reads model state from file at `path`, checking format header.
+pub fn load<M: DeserializeOwned>(path: &Path) -> Result<M, SnapshotError> {
+    Ok(FORMAT.load(path)?)
+}
+
+/// Writes format header and model state to `writer`.
+pub fn write<M: Serialize>(model: &M, writer: &mut impl Write) -> Result<(), SnapshotError> {
+    Ok(FORMAT.write(model, writer)?)
+}
+
+/// Reads model state from `reader`, checking format header.
+pub fn read<M: DeserializeOwned>(reader: &mut impl Read) -> Result<M, SnapshotError> {
+    Ok(FORMAT.read(reader)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::versioned_file::VersionedFileError;
+    use crate::model::counter_model::CounterModel;
+    use crate::model::Model;
+
+    #[test]
+    fn saved_model_loads_back() {
+        let mut model = CounterModel::new();
+        model.set_variable("counter", "7").unwrap();
+        let path = std::env::temp_dir().join(format!("snapshot_test_{}", std::process::id()));
+        save(&model, &path).unwrap();
+        let loaded: CounterModel = load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.count(), 7);
+    }
+
+    #[test]
+    fn foreign_file_is_rejected() {
+        let bytes = b"PNG\0\x01\0\0\0";
+        assert!(matches!(
+            read::<CounterModel>(&mut &bytes[..]),
+            Err(SnapshotError::File(VersionedFileError::UnknownFormat(_)))
+        ));
+    }
+
+    #[test]
+    fn other_version_is_rejected() {
+        let mut bytes = Vec::new();
+        write(&CounterModel::new(), &mut bytes).unwrap();
+        bytes[4..8].copy_from_slice(&(VERSION + 1).to_le_bytes());
+        assert!(matches!(
+            read::<CounterModel>(&mut &bytes[..]),
+            Err(SnapshotError::File(VersionedFileError::UnsupportedVersion(_, v))) if v == VERSION + 1
+        ));
+    }
+}
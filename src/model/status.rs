@@ -0,0 +1,37 @@
+use crate::model::model_manager::State;
+use std::sync::{Arc, Mutex};
+
+/// Model manager state, published for monitoring, e.g. by debug overlay.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Status {
+    pub state: State,
+    /// Count of model updates done.
+    pub tick: u64,
+    /// Configured count of model updates per second. None means as fast as possible.
+    pub tick_rate: Option<f64>,
+}
+
+/// The latest status of model manager. Cheap to clone and to read from any thread.
+#[derive(Debug, Clone)]
+pub struct StatusWatch(Arc<Mutex<Status>>);
+
+impl Status {
+    /// Model manager doesn't update model by itself, but may make requested steps.
+    pub fn is_paused(&self) -> bool {
+        matches!(self.state, State::Stoped | State::Stepping(_))
+    }
+}
+
+impl StatusWatch {
+    pub fn new(status: Status) -> Self {
+        Self(Arc::new(Mutex::new(status)))
+    }
+
+    pub fn get(&self) -> Status {
+        *self.0.lock().unwrap()
+    }
+
+    pub fn set(&self, status: Status) {
+        *self.0.lock().unwrap() = status;
+    }
+}
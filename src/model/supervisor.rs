@@ -0,0 +1,271 @@
+use crate::model::error::ModelManagerError;
+use crate::model::model_manager::{Command, CommandSender, Notification};
+use slog::{error, info, trace, warn, Logger};
+use std::sync::mpsc::Receiver;
+use std::time::{Duration, Instant};
+
+/// How often good model is copied by default.
+pub const DEFAULT_SNAPSHOT_PERIOD: Duration = Duration::from_secs(1);
+
+/// How supervisor reacts on model manager errors.
+#[derive(Debug, Clone, Copy)]
+pub enum Policy {
+    /// Run model manager again with current model, at most `attempts` times in a row.
+    Retry { attempts: u32 },
+    /// Reset model to last good snapshot and run model manager again, at most `attempts` times in a row.
+    /// Before the first snapshot model is reset to initial one.
+    Restart { attempts: u32 },
+    /// Exit. Error message is kept in `abort_reason`.
+    Abort,
+}
+
+/// Decision made by supervisor about model manager error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    Retry,
+    Restart,
+    Abort,
+}
+
+/// Consumes model manager notifications and recovers model manager according to policy.
+pub struct Supervisor<M> {
+    command_tx: CommandSender<M>,
+    notification_rx: Receiver<Notification>,
+    policy: Policy,
+    failures: u32,
+    /// Last good model, or initial one.
+    snapshot: M,
+    snapshot_period: Duration,
+    snapshot_made_at: Option<Instant>,
+    abort_reason: Option<String>,
+    logger: Logger,
+}
+
+impl<M: Clone> Supervisor<M> {
+    /// `initial` is model, the manager was started with.
+    pub fn new(
+        command_tx: CommandSender<M>,
+        notification_rx: Receiver<Notification>,
+        policy: Policy,
+        initial: M,
+        logger: Logger,
+    ) -> Self {
+        trace!(logger, "Creating supervisor with policy: {:?}", policy);
+        Self {
+            command_tx,
+            notification_rx,
+            policy,
+            failures: 0,
+            snapshot: initial,
+            snapshot_period: DEFAULT_SNAPSHOT_PERIOD,
+            snapshot_made_at: None,
+            abort_reason: None,
+            logger,
+        }
+    }
+
+    /// Sets how often model is copied by `remember`.
+    pub fn with_snapshot_period(mut self, period: Duration) -> Self {
+        self.snapshot_period = period;
+        self
+    }
+
+    /// Remembers copy of model as last good snapshot, if snapshot period is passed since previous one.
+    /// Model manager produced model successfully, so failures counter is reset.
+    pub fn remember(&mut self, model: &M) {
+        let now = Instant::now();
+        if self
+            .snapshot_made_at
+            .map_or(true, |at| now >= at + self.snapshot_period)
+        {
+            self.snapshot = model.clone();
+            self.snapshot_made_at = Some(now);
+        }
+        self.failures = 0;
+    }
+
+    /// Error, model manager was given up with.
+    pub fn abort_reason(&self) -> Option<&str> {
+        self.abort_reason.as_deref()
+    }
+
+    /// Handles all recieved notifications.
+    /// Returns last decision made, or None if there was nothing to handle.
+    pub fn supervise(&mut self) -> Option<Decision> {
+        let notifications: Vec<_> = self.notification_rx.try_iter().collect();
+        let mut decision = None;
+        for notification in notifications {
+            let d = self.handle(notification);
+            if d.is_some() {
+                decision = d;
+            }
+            if d == Some(Decision::Abort) {
+                break;
+            }
+        }
+        decision
+    }
+
+    /// Applies policy to notification. Returns None if no recovery is needed.
+    fn handle(&mut self, notification: Notification) -> Option<Decision> {
+        let e = match notification {
+            Notification::Error(ModelManagerError::UpdateError(e)) => e,
+            Notification::Error(e) => {
+                warn!(self.logger, "Model manager reported error: {}", e);
+                return None;
+            }
+            Notification::InputDropped(count) => {
+                warn!(self.logger, "Model manager dropped {} inputs", count);
+                return None;
+            }
+        };
+        warn!(self.logger, "Model manager stopped with error: {}", e);
+        self.failures += 1;
+
+        let decision = self.decide();
+        info!(
+            self.logger,
+            "Supervisor decision: {:?} (failures in a row: {})", decision, self.failures
+        );
+
+        match decision {
+            Decision::Retry => self.send(Command::Run),
+            Decision::Restart => {
+                self.send(Command::Reset(self.snapshot.clone()));
+                self.send(Command::Run);
+            }
+            Decision::Abort => {
+                let message = format!("Model manager failed and can't be recovered: {}", e);
+                error!(self.logger, "{}", message);
+                self.abort_reason = Some(message);
+                self.send(Command::Exit);
+            }
+        }
+        Some(decision)
+    }
+
+    /// Chooses what to do with failed model manager.
+    fn decide(&self) -> Decision {
+        match self.policy {
+            Policy::Retry { attempts } if self.failures <= attempts => Decision::Retry,
+            Policy::Restart { attempts } if self.failures <= attempts => Decision::Restart,
+            _ => Decision::Abort,
+        }
+    }
+
+    fn send(&self, command: Command<M>) {
+        trace!(self.logger, "Sending command: {:?}", command);
+        self.command_tx.send(command).unwrap_or_else(|e| {
+            error!(self.logger, "Can't send command to model manager: {}", e);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::counter_model::CounterModel;
+    use crate::model::error::update::UpdateError;
+    use crate::model::Model;
+    use slog::{o, Discard};
+    use std::sync::mpsc::{channel, Sender};
+
+    fn supervisor(
+        policy: Policy,
+    ) -> (
+        Supervisor<CounterModel>,
+        Sender<Notification>,
+        Receiver<Command<CounterModel>>,
+    ) {
+        let (command_tx, command_rx) = CommandSender::detached();
+        let (notification_tx, notification_rx) = channel();
+        let supervisor = Supervisor::new(
+            command_tx,
+            notification_rx,
+            policy,
+            CounterModel::new(),
+            Logger::root(Discard, o!()),
+        );
+        (supervisor, notification_tx, command_rx)
+    }
+
+    fn fail(notification_tx: &Sender<Notification>) {
+        let e = ModelManagerError::UpdateError(UpdateError::CantSendToPresenter);
+        notification_tx.send(Notification::Error(e)).unwrap();
+    }
+
+    fn counter(count: i32) -> CounterModel {
+        let mut model = CounterModel::new();
+        model.set_variable("counter", &count.to_string()).unwrap();
+        model
+    }
+
+    /// Counts of models, the manager was reset to, and names of other commands.
+    fn sent(command_rx: &Receiver<Command<CounterModel>>) -> Vec<String> {
+        command_rx
+            .try_iter()
+            .map(|command| match command {
+                Command::Reset(model) => format!("Reset({})", model.count()),
+                command => format!("{:?}", command),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn retries_until_attempts_are_exhausted() {
+        let (mut supervisor, notification_tx, command_rx) =
+            supervisor(Policy::Retry { attempts: 2 });
+        fail(&notification_tx);
+        fail(&notification_tx);
+        assert_eq!(supervisor.supervise(), Some(Decision::Retry));
+        assert_eq!(sent(&command_rx), vec!["Run", "Run"]);
+
+        fail(&notification_tx);
+        assert_eq!(supervisor.supervise(), Some(Decision::Abort));
+        assert_eq!(sent(&command_rx), vec!["Exit"]);
+        assert!(supervisor.abort_reason().is_some());
+    }
+
+    #[test]
+    fn good_model_resets_failures() {
+        let (mut supervisor, notification_tx, command_rx) =
+            supervisor(Policy::Retry { attempts: 1 });
+        fail(&notification_tx);
+        assert_eq!(supervisor.supervise(), Some(Decision::Retry));
+        supervisor.remember(&counter(1));
+        fail(&notification_tx);
+        assert_eq!(supervisor.supervise(), Some(Decision::Retry));
+        assert_eq!(sent(&command_rx), vec!["Run", "Run"]);
+    }
+
+    #[test]
+    fn restarts_with_remembered_model() {
+        let (supervisor, notification_tx, command_rx) = supervisor(Policy::Restart { attempts: 1 });
+        let mut supervisor = supervisor.with_snapshot_period(Duration::from_secs(3600));
+        supervisor.remember(&counter(5));
+        // Snapshot period is not passed, so this one is not copied.
+        supervisor.remember(&counter(6));
+        fail(&notification_tx);
+        assert_eq!(supervisor.supervise(), Some(Decision::Restart));
+        assert_eq!(sent(&command_rx), vec!["Reset(5)", "Run"]);
+    }
+
+    #[test]
+    fn restarts_with_initial_model_before_first_snapshot() {
+        let (mut supervisor, notification_tx, command_rx) =
+            supervisor(Policy::Restart { attempts: 1 });
+        fail(&notification_tx);
+        assert_eq!(supervisor.supervise(), Some(Decision::Restart));
+        assert_eq!(sent(&command_rx), vec!["Reset(0)", "Run"]);
+    }
+
+    #[test]
+    fn aborts_at_once_with_abort_policy() {
+        let (mut supervisor, notification_tx, command_rx) = supervisor(Policy::Abort);
+        notification_tx.send(Notification::InputDropped(3)).unwrap();
+        assert_eq!(supervisor.supervise(), None);
+        fail(&notification_tx);
+        assert_eq!(supervisor.supervise(), Some(Decision::Abort));
+        assert_eq!(sent(&command_rx), vec!["Exit"]);
+    }
+}
@@ -0,0 +1,183 @@
+use super::shape::{Aabb, Shape};
+use super::vector::Vec2;
+use crate::ecs::entity::Entity;
+use serde::{Deserialize, Serialize};
+
+/// Handle of body in physics world.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct BodyHandle(pub(super) Entity);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BodyKind {
+    /// Moved by velocity, forces, gravity and collisions.
+    Dynamic,
+    /// Never moves.
+    Static,
+    /// Moved only by its velocity. Pushes dynamic bodies, but is not pushed by them.
+    Kinematic,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Collider {
+    pub shape: Shape,
+    /// Sensor reports collisions, but doesn't push bodies.
+    pub sensor: bool,
+    /// Bounciness from 0 to 1. The bigger one of two colliders is used.
+    pub restitution: f64,
+    /// Not negative. Geometric mean of two colliders is used.
+    pub friction: f64,
+}
+
+impl Collider {
+    pub fn new(shape: Shape) -> Self {
+        Self {
+            shape,
+            sensor: false,
+            restitution: 0.0,
+            friction: 0.5,
+        }
+    }
+
+    pub fn sensor(mut self) -> Self {
+        self.sensor = true;
+        self
+    }
+
+    /// Panics if restitution is not from 0 to 1.
+    pub fn with_restitution(mut self, restitution: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&restitution),
+            "Restitution must be from 0 to 1, but it's {}",
+            restitution
+        );
+        self.restitution = restitution;
+        self
+    }
+
+    /// Panics if friction is negative.
+    pub fn with_friction(mut self, friction: f64) -> Self {
+        assert!(
+            friction >= 0.0,
+            "Friction must not be negative, but it's {}",
+            friction
+        );
+        self.friction = friction;
+        self
+    }
+}
+
+/// Body with one collider. Bodies are not rotated by collisions:
+/// `angle` only turns polygon collider.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RigidBody {
+    pub kind: BodyKind,
+    pub collider: Collider,
+    pub position: Vec2,
+    /// Rotation in radians, counter-clockwise.
+    pub angle: f64,
+    pub velocity: Vec2,
+    pub gravity_scale: f64,
+    /// Part of velocity, lost every second.
+    pub damping: f64,
+    mass: f64,
+    /// Force, applied during current tick.
+    force: Vec2,
+}
+
+impl RigidBody {
+    pub fn new(kind: BodyKind, collider: Collider) -> Self {
+        Self {
+            kind,
+            collider,
+            position: Vec2::ZERO,
+            angle: 0.0,
+            velocity: Vec2::ZERO,
+            gravity_scale: 1.0,
+            damping: 0.0,
+            mass: 1.0,
+            force: Vec2::ZERO,
+        }
+    }
+
+    pub fn dynamic(collider: Collider) -> Self {
+        Self::new(BodyKind::Dynamic, collider)
+    }
+
+    pub fn fixed(collider: Collider) -> Self {
+        Self::new(BodyKind::Static, collider)
+    }
+
+    pub fn kinematic(collider: Collider) -> Self {
+        Self::new(BodyKind::Kinematic, collider)
+    }
+
+    pub fn with_position(mut self, position: Vec2) -> Self {
+        self.position = position;
+        self
+    }
+
+    pub fn with_angle(mut self, angle: f64) -> Self {
+        self.angle = angle;
+        self
+    }
+
+    pub fn with_velocity(mut self, velocity: Vec2) -> Self {
+        self.velocity = velocity;
+        self
+    }
+
+    pub fn with_mass(mut self, mass: f64) -> Self {
+        self.set_mass(mass);
+        self
+    }
+
+    pub fn mass(&self) -> f64 {
+        self.mass
+    }
+
+    /// Panics if mass is not positive.
+    pub fn set_mass(&mut self, mass: f64) {
+        assert!(mass > 0.0, "Mass must be positive, but it's {}", mass);
+        self.mass = mass;
+    }
+
+    /// Zero for bodies, which are not pushed by collisions.
+    pub fn inverse_mass(&self) -> f64 {
+        match self.kind {
+            BodyKind::Dynamic => 1.0 / self.mass,
+            BodyKind::Static | BodyKind::Kinematic => 0.0,
+        }
+    }
+
+    /// Applies force during current tick.
+    pub fn apply_force(&mut self, force: Vec2) {
+        self.force += force;
+    }
+
+    /// Changes velocity of dynamic body at once.
+    pub fn apply_impulse(&mut self, impulse: Vec2) {
+        self.velocity += impulse * self.inverse_mass();
+    }
+
+    pub fn bounds(&self) -> Aabb {
+        self.collider.shape.bounds(self.position, self.angle)
+    }
+
+    /// Moves body by one step of integration.
+    pub(super) fn integrate(&mut self, gravity: Vec2, dt: f64) {
+        match self.kind {
+            BodyKind::Dynamic => {
+                let acceleration = gravity * self.gravity_scale + self.force / self.mass;
+                self.velocity += acceleration * dt;
+                self.velocity = self.velocity / (1.0 + self.damping * dt);
+                self.position += self.velocity * dt;
+            }
+            BodyKind::Kinematic => self.position += self.velocity * dt,
+            BodyKind::Static => {}
+        }
+    }
+
+    pub(super) fn clear_force(&mut self) {
+        self.force = Vec2::ZERO;
+    }
+}
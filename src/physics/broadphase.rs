@@ -0,0 +1,70 @@
+use super::shape::Aabb;
+
+/// Finds pairs of overlapping bounding boxes by sorting them along X axis.
+/// Order is kept between steps, so sorting of slightly moved boxes is fast.
+#[derive(Debug, Clone, Default)]
+pub struct SweepAndPrune {
+    order: Vec<usize>,
+}
+
+impl SweepAndPrune {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns sorted pairs of indices of overlapping boxes, with smaller index first.
+    pub fn pairs(&mut self, bounds: &[Aabb]) -> Vec<(usize, usize)> {
+        if self.order.len() != bounds.len() {
+            self.order = (0..bounds.len()).collect();
+        }
+        // Insertion sort is linear for nearly sorted order.
+        for i in 1..self.order.len() {
+            let mut j = i;
+            while j > 0 && bounds[self.order[j - 1]].min.x > bounds[self.order[j]].min.x {
+                self.order.swap(j - 1, j);
+                j -= 1;
+            }
+        }
+
+        let mut pairs = Vec::new();
+        let mut active: Vec<usize> = Vec::new();
+        for &index in &self.order {
+            let aabb = &bounds[index];
+            active.retain(|other| bounds[*other].max.x >= aabb.min.x);
+            for &other in &active {
+                if bounds[other].overlaps(aabb) {
+                    pairs.push((other.min(index), other.max(index)));
+                }
+            }
+            active.push(index);
+        }
+        pairs.sort_unstable();
+        pairs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::vector::Vec2;
+
+    fn square(x: f64, y: f64) -> Aabb {
+        Aabb::around(Vec2::new(x, y), Vec2::new(0.5, 0.5))
+    }
+
+    #[test]
+    fn finds_overlapping_pairs_after_moves() {
+        let mut broadphase = SweepAndPrune::new();
+        let mut bounds = vec![
+            square(3.0, 0.0),
+            square(0.0, 0.0),
+            square(0.8, 0.0),
+            square(0.5, 5.0),
+        ];
+        assert_eq!(broadphase.pairs(&bounds), vec![(1, 2)]);
+
+        bounds[0] = square(-0.7, 0.0);
+        bounds[3] = square(1.5, 0.4);
+        assert_eq!(broadphase.pairs(&bounds), vec![(0, 1), (1, 2), (2, 3)]);
+    }
+}
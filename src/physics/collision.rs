@@ -0,0 +1,231 @@
+use super::shape::Shape;
+use super::vector::Vec2;
+use serde::{Deserialize, Serialize};
+
+/// Contact of two shapes.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Contact {
+    /// Unit vector from the first shape to the second one.
+    pub normal: Vec2,
+    /// Distance, the shapes must be moved apart along normal to stop touching.
+    pub depth: f64,
+    /// Approximate point of the second shape, which is the deepest inside the first one.
+    pub point: Vec2,
+}
+
+impl Contact {
+    /// The same contact, seen from the second shape.
+    fn flipped(self) -> Self {
+        Self {
+            normal: -self.normal,
+            point: self.point + self.normal * self.depth,
+            ..self
+        }
+    }
+}
+
+/// Finds contact of shape `a` at `position_a` with `angle_a` and shape `b`.
+pub fn collide(
+    a: &Shape,
+    position_a: Vec2,
+    angle_a: f64,
+    b: &Shape,
+    position_b: Vec2,
+    angle_b: f64,
+) -> Option<Contact> {
+    match (a, b) {
+        (Shape::Circle { radius: radius_a }, Shape::Circle { radius: radius_b }) => {
+            circles(position_a, *radius_a, position_b, *radius_b)
+        }
+        (_, Shape::Circle { radius }) => {
+            let vertices = a.world_vertices(position_a, angle_a)?;
+            polygon_circle(&vertices, position_b, *radius)
+        }
+        (Shape::Circle { radius }, _) => {
+            let vertices = b.world_vertices(position_b, angle_b)?;
+            polygon_circle(&vertices, position_a, *radius).map(Contact::flipped)
+        }
+        _ => {
+            let vertices_a = a.world_vertices(position_a, angle_a)?;
+            let vertices_b = b.world_vertices(position_b, angle_b)?;
+            polygons(&vertices_a, &vertices_b)
+        }
+    }
+}
+
+fn circles(center_a: Vec2, radius_a: f64, center_b: Vec2, radius_b: f64) -> Option<Contact> {
+    let offset = center_b - center_a;
+    let distance = offset.length();
+    let depth = radius_a + radius_b - distance;
+    if depth <= 0.0 {
+        return None;
+    }
+    // Concentric circles are pushed apart upwards.
+    let normal = if distance > 0.0 {
+        offset / distance
+    } else {
+        Vec2::new(0.0, 1.0)
+    };
+    Some(Contact {
+        normal,
+        depth,
+        point: center_b - normal * radius_b,
+    })
+}
+
+/// Separating axis test of convex polygon and circle.
+fn polygon_circle(vertices: &[Vec2], center: Vec2, radius: f64) -> Option<Contact> {
+    let closest = vertices.iter().copied().min_by(|a, b| {
+        (*a - center)
+            .length_squared()
+            .total_cmp(&(*b - center).length_squared())
+    })?;
+    let axes = edge_normals(vertices).chain(Some((center - closest).normalized()));
+    let (normal, depth) = min_overlap(axes, |axis| {
+        let (min, max) = project(vertices, axis);
+        let center = center.dot(axis);
+        (min, max, center - radius, center + radius)
+    })?;
+    let normal = oriented(normal, center - centroid(vertices));
+    Some(Contact {
+        normal,
+        depth,
+        point: center - normal * radius,
+    })
+}
+
+/// Separating axis test of two convex polygons.
+fn polygons(vertices_a: &[Vec2], vertices_b: &[Vec2]) -> Option<Contact> {
+    let axes = edge_normals(vertices_a).chain(edge_normals(vertices_b));
+    let (normal, depth) = min_overlap(axes, |axis| {
+        let (min_a, max_a) = project(vertices_a, axis);
+        let (min_b, max_b) = project(vertices_b, axis);
+        (min_a, max_a, min_b, max_b)
+    })?;
+    let normal = oriented(normal, centroid(vertices_b) - centroid(vertices_a));
+    let point = vertices_b
+        .iter()
+        .copied()
+        .min_by(|a, b| a.dot(normal).total_cmp(&b.dot(normal)))?;
+    Some(Contact {
+        normal,
+        depth,
+        point,
+    })
+}
+
+/// Returns axis with the smallest overlap of projections, or None if some axis separates shapes.
+/// `projections` returns projections of both shapes on axis.
+fn min_overlap(
+    axes: impl Iterator<Item = Vec2>,
+    projections: impl Fn(Vec2) -> (f64, f64, f64, f64),
+) -> Option<(Vec2, f64)> {
+    let mut best: Option<(Vec2, f64)> = None;
+    for axis in axes.filter(|axis| *axis != Vec2::ZERO) {
+        let (min_a, max_a, min_b, max_b) = projections(axis);
+        let overlap = max_a.min(max_b) - min_a.max(min_b);
+        if overlap <= 0.0 {
+            return None;
+        }
+        if best.map_or(true, |(_, depth)| overlap < depth) {
+            best = Some((axis, overlap));
+        }
+    }
+    best
+}
+
+fn edge_normals(vertices: &[Vec2]) -> impl Iterator<Item = Vec2> + '_ {
+    (0..vertices.len()).map(move |i| {
+        let edge = vertices[(i + 1) % vertices.len()] - vertices[i];
+        (-edge.perp()).normalized()
+    })
+}
+
+fn project(vertices: &[Vec2], axis: Vec2) -> (f64, f64) {
+    vertices.iter().map(|vertex| vertex.dot(axis)).fold(
+        (f64::INFINITY, f64::NEG_INFINITY),
+        |(min, max), projection| (min.min(projection), max.max(projection)),
+    )
+}
+
+fn centroid(vertices: &[Vec2]) -> Vec2 {
+    vertices
+        .iter()
+        .fold(Vec2::ZERO, |sum, vertex| sum + *vertex)
+        / vertices.len() as f64
+}
+
+/// Returns `normal` or opposite one, whichever looks along `direction`.
+fn oriented(normal: Vec2, direction: Vec2) -> Vec2 {
+    if normal.dot(direction) < 0.0 {
+        -normal
+    } else {
+        normal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(size: f64) -> Shape {
+        let half = size / 2.0;
+        Shape::polygon(vec![
+            Vec2::new(-half, -half),
+            Vec2::new(half, -half),
+            Vec2::new(half, half),
+            Vec2::new(-half, half),
+        ])
+        .unwrap()
+    }
+
+    fn assert_near(actual: Vec2, expected: Vec2) {
+        assert!(
+            (actual - expected).length() < 1e-9,
+            "{:?} is not {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn circles_touch_along_centers() {
+        let circle = Shape::circle(1.0);
+        let contact = collide(&circle, Vec2::ZERO, 0.0, &circle, Vec2::new(1.5, 0.0), 0.0).unwrap();
+        assert_near(contact.normal, Vec2::new(1.0, 0.0));
+        assert!((contact.depth - 0.5).abs() < 1e-9);
+        assert_near(contact.point, Vec2::new(0.5, 0.0));
+        assert!(collide(&circle, Vec2::ZERO, 0.0, &circle, Vec2::new(2.5, 0.0), 0.0).is_none());
+    }
+
+    #[test]
+    fn polygon_and_circle_touch_in_both_orders() {
+        let (polygon, circle) = (square(2.0), Shape::circle(0.5));
+        let position = Vec2::new(0.0, 1.25);
+        let contact = collide(&polygon, Vec2::ZERO, 0.0, &circle, position, 0.0).unwrap();
+        assert_near(contact.normal, Vec2::new(0.0, 1.0));
+        assert!((contact.depth - 0.25).abs() < 1e-9);
+
+        let flipped = collide(&circle, position, 0.0, &polygon, Vec2::ZERO, 0.0).unwrap();
+        assert_near(flipped.normal, Vec2::new(0.0, -1.0));
+        assert!((flipped.depth - 0.25).abs() < 1e-9);
+
+        // Near the corner, but outside of it.
+        let outside = Vec2::new(1.4, 1.4);
+        assert!(collide(&polygon, Vec2::ZERO, 0.0, &circle, outside, 0.0).is_none());
+    }
+
+    #[test]
+    fn polygons_touch_along_least_overlap() {
+        let square = square(2.0);
+        let contact = collide(&square, Vec2::ZERO, 0.0, &square, Vec2::new(1.8, 0.5), 0.0).unwrap();
+        assert_near(contact.normal, Vec2::new(1.0, 0.0));
+        assert!((contact.depth - 0.2).abs() < 1e-9);
+
+        // Rotated square reaches further with its corner.
+        let turned = std::f64::consts::FRAC_PI_4;
+        let position = Vec2::new(2.3, 0.0);
+        assert!(collide(&square, Vec2::ZERO, 0.0, &square, position, 0.0).is_none());
+        assert!(collide(&square, Vec2::ZERO, 0.0, &square, position, turned).is_some());
+    }
+}
@@ -0,0 +1,52 @@
+use super::vector::Vec2;
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, PartialEq)]
+pub enum PolygonError {
+    TooFewVertices(usize),
+    NotConvex,
+}
+
+impl Error for PolygonError {}
+
+impl fmt::Display for PolygonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PolygonError::TooFewVertices(count) => {
+                write!(
+                    f,
+                    "Polygon must have at least 3 vertices, but has {}",
+                    count
+                )
+            }
+            PolygonError::NotConvex => write!(f, "Polygon is not convex"),
+        }
+    }
+}
+
+/// Deserialized shape has wrong size.
+#[derive(Debug, PartialEq)]
+pub enum ShapeError {
+    /// Circle radius is negative or NaN.
+    Radius(f64),
+    /// Box half extents are negative or NaN.
+    HalfExtents(Vec2),
+}
+
+impl Error for ShapeError {}
+
+impl fmt::Display for ShapeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShapeError::Radius(radius) => {
+                write!(f, "Circle radius must not be negative, but it's {}", radius)
+            }
+            ShapeError::HalfExtents(half_extents) => write!(
+                f,
+                "Box half extents must not be negative, but they're {:?}",
+                half_extents
+            ),
+        }
+    }
+}
@@ -0,0 +1,10 @@
+//! 2D physics for models: rigid bodies with AABB, circle and polygon colliders,
+//! sweep and prune broadphase, collision events and fixed-step integration.
+
+pub mod body;
+pub mod broadphase;
+pub mod collision;
+pub mod error;
+pub mod shape;
+pub mod vector;
+pub mod world;
@@ -0,0 +1,283 @@
+use super::error::{PolygonError, ShapeError};
+use super::vector::Vec2;
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::f64::consts::PI;
+
+/// Allowed error of total turning angle of polygon, accumulated from its vertices.
+const TURNING_TOLERANCE: f64 = 1e-6;
+
+/// Axis-aligned bounding box.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Aabb {
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+impl Aabb {
+    pub fn new(min: Vec2, max: Vec2) -> Self {
+        Self { min, max }
+    }
+
+    pub fn around(center: Vec2, half_extents: Vec2) -> Self {
+        Self::new(center - half_extents, center + half_extents)
+    }
+
+    pub fn overlaps(&self, other: &Aabb) -> bool {
+        self.min.x <= other.max.x
+            && other.min.x <= self.max.x
+            && self.min.y <= other.max.y
+            && other.min.y <= self.max.y
+    }
+
+    pub fn contains(&self, point: Vec2) -> bool {
+        self.min.x <= point.x
+            && point.x <= self.max.x
+            && self.min.y <= point.y
+            && point.y <= self.max.y
+    }
+}
+
+/// Geometry of collider in body space. Sizes are checked when made or deserialized.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "UncheckedShape")]
+pub enum Shape {
+    Circle {
+        radius: f64,
+    },
+    /// Box, which stays axis-aligned whatever angle body has.
+    Aabb {
+        half_extents: Vec2,
+    },
+    /// Convex polygon. Made by `Shape::polygon`.
+    Polygon {
+        vertices: Vertices,
+    },
+}
+
+/// Shape as it's deserialized, before its sizes are checked.
+#[derive(Deserialize)]
+enum UncheckedShape {
+    Circle { radius: f64 },
+    Aabb { half_extents: Vec2 },
+    Polygon { vertices: Vertices },
+}
+
+impl TryFrom<UncheckedShape> for Shape {
+    type Error = ShapeError;
+
+    fn try_from(shape: UncheckedShape) -> Result<Self, ShapeError> {
+        match shape {
+            UncheckedShape::Circle { radius } if !is_length(radius) => {
+                Err(ShapeError::Radius(radius))
+            }
+            UncheckedShape::Aabb { half_extents } if !is_size(half_extents) => {
+                Err(ShapeError::HalfExtents(half_extents))
+            }
+            UncheckedShape::Circle { radius } => Ok(Shape::Circle { radius }),
+            UncheckedShape::Aabb { half_extents } => Ok(Shape::Aabb { half_extents }),
+            UncheckedShape::Polygon { vertices } => Ok(Shape::Polygon { vertices }),
+        }
+    }
+}
+
+/// Value is not negative and not NaN.
+fn is_length(value: f64) -> bool {
+    value >= 0.0
+}
+
+fn is_size(size: Vec2) -> bool {
+    is_length(size.x) && is_length(size.y)
+}
+
+/// Vertices of convex polygon in counter-clockwise order.
+/// Checked when made or deserialized, so collision code can rely on them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "Vec<Vec2>", into = "Vec<Vec2>")]
+pub struct Vertices(Vec<Vec2>);
+
+impl Vertices {
+    pub fn as_slice(&self) -> &[Vec2] {
+        &self.0
+    }
+}
+
+impl TryFrom<Vec<Vec2>> for Vertices {
+    type Error = PolygonError;
+
+    /// Vertices may go in any direction.
+    /// Polygon, which turns the same way at every vertex, is convex only if it goes round once:
+    /// e.g. pentagram goes round twice and intersects itself.
+    fn try_from(mut vertices: Vec<Vec2>) -> Result<Self, PolygonError> {
+        if vertices.len() < 3 {
+            return Err(PolygonError::TooFewVertices(vertices.len()));
+        }
+        let edges: Vec<(Vec2, Vec2)> = (0..vertices.len())
+            .map(|i| {
+                let (a, b, c) = (
+                    vertices[i],
+                    vertices[(i + 1) % vertices.len()],
+                    vertices[(i + 2) % vertices.len()],
+                );
+                (b - a, c - b)
+            })
+            .collect();
+        let turns: Vec<f64> = edges.iter().map(|(ab, bc)| ab.cross(*bc)).collect();
+        let total_angle: f64 = edges
+            .iter()
+            .map(|(ab, bc)| ab.cross(*bc).atan2(ab.dot(*bc)))
+            .sum();
+        let goes_round_once = (total_angle.abs() - 2.0 * PI).abs() < TURNING_TOLERANCE;
+        if !goes_round_once {
+            return Err(PolygonError::NotConvex);
+        }
+        if turns.iter().all(|turn| *turn == 0.0) {
+            return Err(PolygonError::NotConvex);
+        } else if turns.iter().all(|turn| *turn <= 0.0) {
+            vertices.reverse();
+        } else if !turns.iter().all(|turn| *turn >= 0.0) {
+            return Err(PolygonError::NotConvex);
+        }
+        Ok(Self(vertices))
+    }
+}
+
+impl From<Vertices> for Vec<Vec2> {
+    fn from(vertices: Vertices) -> Self {
+        vertices.0
+    }
+}
+
+impl Shape {
+    /// Panics if radius is negative or NaN.
+    pub fn circle(radius: f64) -> Self {
+        assert!(
+            is_length(radius),
+            "Circle radius must not be negative, but it's {}",
+            radius
+        );
+        Shape::Circle { radius }
+    }
+
+    /// Panics if width or height is negative or NaN.
+    pub fn aabb(width: f64, height: f64) -> Self {
+        assert!(
+            is_size(Vec2::new(width, height)),
+            "Box size must not be negative, but it's {}x{}",
+            width,
+            height
+        );
+        Shape::Aabb {
+            half_extents: Vec2::new(width / 2.0, height / 2.0),
+        }
+    }
+
+    /// Convex polygon. Vertices may go in any direction.
+    pub fn polygon(vertices: Vec<Vec2>) -> Result<Self, PolygonError> {
+        Ok(Shape::Polygon {
+            vertices: Vertices::try_from(vertices)?,
+        })
+    }
+
+    /// Bounding box of shape, placed at `position` with `angle`.
+    pub fn bounds(&self, position: Vec2, angle: f64) -> Aabb {
+        match self {
+            Shape::Circle { radius } => Aabb::around(position, Vec2::new(*radius, *radius)),
+            Shape::Aabb { half_extents } => Aabb::around(position, *half_extents),
+            Shape::Polygon { vertices } => {
+                let vertices = vertices.as_slice();
+                let first = position + vertices[0].rotated(angle);
+                vertices
+                    .iter()
+                    .skip(1)
+                    .fold(Aabb::new(first, first), |aabb, vertex| {
+                        let vertex = position + vertex.rotated(angle);
+                        Aabb::new(aabb.min.min(vertex), aabb.max.max(vertex))
+                    })
+            }
+        }
+    }
+
+    /// Vertices of shape, placed at `position` with `angle`, in counter-clockwise order.
+    /// None for circle.
+    pub fn world_vertices(&self, position: Vec2, angle: f64) -> Option<Vec<Vec2>> {
+        match self {
+            Shape::Circle { .. } => None,
+            Shape::Aabb { half_extents } => {
+                let (x, y) = (half_extents.x, half_extents.y);
+                let corners = [(-x, -y), (x, -y), (x, y), (-x, y)];
+                Some(
+                    corners
+                        .iter()
+                        .map(|corner| position + Vec2::from(*corner))
+                        .collect(),
+                )
+            }
+            Shape::Polygon { vertices } => Some(
+                vertices
+                    .as_slice()
+                    .iter()
+                    .map(|vertex| position + vertex.rotated(angle))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Vertices of regular pentagon, counter-clockwise.
+    fn pentagon() -> Vec<Vec2> {
+        (0..5)
+            .map(|i| Vec2::new(1.0, 0.0).rotated(2.0 * PI * i as f64 / 5.0))
+            .collect()
+    }
+
+    #[test]
+    fn convex_polygon_goes_counter_clockwise() {
+        let mut clockwise = pentagon();
+        clockwise.reverse();
+        let vertices = Vertices::try_from(clockwise).unwrap();
+        assert_eq!(vertices.as_slice(), pentagon().as_slice());
+    }
+
+    #[test]
+    fn self_intersecting_polygon_is_rejected() {
+        let pentagon = pentagon();
+        let pentagram: Vec<Vec2> = [0, 2, 4, 1, 3].iter().map(|i| pentagon[*i]).collect();
+        assert_eq!(Vertices::try_from(pentagram), Err(PolygonError::NotConvex));
+        let bent = vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(1.0, 0.0),
+            Vec2::new(0.0, 1.0),
+            Vec2::new(1.0, 1.0),
+        ];
+        assert_eq!(Vertices::try_from(bent), Err(PolygonError::NotConvex));
+    }
+
+    #[test]
+    fn deserialized_sizes_are_checked() {
+        let load = |shape: &Shape| bincode::deserialize::<Shape>(&bincode::serialize(shape)?);
+        let circle = Shape::circle(0.5);
+        assert_eq!(load(&circle).unwrap(), circle);
+        for radius in &[-1.0, f64::NAN] {
+            assert!(load(&Shape::Circle { radius: *radius }).is_err());
+        }
+        let half_extents = Vec2::new(1.0, -1.0);
+        assert!(load(&Shape::Aabb { half_extents }).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "Circle radius must not be negative")]
+    fn negative_radius_panics() {
+        Shape::circle(-1.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Box size must not be negative")]
+    fn nan_box_size_panics() {
+        Shape::aabb(1.0, f64::NAN);
+    }
+}
@@ -0,0 +1,379 @@
+use super::body::{BodyHandle, BodyKind, RigidBody};
+use super::broadphase::SweepAndPrune;
+use super::collision::{collide, Contact};
+use super::shape::Aabb;
+use super::vector::Vec2;
+use crate::ecs::entity::Entities;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Times velocities of touching bodies are corrected per step. More is more stable stacking.
+const SOLVER_ITERATIONS: u32 = 8;
+/// Penetration, which is allowed to stay, so resting bodies don't jitter.
+const PENETRATION_SLOP: f64 = 0.01;
+/// Part of penetration, removed per step.
+const POSITION_CORRECTION: f64 = 0.8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum CollisionEvent {
+    /// Bodies started touching. Contact normal points from `a` to `b`.
+    Started {
+        a: BodyHandle,
+        b: BodyHandle,
+        contact: Contact,
+    },
+    Ended {
+        a: BodyHandle,
+        b: BodyHandle,
+    },
+}
+
+/// Bodies, which move and collide with fixed time step.
+/// Call `tick` once in every `Model::update` and `set_tick_rate` in `Model::set_tick_rate`,
+/// so simulation runs in real time. Timestep is saved with world, so history replay
+/// gives the same result.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PhysicsWorld {
+    pub gravity: Vec2,
+    timestep: f64,
+    substeps: u32,
+    handles: Entities,
+    bodies: Vec<Option<(BodyHandle, RigidBody)>>,
+    /// Pairs of bodies, which touched after last step, with smaller handle first.
+    touching: BTreeSet<(BodyHandle, BodyHandle)>,
+    events: Vec<CollisionEvent>,
+    #[serde(skip)]
+    broadphase: SweepAndPrune,
+}
+
+impl PhysicsWorld {
+    /// World without gravity, advanced by `timestep` seconds every tick.
+    pub fn new(timestep: f64) -> Self {
+        Self {
+            gravity: Vec2::ZERO,
+            timestep,
+            substeps: 1,
+            handles: Entities::new(),
+            bodies: Vec::new(),
+            touching: BTreeSet::new(),
+            events: Vec::new(),
+            broadphase: SweepAndPrune::new(),
+        }
+    }
+
+    /// World, which runs in real time with model manager ticking `tick_rate` times per second.
+    pub fn for_tick_rate(tick_rate: f64) -> Self {
+        Self::new(1.0 / tick_rate)
+    }
+
+    /// Makes every tick advance simulation by tick period of model manager.
+    pub fn set_tick_rate(&mut self, tick_rate: f64) {
+        self.timestep = 1.0 / tick_rate;
+    }
+
+    pub fn with_gravity(mut self, gravity: Vec2) -> Self {
+        self.gravity = gravity;
+        self
+    }
+
+    /// Splits every tick into `substeps` steps, so fast bodies don't pass through thin ones.
+    pub fn with_substeps(mut self, substeps: u32) -> Self {
+        self.substeps = substeps.max(1);
+        self
+    }
+
+    pub fn timestep(&self) -> f64 {
+        self.timestep
+    }
+
+    pub fn add(&mut self, body: RigidBody) -> BodyHandle {
+        let handle = BodyHandle(self.handles.create());
+        let index = handle.0.index();
+        if index >= self.bodies.len() {
+            self.bodies.resize_with(index + 1, || None);
+        }
+        self.bodies[index] = Some((handle, body));
+        handle
+    }
+
+    /// Removes body. Its touches end with events.
+    pub fn remove(&mut self, handle: BodyHandle) -> Option<RigidBody> {
+        if !self.handles.destroy(handle.0) {
+            return None;
+        }
+        let ended: Vec<_> = self
+            .touching
+            .iter()
+            .filter(|(a, b)| *a == handle || *b == handle)
+            .copied()
+            .collect();
+        for (a, b) in ended {
+            self.touching.remove(&(a, b));
+            self.events.push(CollisionEvent::Ended { a, b });
+        }
+        self.bodies[handle.0.index()].take().map(|(_, body)| body)
+    }
+
+    pub fn get(&self, handle: BodyHandle) -> Option<&RigidBody> {
+        match self.bodies.get(handle.0.index()) {
+            Some(Some((owner, body))) if *owner == handle => Some(body),
+            _ => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, handle: BodyHandle) -> Option<&mut RigidBody> {
+        match self.bodies.get_mut(handle.0.index()) {
+            Some(Some((owner, body))) if *owner == handle => Some(body),
+            _ => None,
+        }
+    }
+
+    pub fn bodies(&self) -> impl Iterator<Item = (BodyHandle, &RigidBody)> {
+        self.bodies
+            .iter()
+            .flatten()
+            .map(|(handle, body)| (*handle, body))
+    }
+
+    pub fn len(&self) -> usize {
+        self.handles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.handles.is_empty()
+    }
+
+    /// Bodies, which bounding boxes overlap `area`.
+    pub fn query(&self, area: &Aabb) -> Vec<BodyHandle> {
+        self.bodies()
+            .filter(|(_, body)| body.bounds().overlaps(area))
+            .map(|(handle, _)| handle)
+            .collect()
+    }
+
+    /// Whether bodies touched after last step.
+    pub fn are_touching(&self, a: BodyHandle, b: BodyHandle) -> bool {
+        self.touching.contains(&(a.min(b), a.max(b)))
+    }
+
+    /// Returns collision events, happened since previous call.
+    pub fn take_events(&mut self) -> Vec<CollisionEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Advances simulation by one timestep. Forces, applied during tick, are cleared after it.
+    pub fn tick(&mut self) {
+        let dt = self.timestep / self.substeps as f64;
+        for _ in 0..self.substeps {
+            self.step(dt);
+        }
+        for (_, body) in self.bodies.iter_mut().flatten() {
+            body.clear_force();
+        }
+    }
+
+    fn step(&mut self, dt: f64) {
+        let gravity = self.gravity;
+        for (_, body) in self.bodies.iter_mut().flatten() {
+            body.integrate(gravity, dt);
+        }
+
+        let contacts = self.find_contacts();
+        let solid: Vec<&(usize, usize, Contact)> = contacts
+            .iter()
+            .filter(|(a, b, _)| !self.body(*a).collider.sensor && !self.body(*b).collider.sensor)
+            .collect();
+        for _ in 0..SOLVER_ITERATIONS {
+            for (a, b, contact) in &solid {
+                let (a, b) = pair_mut(&mut self.bodies, *a, *b);
+                resolve_velocity(a, b, contact);
+            }
+        }
+        for (a, b, contact) in &solid {
+            let (a, b) = pair_mut(&mut self.bodies, *a, *b);
+            correct_position(a, b, contact);
+        }
+
+        self.update_touching(&contacts);
+    }
+
+    /// Contacts of bodies as their indices, with smaller index first.
+    fn find_contacts(&mut self) -> Vec<(usize, usize, Contact)> {
+        let indices: Vec<usize> = (0..self.bodies.len())
+            .filter(|index| self.bodies[*index].is_some())
+            .collect();
+        let bounds: Vec<Aabb> = indices
+            .iter()
+            .map(|index| self.body(*index).bounds())
+            .collect();
+        self.broadphase
+            .pairs(&bounds)
+            .into_iter()
+            .filter_map(|(a, b)| {
+                let (a, b) = (indices[a].min(indices[b]), indices[a].max(indices[b]));
+                let (body_a, body_b) = (self.body(a), self.body(b));
+                // Solid bodies, which are not pushed, don't need contact. Sensors report any body.
+                let solid = !body_a.collider.sensor && !body_b.collider.sensor;
+                if solid && body_a.kind != BodyKind::Dynamic && body_b.kind != BodyKind::Dynamic {
+                    return None;
+                }
+                let contact = collide(
+                    &body_a.collider.shape,
+                    body_a.position,
+                    body_a.angle,
+                    &body_b.collider.shape,
+                    body_b.position,
+                    body_b.angle,
+                )?;
+                Some((a, b, contact))
+            })
+            .collect()
+    }
+
+    /// Reports touches, which started or ended.
+    fn update_touching(&mut self, contacts: &[(usize, usize, Contact)]) {
+        let mut touching = BTreeSet::new();
+        for (a, b, contact) in contacts {
+            let (a, b) = (self.handle(*a), self.handle(*b));
+            touching.insert((a, b));
+            if !self.touching.contains(&(a, b)) {
+                self.events.push(CollisionEvent::Started {
+                    a,
+                    b,
+                    contact: *contact,
+                });
+            }
+        }
+        for (a, b) in &self.touching {
+            if !touching.contains(&(*a, *b)) {
+                self.events.push(CollisionEvent::Ended { a: *a, b: *b });
+            }
+        }
+        self.touching = touching;
+    }
+
+    fn body(&self, index: usize) -> &RigidBody {
+        &self.bodies[index].as_ref().unwrap().1
+    }
+
+    fn handle(&self, index: usize) -> BodyHandle {
+        self.bodies[index].as_ref().unwrap().0
+    }
+}
+
+/// Mutable bodies at different indices `a < b`.
+fn pair_mut(
+    bodies: &mut [Option<(BodyHandle, RigidBody)>],
+    a: usize,
+    b: usize,
+) -> (&mut RigidBody, &mut RigidBody) {
+    let (left, right) = bodies.split_at_mut(b);
+    (
+        &mut left[a].as_mut().unwrap().1,
+        &mut right[0].as_mut().unwrap().1,
+    )
+}
+
+/// Applies impulses, which stop bodies from moving into each other, with bounce and friction.
+fn resolve_velocity(a: &mut RigidBody, b: &mut RigidBody, contact: &Contact) {
+    let (inverse_a, inverse_b) = (a.inverse_mass(), b.inverse_mass());
+    let inverse_sum = inverse_a + inverse_b;
+    if inverse_sum == 0.0 {
+        return;
+    }
+    let normal = contact.normal;
+    let relative = b.velocity - a.velocity;
+    let approach = relative.dot(normal);
+    if approach > 0.0 {
+        return;
+    }
+    // Fields are public, so values, which builders reject, may still get here.
+    let restitution = a
+        .collider
+        .restitution
+        .max(b.collider.restitution)
+        .clamp(0.0, 1.0);
+    let impulse = -(1.0 + restitution) * approach / inverse_sum;
+    a.velocity -= normal * (impulse * inverse_a);
+    b.velocity += normal * (impulse * inverse_b);
+
+    let relative = b.velocity - a.velocity;
+    let tangent = (relative - normal * relative.dot(normal)).normalized();
+    let friction = (a.collider.friction.max(0.0) * b.collider.friction.max(0.0)).sqrt();
+    let limit = impulse * friction;
+    let tangent_impulse = (-relative.dot(tangent) / inverse_sum).clamp(-limit, limit);
+    a.velocity -= tangent * (tangent_impulse * inverse_a);
+    b.velocity += tangent * (tangent_impulse * inverse_b);
+}
+
+/// Moves bodies apart, so penetration doesn't accumulate.
+fn correct_position(a: &mut RigidBody, b: &mut RigidBody, contact: &Contact) {
+    let (inverse_a, inverse_b) = (a.inverse_mass(), b.inverse_mass());
+    let inverse_sum = inverse_a + inverse_b;
+    if inverse_sum == 0.0 {
+        return;
+    }
+    let depth = (contact.depth - PENETRATION_SLOP).max(0.0);
+    let correction = contact.normal * (depth * POSITION_CORRECTION / inverse_sum);
+    a.position -= correction * inverse_a;
+    b.position += correction * inverse_b;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::body::Collider;
+    use crate::physics::shape::Shape;
+
+    #[test]
+    fn touches_start_and_end_with_events() {
+        let mut world = PhysicsWorld::new(0.1);
+        let wall = world.add(RigidBody::fixed(Collider::new(Shape::aabb(1.0, 10.0))));
+        let ball = world.add(
+            RigidBody::dynamic(Collider::new(Shape::circle(0.5)).sensor())
+                .with_position(Vec2::new(-2.5, 0.0))
+                .with_velocity(Vec2::new(10.0, 0.0)),
+        );
+        world.tick();
+        assert!(world.take_events().is_empty());
+
+        world.tick();
+        assert!(world.are_touching(wall, ball));
+        match world.take_events()[..] {
+            [CollisionEvent::Started { a, b, contact }] => {
+                assert_eq!((a, b), (wall, ball));
+                assert!(contact.normal.x < 0.0);
+            }
+            ref events => panic!("Unexpected events {:?}", events),
+        }
+
+        world.tick();
+        assert!(world.take_events().is_empty());
+        world.tick();
+        assert_eq!(
+            world.take_events(),
+            vec![CollisionEvent::Ended { a: wall, b: ball }]
+        );
+    }
+
+    #[test]
+    fn static_sensor_detects_kinematic_body() {
+        let mut world = PhysicsWorld::new(0.1);
+        let sensor = world.add(RigidBody::fixed(
+            Collider::new(Shape::aabb(2.0, 2.0)).sensor(),
+        ));
+        let platform = world.add(
+            RigidBody::kinematic(Collider::new(Shape::aabb(1.0, 1.0)))
+                .with_position(Vec2::new(-2.0, 0.0))
+                .with_velocity(Vec2::new(10.0, 0.0)),
+        );
+        let wall = world.add(
+            RigidBody::fixed(Collider::new(Shape::aabb(1.0, 1.0)))
+                .with_position(Vec2::new(-1.0, 0.0)),
+        );
+        world.tick();
+        assert!(world.are_touching(sensor, platform));
+        // Solid bodies, which are not pushed, still don't touch.
+        assert!(!world.are_touching(platform, wall));
+    }
+}
@@ -1 +1,3 @@
-
+/// Presenter doesn't draw debug UI draw lists yet, so debug UI widgets must not take pointer input.
+/// Debug UI text is shown in the main window title instead.
+pub const DRAWS_DEBUG_UI: bool = false;
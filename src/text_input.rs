@@ -0,0 +1,224 @@
+use crate::input::{Input, InputEvent};
+use crate::model::request::Request;
+use copypasta::{ClipboardContext, ClipboardProvider};
+use slog::{info, warn, Logger};
+use winit::event::{ElementState, Event, ModifiersState, VirtualKeyCode, WindowEvent};
+
+/// Control character, typed along with paste shortcut.
+const PASTE_CHARACTER: char = '\u{16}';
+
+/// How window event is handled by text input.
+#[derive(Debug)]
+pub enum Handled {
+    /// Event is not part of paste shortcut and goes to model as usual.
+    Ignored,
+    /// Event is part of paste shortcut, which triggered paste. It must not go to model.
+    /// Paste input must be sent instead, if any.
+    Shortcut(Option<Input>),
+}
+
+/// Turns typed characters into text and handles clipboard paste, while text input mode is on.
+pub struct TextInput {
+    enabled: bool,
+    modifiers: ModifiersState,
+    /// Paste shortcut key triggered paste and is not released yet.
+    pasting: bool,
+    clipboard: Option<ClipboardContext>,
+    logger: Logger,
+}
+
+impl TextInput {
+    pub fn new(logger: Logger) -> Self {
+        Self {
+            enabled: false,
+            modifiers: ModifiersState::empty(),
+            pasting: false,
+            clipboard: None,
+            logger,
+        }
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        info!(self.logger, "Text input mode: {}", enabled);
+        self.enabled = enabled;
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Applies text input request. Returns false for other requests, which are applied to window.
+    pub fn apply(&mut self, request: &Request) -> bool {
+        match request {
+            Request::TextInput(enabled) => {
+                self.set_enabled(*enabled);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Tracks modifiers and returns paste input, if paste shortcut was pressed in text input mode.
+    /// Key press, character and key release of shortcut, which triggered paste, are not forwarded.
+    pub fn handle_event<T>(&mut self, event: &Event<T>) -> Handled {
+        let (input, window_id) = match event {
+            Event::WindowEvent {
+                event: WindowEvent::ModifiersChanged(modifiers),
+                ..
+            } => {
+                self.modifiers = *modifiers;
+                return Handled::Ignored;
+            }
+            Event::WindowEvent {
+                event: WindowEvent::ReceivedCharacter(PASTE_CHARACTER),
+                ..
+            } if self.pasting => return Handled::Shortcut(None),
+            Event::WindowEvent {
+                event: WindowEvent::KeyboardInput { input, .. },
+                window_id,
+            } if input.virtual_keycode == Some(VirtualKeyCode::V) => (input, *window_id),
+            _ => return Handled::Ignored,
+        };
+        if input.state == ElementState::Released {
+            let pasted = std::mem::replace(&mut self.pasting, false);
+            return if pasted {
+                Handled::Shortcut(None)
+            } else {
+                Handled::Ignored
+            };
+        }
+        if self.pasting {
+            // Key repeat of shortcut.
+            return Handled::Shortcut(None);
+        }
+        let paste = self.enabled && (self.modifiers.ctrl() || self.modifiers.logo());
+        if !paste {
+            return Handled::Ignored;
+        }
+        match self.clipboard_text() {
+            Some(text) => {
+                self.pasting = true;
+                let paste: Input = (None, InputEvent::Paste(text)).into();
+                Handled::Shortcut(Some(paste.in_window(Some(window_id))))
+            }
+            None => Handled::Ignored,
+        }
+    }
+
+    /// Converts typed symbols into text in text input mode. Control characters stay symbols.
+    pub fn convert(&self, input: Input) -> Input {
+        match input.event() {
+            InputEvent::Symbol(c) if self.enabled && !c.is_control() => {
+                input.with_event(InputEvent::Text(c.to_string()))
+            }
+            _ => input,
+        }
+    }
+
+    fn clipboard_text(&mut self) -> Option<String> {
+        if self.clipboard.is_none() {
+            match ClipboardContext::new() {
+                Ok(clipboard) => self.clipboard = Some(clipboard),
+                Err(e) => {
+                    warn!(self.logger, "Can't access clipboard: {}", e);
+                    return None;
+                }
+            }
+        }
+        let clipboard = self.clipboard.as_mut()?;
+        clipboard
+            .get_contents()
+            .map_err(|e| warn!(self.logger, "Can't read clipboard: {}", e))
+            .ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use slog::{o, Discard};
+    use winit::event::{DeviceId, KeyboardInput};
+    use winit::window::WindowId;
+
+    fn text_input(enabled: bool) -> TextInput {
+        let mut text_input = TextInput::new(Logger::root(Discard, o!()));
+        text_input.set_enabled(enabled);
+        text_input
+    }
+
+    fn converted(text_input: &TextInput, c: char) -> InputEvent {
+        let symbol: Input = (None, InputEvent::Symbol(c)).into();
+        text_input.convert(symbol).event().clone()
+    }
+
+    #[test]
+    fn typed_characters_become_text() {
+        let text_input = text_input(true);
+        match converted(&text_input, 'ж') {
+            InputEvent::Text(text) => assert_eq!(text, "ж"),
+            event => panic!("unexpected event {:?}", event),
+        }
+    }
+
+    #[test]
+    fn control_characters_stay_symbols() {
+        let text_input = text_input(true);
+        for c in ['\u{8}', '\r', '\t', '\u{1b}', '\u{7f}', PASTE_CHARACTER] {
+            match converted(&text_input, c) {
+                InputEvent::Symbol(symbol) => assert_eq!(symbol, c),
+                event => panic!("unexpected event {:?}", event),
+            }
+        }
+    }
+
+    #[test]
+    fn text_mode_is_toggled_by_request() {
+        let mut text_input = text_input(false);
+        assert!(matches!(
+            converted(&text_input, 'a'),
+            InputEvent::Symbol('a')
+        ));
+
+        assert!(text_input.apply(&Request::TextInput(true)));
+        assert!(text_input.enabled());
+        assert!(matches!(converted(&text_input, 'a'), InputEvent::Text(_)));
+
+        assert!(text_input.apply(&Request::TextInput(false)));
+        assert!(!text_input.enabled());
+        assert!(!text_input.apply(&Request::GrabCursor(true)));
+    }
+
+    #[test]
+    fn paste_shortcut_is_ignored_out_of_text_mode() {
+        let mut text_input = text_input(false);
+        // SAFETY: dummy ids are only compared with themselves.
+        let (window_id, device_id) = unsafe { (WindowId::dummy(), DeviceId::dummy()) };
+        let modifiers = ModifiersState::CTRL;
+        let changed: Event<()> = Event::WindowEvent {
+            window_id,
+            event: WindowEvent::ModifiersChanged(modifiers),
+        };
+        assert!(matches!(
+            text_input.handle_event(&changed),
+            Handled::Ignored
+        ));
+        #[allow(deprecated)]
+        let pressed: Event<()> = Event::WindowEvent {
+            window_id,
+            event: WindowEvent::KeyboardInput {
+                device_id,
+                input: KeyboardInput {
+                    scancode: 0,
+                    state: ElementState::Pressed,
+                    virtual_keycode: Some(VirtualKeyCode::V),
+                    modifiers,
+                },
+                is_synthetic: false,
+            },
+        };
+        assert!(matches!(
+            text_input.handle_event(&pressed),
+            Handled::Ignored
+        ));
+    }
+}
@@ -0,0 +1,162 @@
+use crate::input::{Input, InputEvent};
+use winit::event::{ElementState, MouseButton, TouchPhase};
+
+/// Emulates left mouse button and cursor with primary touch,
+/// so models, that know nothing about touch, work on touchscreens.
+#[derive(Debug, Default)]
+pub struct TouchEmulation {
+    /// The first finger, that touched screen while no other fingers did.
+    primary: Option<u64>,
+}
+
+impl TouchEmulation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns mouse inputs emulated by touch input. Touch input itself is not consumed.
+    pub fn emulate(&mut self, input: &Input) -> Vec<Input> {
+        let (phase, finger, position) = match input.event() {
+            InputEvent::Touch {
+                phase,
+                finger,
+                position,
+                ..
+            } => (*phase, *finger, *position),
+            _ => return Vec::new(),
+        };
+
+        match self.primary {
+            Some(primary) if primary != finger => return Vec::new(),
+            None if phase != TouchPhase::Started => return Vec::new(),
+            _ => self.primary = Some(finger),
+        }
+
+        let cursor = input.with_event(InputEvent::CursorMove { position });
+        match phase {
+            TouchPhase::Started => vec![cursor, input.with_event(left(ElementState::Pressed))],
+            TouchPhase::Moved => vec![cursor],
+            TouchPhase::Ended | TouchPhase::Cancelled => {
+                self.primary = None;
+                vec![cursor, input.with_event(left(ElementState::Released))]
+            }
+        }
+    }
+}
+
+fn left(state: ElementState) -> InputEvent {
+    InputEvent::MouseButton {
+        button: MouseButton::Left,
+        state,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+    use winit::dpi::PhysicalPosition;
+    use winit::event::{DeviceId, Event, Force, Touch, WindowEvent};
+    use winit::window::WindowId;
+
+    /// Input, converted from synthesized window touch event.
+    fn touch(phase: TouchPhase, id: u64, position: (f64, f64)) -> Input {
+        // SAFETY: dummy ids are only compared with themselves.
+        let (window_id, device_id) = unsafe { (WindowId::dummy(), DeviceId::dummy()) };
+        let event: Event<()> = Event::WindowEvent {
+            window_id,
+            event: WindowEvent::Touch(Touch {
+                device_id,
+                phase,
+                location: PhysicalPosition::new(position.0, position.1),
+                force: Some(Force::Normalized(0.5)),
+                id,
+            }),
+        };
+        Input::try_from(&event).unwrap()
+    }
+
+    fn cursor(input: &Input) -> (f64, f64) {
+        match input.event() {
+            InputEvent::CursorMove { position } => *position,
+            event => panic!("unexpected event {:?}", event),
+        }
+    }
+
+    fn button(input: &Input) -> ElementState {
+        match input.event() {
+            InputEvent::MouseButton {
+                button: MouseButton::Left,
+                state,
+            } => *state,
+            event => panic!("unexpected event {:?}", event),
+        }
+    }
+
+    #[test]
+    fn touch_keeps_finger_and_phase() {
+        let input = touch(TouchPhase::Moved, 7, (1.0, 2.0));
+        match input.event() {
+            InputEvent::Touch {
+                phase,
+                finger,
+                position,
+                pressure,
+                ..
+            } => {
+                assert_eq!(*phase, TouchPhase::Moved);
+                assert_eq!(*finger, 7);
+                assert_eq!(*position, (1.0, 2.0));
+                assert_eq!(*pressure, Some(0.5));
+            }
+            event => panic!("unexpected event {:?}", event),
+        }
+        assert!(input.window_id().is_some());
+    }
+
+    #[test]
+    fn primary_touch_drags_left_button() {
+        let mut emulation = TouchEmulation::new();
+        let started = emulation.emulate(&touch(TouchPhase::Started, 3, (1.0, 1.0)));
+        assert_eq!(started.len(), 2);
+        assert_eq!(cursor(&started[0]), (1.0, 1.0));
+        assert_eq!(button(&started[1]), ElementState::Pressed);
+
+        let moved = emulation.emulate(&touch(TouchPhase::Moved, 3, (4.0, 5.0)));
+        assert_eq!(moved.len(), 1);
+        assert_eq!(cursor(&moved[0]), (4.0, 5.0));
+
+        let ended = emulation.emulate(&touch(TouchPhase::Ended, 3, (4.0, 6.0)));
+        assert_eq!(ended.len(), 2);
+        assert_eq!(cursor(&ended[0]), (4.0, 6.0));
+        assert_eq!(button(&ended[1]), ElementState::Released);
+    }
+
+    #[test]
+    fn other_fingers_are_not_emulated() {
+        let mut emulation = TouchEmulation::new();
+        emulation.emulate(&touch(TouchPhase::Started, 1, (0.0, 0.0)));
+        assert!(emulation
+            .emulate(&touch(TouchPhase::Started, 2, (9.0, 9.0)))
+            .is_empty());
+        assert!(emulation
+            .emulate(&touch(TouchPhase::Moved, 2, (8.0, 8.0)))
+            .is_empty());
+
+        // Cancelled touch releases button, so the next finger becomes primary.
+        let cancelled = emulation.emulate(&touch(TouchPhase::Cancelled, 1, (0.0, 0.0)));
+        assert_eq!(button(&cancelled[1]), ElementState::Released);
+        assert!(emulation
+            .emulate(&touch(TouchPhase::Moved, 2, (7.0, 7.0)))
+            .is_empty());
+        let started = emulation.emulate(&touch(TouchPhase::Started, 4, (5.0, 5.0)));
+        assert_eq!(button(&started[1]), ElementState::Pressed);
+    }
+
+    #[test]
+    fn other_input_is_not_emulated() {
+        let mut emulation = TouchEmulation::new();
+        let key: Input = (None, InputEvent::Symbol('a')).into();
+        assert!(emulation.emulate(&key).is_empty());
+    }
+}
@@ -9,7 +9,7 @@ pub fn wait_for(
 ) -> bool {
     let timeout = match timeout {
         Some(t) => t,
-        None => Duration::from_secs(u64::max_value()),
+        None => Duration::from_secs(u64::MAX),
     };
     let deadline = Instant::now() + timeout;
     loop {
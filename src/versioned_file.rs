@@ -0,0 +1,114 @@
+use crate::error::versioned_file::VersionedFileError;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+/// Binary file format: magic bytes and version header, followed by bincode encoded value.
+#[derive(Debug, Clone, Copy)]
+pub struct FileFormat {
+    /// What files of format keep, used in error messages.
+    pub name: &'static str,
+    /// Bytes every file of format starts with.
+    pub magic: &'static [u8; 4],
+    /// Increase it on every incompatible change.
+    pub version: u32,
+}
+
+impl FileFormat {
+    /// Writes value to file at `path`, prefixed with format header.
+    pub fn save<T: Serialize>(&self, value: &T, path: &Path) -> Result<(), VersionedFileError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write(value, &mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Reads value from file at `path`, checking format header.
+    pub fn load<T: DeserializeOwned>(&self, path: &Path) -> Result<T, VersionedFileError> {
+        let mut reader = BufReader::new(File::open(path)?);
+        self.read(&mut reader)
+    }
+
+    /// Writes format header and value to `writer`.
+    pub fn write<T: Serialize>(
+        &self,
+        value: &T,
+        writer: &mut impl Write,
+    ) -> Result<(), VersionedFileError> {
+        writer.write_all(self.magic)?;
+        writer.write_all(&self.version.to_le_bytes())?;
+        bincode::serialize_into(writer, value)?;
+        Ok(())
+    }
+
+    /// Reads value from `reader`, checking format header.
+    pub fn read<T: DeserializeOwned>(
+        &self,
+        reader: &mut impl Read,
+    ) -> Result<T, VersionedFileError> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != self.magic {
+            return Err(VersionedFileError::UnknownFormat(self.name));
+        }
+
+        let mut version = [0u8; 4];
+        reader.read_exact(&mut version)?;
+        let version = u32::from_le_bytes(version);
+        if version != self.version {
+            return Err(VersionedFileError::UnsupportedVersion(self.name, version));
+        }
+
+        Ok(bincode::deserialize_from(reader)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FORMAT: FileFormat = FileFormat {
+        name: "test file",
+        magic: b"TEST",
+        version: 3,
+    };
+
+    #[test]
+    fn value_is_read_back() {
+        let mut bytes = Vec::new();
+        FORMAT
+            .write(&(7u32, String::from("seven")), &mut bytes)
+            .unwrap();
+        assert_eq!(&bytes[..4], b"TEST");
+        let value: (u32, String) = FORMAT.read(&mut &bytes[..]).unwrap();
+        assert_eq!(value, (7, String::from("seven")));
+    }
+
+    #[test]
+    fn other_format_and_version_are_rejected() {
+        let mut bytes = Vec::new();
+        FORMAT.write(&1u8, &mut bytes).unwrap();
+        let other = FileFormat {
+            magic: b"ELSE",
+            ..FORMAT
+        };
+        assert!(matches!(
+            other.read::<u8>(&mut &bytes[..]),
+            Err(VersionedFileError::UnknownFormat("test file"))
+        ));
+        let newer = FileFormat {
+            version: 4,
+            ..FORMAT
+        };
+        assert!(matches!(
+            newer.read::<u8>(&mut &bytes[..]),
+            Err(VersionedFileError::UnsupportedVersion(_, 3))
+        ));
+        assert_eq!(
+            newer.read::<u8>(&mut &bytes[..]).unwrap_err().to_string(),
+            "Unsupported test file version: 3"
+        );
+    }
+}
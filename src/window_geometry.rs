@@ -0,0 +1,224 @@
+use crate::error::settings::SettingsError;
+use crate::versioned_file::FileFormat;
+use serde::{Deserialize, Serialize};
+use slog::{info, Logger};
+use std::path::Path;
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::error::OsError;
+use winit::event_loop::EventLoop;
+use winit::monitor::MonitorHandle;
+use winit::window::{Fullscreen, Window, WindowBuilder};
+
+const FORMAT: FileFormat = FileFormat {
+    name: "window geometry file",
+    magic: b"IPWG",
+    version: 1,
+};
+
+/// Restored window can't be smaller, so it can't become invisible.
+const MIN_SIZE: (u32, u32) = (160, 120);
+/// Part of window, in pixels, which must be on monitor, so user is able to drag window back.
+const VISIBLE_PART: i32 = 64;
+
+/// Window size, position and state, saved between sessions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WindowGeometry {
+    /// Inner size of window, when it's neither maximized nor fullscreen.
+    pub size: (u32, u32),
+    /// Outer position of window, when it's neither maximized nor fullscreen.
+    /// None lets OS place window.
+    pub position: Option<(i32, i32)>,
+    /// Name of monitor, containing window.
+    pub monitor: Option<String>,
+    pub maximized: bool,
+    pub fullscreen: bool,
+}
+
+impl Default for WindowGeometry {
+    fn default() -> Self {
+        Self {
+            size: (800, 600),
+            position: None,
+            monitor: None,
+            maximized: false,
+            fullscreen: false,
+        }
+    }
+}
+
+impl WindowGeometry {
+    /// Reads geometry from file at `path`, checking format header.
+    pub fn load(path: &Path) -> Result<Self, SettingsError> {
+        Ok(FORMAT.load(path)?)
+    }
+
+    /// Writes geometry to file at `path`, prefixed with format header.
+    pub fn save(&self, path: &Path) -> Result<(), SettingsError> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        Ok(FORMAT.save(self, path)?)
+    }
+
+    /// Remembers current window state. Size and position are remembered only while window is
+    /// neither maximized nor fullscreen, so they can be restored after leaving these states.
+    pub fn update(&mut self, window: &Window) {
+        self.monitor = window.current_monitor().name();
+        self.fullscreen = window.fullscreen().is_some();
+        self.maximized = !self.fullscreen && looks_maximized(window);
+        if self.fullscreen || self.maximized {
+            return;
+        }
+        let size = window.inner_size();
+        self.size = (size.width, size.height);
+        if let Ok(position) = window.outer_position() {
+            self.position = Some((position.x, position.y));
+        }
+    }
+
+    /// Builds window with this geometry.
+    /// If saved monitor is gone, window goes to primary monitor and OS chooses position.
+    /// Size is fit into monitor, and position is dropped if window would be out of monitor.
+    pub fn build<T>(
+        &self,
+        builder: WindowBuilder,
+        event_loop: &EventLoop<T>,
+        logger: &Logger,
+    ) -> Result<Window, OsError> {
+        let saved_monitor = self.monitor.as_ref().and_then(|name| {
+            event_loop
+                .available_monitors()
+                .find(|m| m.name().as_ref() == Some(name))
+        });
+        let monitor = match saved_monitor {
+            Some(monitor) => monitor,
+            None => {
+                if let Some(name) = &self.monitor {
+                    info!(logger, "Monitor {} is gone. Using primary monitor", name);
+                }
+                event_loop.primary_monitor()
+            }
+        };
+        let size = fit_size(self.size, monitor.size());
+        let position = match self.position {
+            Some(position) if on_monitor(position, &monitor) => Some(position),
+            Some(_) => {
+                info!(
+                    logger,
+                    "Saved window position is out of monitor. Dropping it"
+                );
+                None
+            }
+            None => None,
+        };
+
+        let window = builder
+            .with_inner_size(PhysicalSize::new(size.0, size.1))
+            .with_visible(false)
+            .build(event_loop)?;
+        // Window must be moved to its monitor before it's maximized or made fullscreen there.
+        if let Some((x, y)) = position {
+            window.set_outer_position(PhysicalPosition::new(x, y));
+        }
+        if self.fullscreen {
+            window.set_fullscreen(Some(Fullscreen::Borderless(monitor)));
+        } else if self.maximized {
+            window.set_maximized(true);
+        }
+        window.set_visible(true);
+        Ok(window)
+    }
+}
+
+/// Winit 0.22 can maximize window, but doesn't report whether it's maximized, and
+/// `WindowEvent` has no such event either. So maximized state is guessed: window is
+/// considered maximized, if it covers its monitor, except for taskbar, which may take
+/// part of monitor height. Window, resized by user to cover monitor, is saved as maximized.
+fn looks_maximized(window: &Window) -> bool {
+    let monitor = window.current_monitor();
+    match window.outer_position() {
+        Ok(position) => covers_monitor(
+            (position.x, position.y),
+            window.outer_size(),
+            (monitor.position().x, monitor.position().y),
+            monitor.size(),
+        ),
+        Err(_) => false,
+    }
+}
+
+/// Whether window covers monitor horizontally and the most of it vertically.
+/// Maximized window borders may be out of monitor.
+fn covers_monitor(
+    position: (i32, i32),
+    size: PhysicalSize<u32>,
+    monitor_position: (i32, i32),
+    monitor_size: PhysicalSize<u32>,
+) -> bool {
+    let right = position.0 + size.width as i32;
+    let monitor_right = monitor_position.0 + monitor_size.width as i32;
+    position.0 <= monitor_position.0
+        && right >= monitor_right
+        && size.height >= monitor_size.height * 3 / 4
+}
+
+fn fit_size(size: (u32, u32), monitor: PhysicalSize<u32>) -> (u32, u32) {
+    let width = size.0.min(monitor.width).max(MIN_SIZE.0);
+    let height = size.1.min(monitor.height).max(MIN_SIZE.1);
+    (width, height)
+}
+
+/// Top left part of window, where title bar usually is, is on monitor.
+fn on_monitor(position: (i32, i32), monitor: &MonitorHandle) -> bool {
+    let origin = monitor.position();
+    let size = monitor.size();
+    let (right, bottom) = (
+        origin.x + size.width as i32 - VISIBLE_PART,
+        origin.y + size.height as i32 - VISIBLE_PART,
+    );
+    (origin.x - VISIBLE_PART..=right).contains(&position.0)
+        && (origin.y..=bottom).contains(&position.1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::versioned_file::VersionedFileError;
+
+    #[test]
+    fn geometry_file_has_versioned_header() {
+        let path = std::env::temp_dir().join(format!("window_geometry_{}", std::process::id()));
+        let geometry = WindowGeometry {
+            position: Some((10, 20)),
+            maximized: true,
+            ..WindowGeometry::default()
+        };
+        geometry.save(&path).unwrap();
+        assert_eq!(WindowGeometry::load(&path).unwrap(), geometry);
+
+        // File of previous format has no header.
+        std::fs::write(&path, bincode::serialize(&geometry).unwrap()).unwrap();
+        let loaded = WindowGeometry::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            loaded,
+            Err(SettingsError::File(VersionedFileError::UnknownFormat(_)))
+        ));
+    }
+
+    #[test]
+    fn maximized_window_covers_monitor() {
+        let monitor = PhysicalSize::new(1920, 1080);
+        let taskbar_shortened = PhysicalSize::new(1936, 1048);
+        assert!(covers_monitor((-8, -8), taskbar_shortened, (0, 0), monitor));
+        assert!(covers_monitor(
+            (1912, -8),
+            taskbar_shortened,
+            (1920, 0),
+            monitor
+        ));
+        // Wide, but short window.
+        let wide = PhysicalSize::new(1920, 400);
+        assert!(!covers_monitor((0, 300), wide, (0, 0), monitor));
+    }
+}
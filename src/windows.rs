@@ -0,0 +1,60 @@
+use slog::{trace, Logger};
+use std::collections::HashMap;
+use winit::dpi::PhysicalSize;
+use winit::error::OsError;
+use winit::event_loop::EventLoopWindowTarget;
+use winit::window::{Window, WindowBuilder, WindowId};
+
+/// Windows of application: the main one, which lives until exit, and tool windows next to it.
+/// Only windows and input routing are per window: there is no presenter yet, so no window has
+/// a surface and tool windows stay blank. Presenter must get surface for every window it draws.
+pub struct Windows {
+    main: Window,
+    tools: HashMap<WindowId, Window>,
+    logger: Logger,
+}
+
+impl Windows {
+    pub fn new(main: Window, logger: Logger) -> Self {
+        Self {
+            main,
+            tools: HashMap::new(),
+            logger,
+        }
+    }
+
+    pub fn main(&self) -> &Window {
+        &self.main
+    }
+
+    pub fn is_main(&self, id: WindowId) -> bool {
+        self.main.id() == id
+    }
+
+    /// Opens tool window. It gets input, but nothing draws into it yet.
+    pub fn open<T>(
+        &mut self,
+        target: &EventLoopWindowTarget<T>,
+        title: &str,
+        size: (u32, u32),
+    ) -> Result<WindowId, OsError> {
+        let window = WindowBuilder::default()
+            .with_title(title)
+            .with_inner_size(PhysicalSize::new(size.0, size.1))
+            .build(target)?;
+        let id = window.id();
+        trace!(self.logger, "Opened tool window {:?}", id);
+        self.tools.insert(id, window);
+        Ok(id)
+    }
+
+    /// Closes tool window. Returns false if there is no such tool window.
+    /// The main window can't be closed, application must exit instead.
+    pub fn close(&mut self, id: WindowId) -> bool {
+        let closed = self.tools.remove(&id).is_some();
+        if closed {
+            trace!(self.logger, "Closed tool window {:?}", id);
+        }
+        closed
+    }
+}